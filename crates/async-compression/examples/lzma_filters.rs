@@ -82,9 +82,7 @@ async fn decompress(stdin: Stdin, filters: LzmaFilters, mut stdout: Stdout) -> R
 }
 async fn compress(mut stdin: Stdin, filters: LzmaFilters, stdout: Stdout) -> Result<Stdout> {
     let params = LzmaEncoderParams::Raw { filters };
-    let codec = compression_codecs::Xz2Encoder::try_from(params).expect("Could not create encoder");
-    let codec = compression_codecs::LzmaEncoder::from(codec);
-    let mut writer = async_compression::tokio::write::LzmaEncoder::with_codec(stdout, codec);
+    let mut writer = async_compression::tokio::write::LzmaEncoder::with_params(stdout, params)?;
 
     let mut buf = vec![];
     stdin.read_to_end(&mut buf).await?;
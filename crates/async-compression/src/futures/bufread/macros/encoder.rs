@@ -28,6 +28,30 @@ macro_rules! encoder {
                 }
             }
 
+            /// Returns statistics about the data this encoder has compressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Sets how flushing this encoder ends the data compressed so far, see
+            /// [`FlushMode`](crate::FlushMode) for the modes each algorithm supports.
+            ///
+            /// Returns an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported) if the
+            /// algorithm doesn't support `mode`.
+            pub fn set_flush_mode(&mut self, mode: crate::FlushMode) -> std::io::Result<()> {
+                self.inner.set_flush_mode(mode)
+            }
+
+            /// Resets this encoder to compress a new stream read from `reader`, returning the
+            /// previous reader.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this encoder are kept and its statistics start
+            /// over. Any data of the previous stream which hasn't been compressed yet is discarded.
+            pub fn reset(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(reader)
+            }
+
             /// Acquires a reference to the underlying reader that this encoder is wrapping.
             pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
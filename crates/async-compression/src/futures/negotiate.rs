@@ -0,0 +1,8 @@
+//! Encoders and decoders over [`futures-io`](futures_io) streams for HTTP content codings, either
+//! negotiated from an `Accept-Encoding` header or listed in a `Content-Encoding` header, see
+//! [`crate::negotiate`].
+
+use crate::generic::negotiate::impl_negotiate;
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+impl_negotiate!(futures);
@@ -0,0 +1,45 @@
+use crate::{
+    codecs::EncodeV2,
+    futures::bufread::{BufReader, Encoder as BufReadEncoder},
+    generic::read::impl_encoder,
+};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::{AsyncRead, AsyncWrite};
+use std::io::{IoSlice, Result};
+
+impl_encoder!();
+
+impl<R: AsyncRead, E: EncodeV2> AsyncRead for Encoder<R, E> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+impl<R: AsyncWrite, E> AsyncWrite for Encoder<R, E> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.get_pin_mut().poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_pin_mut().poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_pin_mut().poll_close(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        self.get_pin_mut().poll_write_vectored(cx, bufs)
+    }
+}
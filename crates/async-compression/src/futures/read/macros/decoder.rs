@@ -0,0 +1,180 @@
+macro_rules! decoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> @new { $($new:tt)* } $({ $($inherent_methods:tt)* })*) => {
+        pin_project_lite::pin_project! {
+            $(#[$attr])*
+            ///
+            /// This structure implements an [`AsyncRead`](futures_io::AsyncRead) interface and will
+            /// read compressed data from an underlying stream and emit a stream of uncompressed data.
+            /// The underlying stream is read through an internal buffer sized for the codec.
+            #[derive(Debug)]
+            pub struct $name<$inner> {
+                #[pin]
+                inner: crate::futures::read::Decoder<$inner, crate::codecs::$name>,
+            }
+        }
+
+        impl<$inner: futures_io::AsyncRead> $name<$inner> {
+            $($new)*
+
+            /// Creates a new decoder with the given codec, which will read compressed data from the given stream and
+            /// emit an uncompressed stream.
+            pub fn with_codec(read: $inner, codec: crate::codecs::$name) -> $name<$inner> {
+                $name {
+                    inner: crate::futures::read::Decoder::new(read, codec)
+                }
+            }
+
+            $($($inherent_methods)*)*
+        }
+
+        impl<$inner> $name<$inner> {
+            /// Configure multi-member/frame decoding, if enabled this will reset the decoder state
+            /// when reaching the end of a compressed member/frame and expect either EOF or another
+            /// compressed member/frame to follow it in the stream.
+            pub fn multiple_members(&mut self, enabled: bool) {
+                self.inner.multiple_members(enabled);
+            }
+
+            /// Limits the decompressed data to `limit` bytes in total, or removes the limit if `None`.
+            ///
+            /// Once the limit is reached, reading returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) instead of more data.
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            /// Limits the decompressed data to `ratio` times the compressed data consumed so far, or
+            /// removes the limit if `None`.
+            ///
+            /// Once the limit is reached, reading returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) instead of more data.
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            /// Limits the memory used to decode the streams started from now on, such as the window
+            /// announced by their header.
+            ///
+            /// Streams needing more memory fail with an error of kind
+            /// [`OutOfMemory`](std::io::ErrorKind::OutOfMemory). Returns an error if this decoder
+            /// can't enforce `limits`, or if every stream of its format needs more memory than
+            /// they allow.
+            pub fn set_limits(&mut self, limits: crate::DecoderLimits) -> std::io::Result<()> {
+                self.inner.set_limits(limits)
+            }
+
+            /// Returns statistics about the data this decoder has decompressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Resets this decoder to decode a new stream read from `reader`, returning the
+            /// previous reader.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this decoder are kept and its statistics start
+            /// over. Any data of the previous stream which hasn't been decoded yet is discarded.
+            pub fn reset(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(reader)
+            }
+
+            /// Acquires a reference to the underlying reader that this decoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying reader that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this decoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Acquires a pinned mutable reference to the underlying reader that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this decoder.
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
+                self.project().inner.get_pin_mut()
+            }
+
+            /// Consumes this decoder returning the underlying reader.
+            ///
+            /// Note that this may discard internal state of this decoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: futures_io::AsyncRead> futures_io::AsyncRead for $name<$inner> {
+            fn poll_read(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &mut [u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.project().inner.poll_read(cx, buf)
+            }
+        }
+
+        impl<$inner: futures_io::AsyncWrite> futures_io::AsyncWrite for $name<$inner> {
+            fn poll_write(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &[u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_pin_mut().poll_write(cx, buf)
+            }
+
+            fn poll_flush(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                self.get_pin_mut().poll_flush(cx)
+            }
+
+            fn poll_close(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                self.get_pin_mut().poll_close(cx)
+            }
+
+            fn poll_write_vectored(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                bufs: &[std::io::IoSlice<'_>]
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_pin_mut().poll_write_vectored(cx, bufs)
+            }
+        }
+
+        const _: () = {
+            use crate::core::util::{_assert_send, _assert_sync};
+            use core::pin::Pin;
+            use futures_io::AsyncRead;
+
+            _assert_send::<$name<Pin<Box<dyn AsyncRead + Send>>>>();
+            _assert_sync::<$name<Pin<Box<dyn AsyncRead + Sync>>>>();
+        };
+    };
+
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        decoder! {
+            $(#[$attr])*
+            $name<$inner> @new {
+                /// Creates a new decoder which will read compressed data from the given stream and
+                /// emit an uncompressed stream.
+                pub fn new(read: $inner) -> $name<$inner> {
+                    $name {
+                        inner: crate::futures::read::Decoder::new(read, crate::codecs::$name::new()),
+                    }
+                }
+            }
+            $({ $($inherent_methods)* })*
+        }
+    };
+}
@@ -0,0 +1,29 @@
+//! Types which operate over [`AsyncRead`](futures_io::AsyncRead) streams, both encoders and decoders for
+//! various formats.
+//!
+//! These read from the underlying stream through an internal buffer sized for the codec, so
+//! unlike the types of the [`bufread`](super::bufread) module they don't need the stream to be
+//! wrapped in a buffered reader first.
+
+#[macro_use]
+mod macros;
+mod generic;
+
+pub(crate) use generic::{Decoder, Encoder};
+
+algos!(futures::read<R>);
+
+decoder! {
+    /// A decoder which detects the compression format of its input from the first bytes, and
+    /// decodes it with the decoder for that format. Only the formats whose crate features are
+    /// enabled are detected, input in any other format is passed through unchanged.
+    ///
+    /// See [`AutoDecoder`](crate::codecs::AutoDecoder) for how each format is recognised.
+    AutoDecoder<R> {
+        /// Returns the detected format, or `None` if not enough input has been read to detect
+        /// it yet.
+        pub fn format(&self) -> Option<crate::auto::Format> {
+            self.inner.codec().format()
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Types which operate over seekable [`AsyncRead`] streams, decoding only
+//! the parts of the stream which are needed.
+
+use crate::generic::seek::{impl_xz_decoder, SeekRead};
+use futures_io::{AsyncRead, AsyncSeek};
+use std::{
+    io::{self, SeekFrom},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+struct Reader<'a, R>(Pin<&'a mut R>);
+
+impl<R: AsyncRead + AsyncSeek> SeekRead for Reader<'_, R> {
+    fn poll_seek(
+        &mut self,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+        _started: &mut bool,
+    ) -> Poll<io::Result<u64>> {
+        self.0.as_mut().poll_seek(cx, pos)
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.0.as_mut().poll_read(cx, buf)
+    }
+}
+
+impl_xz_decoder!();
+
+impl<R: AsyncRead + AsyncSeek> AsyncRead for XzDecoder<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let this = self.project();
+        let mut output = WriteBuffer::new_initialized(buf);
+        let res = this
+            .inner
+            .poll_read(cx, &mut Reader(this.reader), &mut output);
+        res.map_ok(|()| output.written_len())
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek> AsyncSeek for XzDecoder<R> {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.project();
+        this.inner.start_seek(pos);
+        this.inner.poll_complete_seek(cx, &mut Reader(this.reader))
+    }
+}
@@ -1,5 +1,5 @@
 macro_rules! decoder {
-    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> @new { $($new:tt)* } $({ $($inherent_methods:tt)* })*) => {
         pin_project_lite::pin_project! {
             $(#[$attr])*
             ///
@@ -13,13 +13,7 @@ macro_rules! decoder {
         }
 
         impl<$inner: futures_io::AsyncWrite> $name<$inner> {
-            /// Creates a new decoder which will take in compressed data and write it, uncompressed,
-            /// to the given stream.
-            pub fn new(read: $inner) -> $name<$inner> {
-                $name {
-                    inner: crate::futures::write::Decoder::new(read, crate::codecs::$name::new()),
-                }
-            }
+            $($new)*
 
             /// Creates a new decoder with the given codec, which will take in compressed data and write it, uncompressed,
             /// to the given stream.
@@ -33,6 +27,50 @@ macro_rules! decoder {
         }
 
         impl<$inner> $name<$inner> {
+            /// Limits the decompressed data to `limit` bytes in total, or removes the limit if `None`.
+            ///
+            /// Once the limit is reached, writing returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) and the data past it is discarded.
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            /// Limits the decompressed data to `ratio` times the compressed data consumed so far, or
+            /// removes the limit if `None`.
+            ///
+            /// Once the limit is reached, writing returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) and the data past it is discarded.
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            /// Limits the memory used to decode the streams started from now on, such as the window
+            /// announced by their header.
+            ///
+            /// Streams needing more memory fail with an error of kind
+            /// [`OutOfMemory`](std::io::ErrorKind::OutOfMemory). Returns an error if this decoder
+            /// can't enforce `limits`, or if every stream of its format needs more memory than
+            /// they allow.
+            pub fn set_limits(&mut self, limits: crate::DecoderLimits) -> std::io::Result<()> {
+                self.inner.set_limits(limits)
+            }
+
+            /// Returns statistics about the data this decoder has decompressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Resets this decoder to decode a new stream into `writer`, returning the previous
+            /// writer.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this decoder are kept and its statistics start
+            /// over. Any decoded data not yet written to the previous writer is discarded, so the
+            /// previous stream should be finished first.
+            pub fn reset(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(writer)
+            }
+
             /// Acquires a reference to the underlying reader that this decoder is wrapping.
             pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
@@ -128,5 +166,21 @@ macro_rules! decoder {
             _assert_send::<$name<Pin<Box<dyn AsyncWrite + Send>>>>();
             _assert_sync::<$name<Pin<Box<dyn AsyncWrite + Sync>>>>();
         };
-    }
+    };
+
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        decoder! {
+            $(#[$attr])*
+            $name<$inner> @new {
+                /// Creates a new decoder which will take in compressed data and write it, uncompressed,
+                /// to the given stream.
+                pub fn new(read: $inner) -> $name<$inner> {
+                    $name {
+                        inner: crate::futures::write::Decoder::new(read, crate::codecs::$name::new()),
+                    }
+                }
+            }
+            $({ $($inherent_methods)* })*
+        }
+    };
 }
@@ -30,6 +30,37 @@ macro_rules! encoder {
         }
 
         impl<$inner> $name<$inner> {
+            /// Returns statistics about the data this encoder has compressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Sets how flushing this encoder ends the data compressed so far, see
+            /// [`FlushMode`](crate::FlushMode) for the modes each algorithm supports.
+            ///
+            /// Returns an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported) if the
+            /// algorithm doesn't support `mode`.
+            pub fn set_flush_mode(&mut self, mode: crate::FlushMode) -> std::io::Result<()> {
+                self.inner.set_flush_mode(mode)
+            }
+
+            /// Sets when this encoder flushes on its own as data is written, see
+            /// [`AutoFlush`](crate::AutoFlush) for the available conditions.
+            pub fn set_auto_flush(&mut self, policy: crate::AutoFlush) {
+                self.inner.set_auto_flush(policy)
+            }
+
+            /// Resets this encoder to compress a new stream into `writer`, returning the previous
+            /// writer.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this encoder are kept and its statistics start
+            /// over. Any compressed data not yet written to the previous writer is discarded, so
+            /// the previous stream should be finished first.
+            pub fn reset(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(writer)
+            }
+
             /// Acquires a reference to the underlying writer that this encoder is wrapping.
             pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
@@ -0,0 +1,11 @@
+//! Types which write ZIP archives to [`AsyncWrite`] streams.
+
+use crate::{futures::write::BufWriter, generic::zip::impl_zip_writer};
+use futures_io::AsyncWrite;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+impl_zip_writer!(poll_close);
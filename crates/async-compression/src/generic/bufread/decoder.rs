@@ -1,6 +1,8 @@
+use super::pending;
 use crate::{
     codecs::DecodeV2,
     core::util::{PartialBuffer, WriteBuffer},
+    generic::{is_pending, limit::OutputLimit, stats::Stats},
 };
 use std::{io::Result, ops::ControlFlow, panic::AssertUnwindSafe};
 
@@ -17,6 +19,10 @@ enum State {
 pub struct Decoder {
     state: State,
     multiple_members: bool,
+    /// Whether the reader has returned EOF, after which no further members are looked for.
+    input_ended: bool,
+    limit: OutputLimit,
+    stats: Stats,
 }
 
 impl Default for Decoder {
@@ -24,6 +30,9 @@ impl Default for Decoder {
         Self {
             state: State::Decoding,
             multiple_members: false,
+            input_ended: false,
+            limit: OutputLimit::default(),
+            stats: Stats::default(),
         }
     }
 }
@@ -33,7 +42,54 @@ impl Decoder {
         self.multiple_members = enabled;
     }
 
+    pub fn max_output_size(&mut self, limit: Option<u64>) {
+        self.limit.max_size(limit);
+    }
+
+    pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+        self.limit.max_ratio(ratio);
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Gets ready for a new stream, keeping the settings.
+    pub fn reset(&mut self) {
+        self.state = State::Decoding;
+        self.input_ended = false;
+        self.limit.reset();
+        self.stats = Stats::default();
+    }
+
     pub fn do_poll_read(
+        &mut self,
+        output: &mut WriteBuffer<'_>,
+        decoder: &mut dyn DecodeV2,
+        input: &mut PartialBuffer<&[u8]>,
+        first: bool,
+    ) -> ControlFlow<Result<()>> {
+        let (read, written) = (input.written_len(), output.written_len());
+        let control_flow = self.decode(output, decoder, input, first);
+
+        let read = input.written_len() - read;
+        let checked = self.limit.check(read, output, written);
+        self.stats.transferred(read, output.written_len() - written);
+
+        match checked {
+            Ok(()) => control_flow,
+            Err(err) if output.written_len() > 0 => {
+                self.state = State::Error(AssertUnwindSafe(err));
+                ControlFlow::Break(Ok(()))
+            }
+            Err(err) => {
+                self.state = State::Done;
+                ControlFlow::Break(Err(err))
+            }
+        }
+    }
+
+    fn decode(
         &mut self,
         output: &mut WriteBuffer<'_>,
         decoder: &mut dyn DecodeV2,
@@ -46,12 +102,13 @@ impl Decoder {
                     if input.unwritten().is_empty() && !first {
                         // Avoid attempting to reinitialise the decoder if the
                         // reader has returned EOF.
-                        self.multiple_members = false;
+                        self.input_ended = true;
 
                         State::Flushing
                     } else {
-                        match decoder.decode(input, output) {
+                        match self.stats.time(|| decoder.decode(input, output)) {
                             Ok(true) => State::Flushing,
+                            Err(err) if is_pending(&err) => return pending(output, err),
                             // ignore the first error, occurs when input is empty
                             // but we need to run decode to flush
                             Err(err) if !first => {
@@ -69,9 +126,10 @@ impl Decoder {
                 }
 
                 State::Flushing => {
-                    match decoder.finish(output) {
+                    match self.stats.time(|| decoder.finish(output)) {
                         Ok(true) => {
-                            if self.multiple_members {
+                            self.stats.member_done();
+                            if self.multiple_members && !self.input_ended {
                                 if let Err(err) = decoder.reinit() {
                                     self.state = State::Error(AssertUnwindSafe(err));
                                     if output.written_len() > 0 {
@@ -90,6 +148,7 @@ impl Decoder {
                             }
                         }
                         Ok(false) => State::Flushing,
+                        Err(err) if is_pending(&err) => return pending(output, err),
                         Err(err) => {
                             self.state = State::Error(AssertUnwindSafe(err));
                             if output.written_len() > 0 {
@@ -138,7 +197,7 @@ impl Decoder {
 
 macro_rules! impl_decoder {
     () => {
-        use crate::generic::bufread::Decoder as GenericDecoder;
+        use crate::generic::bufread::{poll_result, Decoder as GenericDecoder};
 
         use std::{ops::ControlFlow, task::ready};
 
@@ -181,9 +240,53 @@ macro_rules! impl_decoder {
                 self.reader
             }
 
+            pub fn codec(&self) -> &D {
+                &self.decoder
+            }
+
             pub fn multiple_members(&mut self, enabled: bool) {
                 self.inner.multiple_members(enabled);
             }
+
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            pub fn set_limits(
+                &mut self,
+                limits: crate::codecs::DecoderLimits,
+            ) -> std::io::Result<()>
+            where
+                D: DecodeV2,
+            {
+                self.decoder.set_limits(limits)
+            }
+
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            pub fn reset(&mut self, reader: R) -> std::io::Result<R>
+            where
+                D: DecodeV2,
+            {
+                self.reset_codec()?;
+                Ok(std::mem::replace(&mut self.reader, reader))
+            }
+
+            /// Resets the decoder for a new stream from the same reader.
+            pub(crate) fn reset_codec(&mut self) -> std::io::Result<()>
+            where
+                D: DecodeV2,
+            {
+                self.decoder.reset()?;
+                self.inner.reset();
+                Ok(())
+            }
         }
 
         fn do_poll_read(
@@ -193,10 +296,11 @@ macro_rules! impl_decoder {
             cx: &mut Context<'_>,
             output: &mut WriteBuffer<'_>,
         ) -> Poll<Result<()>> {
+            decoder.register_waker(cx.waker());
             if let ControlFlow::Break(res) =
                 inner.do_poll_read(output, decoder, &mut PartialBuffer::new(&[][..]), true)
             {
-                return Poll::Ready(res);
+                return poll_result(res);
             }
 
             loop {
@@ -212,7 +316,7 @@ macro_rules! impl_decoder {
                 reader.as_mut().consume(bytes_read);
 
                 if let ControlFlow::Break(res) = control_flow {
-                    break Poll::Ready(res);
+                    break poll_result(res);
                 }
             }
         }
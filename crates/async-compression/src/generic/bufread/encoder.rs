@@ -1,6 +1,8 @@
+use super::pending;
 use crate::{
     codecs::EncodeV2,
     core::util::{PartialBuffer, WriteBuffer},
+    generic::{is_pending, stats::Stats},
 };
 use std::{io::Result, ops::ControlFlow, panic::AssertUnwindSafe};
 
@@ -16,23 +18,49 @@ enum State {
 #[derive(Debug)]
 pub struct Encoder {
     state: State,
+    stats: Stats,
 }
 
 impl Default for Encoder {
     fn default() -> Self {
         Self {
             state: State::Encoding(0),
+            stats: Stats::default(),
         }
     }
 }
 
 impl Encoder {
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Gets ready for a new stream.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
     /// `input` - should be `None` if `Poll::Pending`.
     pub fn do_poll_read(
         &mut self,
         output: &mut WriteBuffer<'_>,
         encoder: &mut dyn EncodeV2,
         mut input: Option<&mut PartialBuffer<&[u8]>>,
+    ) -> ControlFlow<Result<()>> {
+        let read = input.as_ref().map_or(0, |input| input.written_len());
+        let written = output.written_len();
+        let control_flow = self.encode(output, encoder, input.as_deref_mut());
+
+        let read = input.map_or(0, |input| input.written_len()) - read;
+        self.stats.transferred(read, output.written_len() - written);
+        control_flow
+    }
+
+    fn encode(
+        &mut self,
+        output: &mut WriteBuffer<'_>,
+        encoder: &mut dyn EncodeV2,
+        mut input: Option<&mut PartialBuffer<&[u8]>>,
     ) -> ControlFlow<Result<()>> {
         loop {
             self.state = match &mut self.state {
@@ -53,7 +81,10 @@ impl Encoder {
                         if input.unwritten().is_empty() {
                             State::Finishing
                         } else {
-                            if let Err(err) = encoder.encode(input, output) {
+                            if let Err(err) = self.stats.time(|| encoder.encode(input, output)) {
+                                if is_pending(&err) {
+                                    return pending(output, err);
+                                }
                                 self.state = State::Error(AssertUnwindSafe(err));
                                 if output.written_len() > 0 {
                                     return ControlFlow::Break(Ok(()));
@@ -70,14 +101,16 @@ impl Encoder {
                     }
                 },
 
-                State::Flushing => match encoder.flush(output) {
+                State::Flushing => match self.stats.time(|| encoder.flush(output)) {
                     Ok(true) => {
+                        self.stats.flushed();
                         self.state = State::Encoding(0);
 
                         // Poll for more data
                         break;
                     }
                     Ok(false) => State::Flushing,
+                    Err(err) if is_pending(&err) => return pending(output, err),
                     Err(err) => {
                         self.state = State::Error(AssertUnwindSafe(err));
                         if output.written_len() > 0 {
@@ -88,9 +121,13 @@ impl Encoder {
                     }
                 },
 
-                State::Finishing => match encoder.finish(output) {
-                    Ok(true) => State::Done,
+                State::Finishing => match self.stats.time(|| encoder.finish(output)) {
+                    Ok(true) => {
+                        self.stats.member_done();
+                        State::Done
+                    }
                     Ok(false) => State::Finishing,
+                    Err(err) if is_pending(&err) => return pending(output, err),
                     Err(err) => {
                         self.state = State::Error(AssertUnwindSafe(err));
                         if output.written_len() > 0 {
@@ -126,7 +163,7 @@ impl Encoder {
 
 macro_rules! impl_encoder {
     () => {
-        use crate::generic::bufread::Encoder as GenericEncoder;
+        use crate::generic::bufread::{poll_result, Encoder as GenericEncoder};
 
         use std::{ops::ControlFlow, task::ready};
 
@@ -176,6 +213,35 @@ macro_rules! impl_encoder {
             pub fn into_inner(self) -> R {
                 self.reader
             }
+
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            pub fn set_flush_mode(&mut self, mode: crate::codecs::FlushMode) -> std::io::Result<()>
+            where
+                E: EncodeV2,
+            {
+                self.encoder.set_flush_mode(mode)
+            }
+
+            pub fn reset(&mut self, reader: R) -> std::io::Result<R>
+            where
+                E: EncodeV2,
+            {
+                self.reset_codec()?;
+                Ok(std::mem::replace(&mut self.reader, reader))
+            }
+
+            /// Resets the encoder for a new stream from the same reader.
+            pub(crate) fn reset_codec(&mut self) -> std::io::Result<()>
+            where
+                E: EncodeV2,
+            {
+                self.encoder.reinit()?;
+                self.inner.reset();
+                Ok(())
+            }
         }
 
         fn do_poll_read(
@@ -185,8 +251,9 @@ macro_rules! impl_encoder {
             cx: &mut Context<'_>,
             output: &mut WriteBuffer<'_>,
         ) -> Poll<Result<()>> {
+            encoder.register_waker(cx.waker());
             if let ControlFlow::Break(res) = inner.do_poll_read(output, encoder, None) {
-                return Poll::Ready(res);
+                return poll_result(res);
             }
 
             loop {
@@ -204,7 +271,7 @@ macro_rules! impl_encoder {
                 }
 
                 if let ControlFlow::Break(res) = control_flow {
-                    break Poll::Ready(res);
+                    break poll_result(res);
                 }
 
                 if is_pending {
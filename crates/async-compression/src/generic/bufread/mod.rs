@@ -3,3 +3,24 @@ mod encoder;
 
 pub(crate) use decoder::*;
 pub(crate) use encoder::*;
+
+use crate::{core::util::WriteBuffer, generic::is_pending};
+use std::{io::Result, ops::ControlFlow, task::Poll};
+
+/// Stops at a codec waiting for its worker threads, returning the output written so far or, if
+/// there is none, the error for [`poll_result`] to turn into `Poll::Pending`.
+fn pending(output: &WriteBuffer<'_>, err: std::io::Error) -> ControlFlow<Result<()>> {
+    if output.written_len() > 0 {
+        ControlFlow::Break(Ok(()))
+    } else {
+        ControlFlow::Break(Err(err))
+    }
+}
+
+/// Turns the result of a state machine into the result of a poll.
+pub(crate) fn poll_result(res: Result<()>) -> Poll<Result<()>> {
+    match res {
+        Err(err) if is_pending(&err) => Poll::Pending,
+        res => Poll::Ready(res),
+    }
+}
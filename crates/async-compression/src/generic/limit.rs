@@ -0,0 +1,81 @@
+use crate::core::util::WriteBuffer;
+use std::io::{Error, ErrorKind, Result};
+
+/// Limits on the total output of a decoder, and on its size relative to the input consumed.
+#[derive(Debug, Default)]
+pub struct OutputLimit {
+    max_size: Option<u64>,
+    max_ratio: Option<u64>,
+    total_in: u64,
+    total_out: u64,
+    exceeded: Option<String>,
+}
+
+impl OutputLimit {
+    pub fn max_size(&mut self, limit: Option<u64>) {
+        self.max_size = limit;
+    }
+
+    pub fn max_ratio(&mut self, ratio: Option<u64>) {
+        self.max_ratio = ratio;
+    }
+
+    /// Starts counting a new stream, keeping the limits.
+    pub fn reset(&mut self) {
+        self.total_in = 0;
+        self.total_out = 0;
+        self.exceeded = None;
+    }
+
+    /// Returns the error for the limit which was exceeded, if any.
+    pub fn error(&self) -> Option<Error> {
+        let message = self.exceeded.clone()?;
+        Some(Error::new(ErrorKind::FileTooLarge, message))
+    }
+
+    /// Accounts for `read` more bytes of input and for the bytes written to `output` past its
+    /// first `written` bytes, discarding those which exceed the limits and returning an error.
+    pub fn check(
+        &mut self,
+        read: usize,
+        output: &mut WriteBuffer<'_>,
+        written: usize,
+    ) -> Result<()> {
+        self.total_in += read as u64;
+
+        let new = (output.written_len() - written) as u64;
+        let ratio_limit = self
+            .max_ratio
+            .map(|ratio| ratio.saturating_mul(self.total_in));
+        let size_limited = self
+            .max_size
+            .is_some_and(|max_size| ratio_limit.is_none_or(|limit| max_size <= limit));
+        let limit = if size_limited {
+            self.max_size
+        } else {
+            ratio_limit
+        };
+        let limit = limit.unwrap_or(u64::MAX);
+
+        if self.total_out + new > limit {
+            // Less than `new`, so it fits in an `usize`.
+            let kept = limit.saturating_sub(self.total_out);
+            // Safety: these bytes have been written, so are initialized.
+            unsafe { output.set_written_and_initialized_len(written + kept as usize) };
+            self.total_out += kept;
+            self.exceeded = Some(if size_limited {
+                format!("decompressed data is longer than the limit of {limit} bytes")
+            } else {
+                format!(
+                    "decompressed data is more than {} times longer than the compressed data",
+                    self.max_ratio.unwrap_or_default()
+                )
+            });
+
+            return Err(self.error().unwrap());
+        }
+
+        self.total_out += new;
+        Ok(())
+    }
+}
@@ -1,2 +1,26 @@
 pub(crate) mod bufread;
+pub(crate) mod limit;
+pub(crate) mod negotiate;
+pub(crate) mod read;
+#[cfg(feature = "xz")]
+pub(crate) mod seek;
+pub(crate) mod stats;
 pub(crate) mod write;
+#[cfg(feature = "zip")]
+pub(crate) mod zip;
+
+use std::{io, task::Poll};
+
+/// Whether an error returned by a codec only means that it is waiting for work it handed to other
+/// threads, in which case it wakes the waker registered with it once it can continue.
+pub(crate) fn is_pending(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::WouldBlock
+}
+
+/// Turns the error of a codec waiting for its worker threads into `Poll::Pending`.
+pub(crate) fn poll_codec<T>(res: io::Result<T>) -> Poll<io::Result<T>> {
+    match res {
+        Err(err) if is_pending(&err) => Poll::Pending,
+        res => Poll::Ready(res),
+    }
+}
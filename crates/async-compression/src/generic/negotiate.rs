@@ -0,0 +1,172 @@
+macro_rules! impl_negotiate {
+    ($($mod:ident)::+) => {
+        pub use crate::negotiate::{
+            negotiate, negotiate_default, parse_content_encoding, ContentEncoding,
+        };
+
+        use crate::core::Level;
+        use std::{io, pin::Pin};
+
+        /// Wraps `writer` in an encoder for `encoding`, which compresses data written to it with
+        /// the given level. [`Identity`](ContentEncoding::Identity) returns `writer` itself.
+        pub fn write_encoder<'a, W>(
+            encoding: ContentEncoding,
+            writer: W,
+            level: Level,
+        ) -> Pin<Box<dyn AsyncWrite + Send + 'a>>
+        where
+            W: AsyncWrite + Send + 'a,
+        {
+            let _ = level;
+            match encoding {
+                ContentEncoding::Identity => Box::pin(writer),
+                #[cfg(feature = "brotli")]
+                ContentEncoding::Brotli => {
+                    Box::pin(crate::$($mod::)+write::BrotliEncoder::with_quality(writer, level))
+                }
+                #[cfg(feature = "zlib")]
+                ContentEncoding::Deflate => {
+                    Box::pin(crate::$($mod::)+write::ZlibEncoder::with_quality(writer, level))
+                }
+                #[cfg(feature = "gzip")]
+                ContentEncoding::Gzip => {
+                    Box::pin(crate::$($mod::)+write::GzipEncoder::with_quality(writer, level))
+                }
+                #[cfg(feature = "zstd")]
+                ContentEncoding::Zstd => {
+                    Box::pin(crate::$($mod::)+write::ZstdEncoder::with_quality(writer, level))
+                }
+            }
+        }
+
+        /// Wraps `reader` in an encoder for `encoding`, which reads uncompressed data from it and
+        /// returns it compressed with the given level. [`Identity`](ContentEncoding::Identity)
+        /// returns `reader` itself.
+        pub fn bufread_encoder<'a, R>(
+            encoding: ContentEncoding,
+            reader: R,
+            level: Level,
+        ) -> Pin<Box<dyn AsyncRead + Send + 'a>>
+        where
+            R: AsyncBufRead + Send + 'a,
+        {
+            let _ = level;
+            match encoding {
+                ContentEncoding::Identity => Box::pin(reader),
+                #[cfg(feature = "brotli")]
+                ContentEncoding::Brotli => {
+                    Box::pin(crate::$($mod::)+bufread::BrotliEncoder::with_quality(reader, level))
+                }
+                #[cfg(feature = "zlib")]
+                ContentEncoding::Deflate => {
+                    Box::pin(crate::$($mod::)+bufread::ZlibEncoder::with_quality(reader, level))
+                }
+                #[cfg(feature = "gzip")]
+                ContentEncoding::Gzip => {
+                    Box::pin(crate::$($mod::)+bufread::GzipEncoder::with_quality(reader, level))
+                }
+                #[cfg(feature = "zstd")]
+                ContentEncoding::Zstd => {
+                    Box::pin(crate::$($mod::)+bufread::ZstdEncoder::with_quality(reader, level))
+                }
+            }
+        }
+
+        /// Chooses the content coding of a response with [`negotiate`], and wraps `writer` in an
+        /// encoder for it. The chosen coding is returned for the `Content-Encoding` header, see
+        /// [`ContentEncoding::as_str`].
+        ///
+        /// Returns `None` if none of the codings are acceptable.
+        pub fn negotiate_write<'a, W>(
+            accept_encoding: &str,
+            preference: &[ContentEncoding],
+            writer: W,
+            level: Level,
+        ) -> Option<(ContentEncoding, Pin<Box<dyn AsyncWrite + Send + 'a>>)>
+        where
+            W: AsyncWrite + Send + 'a,
+        {
+            let encoding = negotiate(accept_encoding, preference)?;
+            Some((encoding, write_encoder(encoding, writer, level)))
+        }
+
+        /// Chooses the content coding of a response with [`negotiate`], and wraps `reader` in an
+        /// encoder for it. The chosen coding is returned for the `Content-Encoding` header, see
+        /// [`ContentEncoding::as_str`].
+        ///
+        /// Returns `None` if none of the codings are acceptable.
+        pub fn negotiate_bufread<'a, R>(
+            accept_encoding: &str,
+            preference: &[ContentEncoding],
+            reader: R,
+            level: Level,
+        ) -> Option<(ContentEncoding, Pin<Box<dyn AsyncRead + Send + 'a>>)>
+        where
+            R: AsyncBufRead + Send + 'a,
+        {
+            let encoding = negotiate(accept_encoding, preference)?;
+            Some((encoding, bufread_encoder(encoding, reader, level)))
+        }
+
+        /// Wraps `reader` in a decoder for `encoding`, which reads data compressed with it and
+        /// returns it decompressed. [`Identity`](ContentEncoding::Identity) returns `reader`
+        /// itself.
+        pub fn bufread_decoder<'a, R>(
+            encoding: ContentEncoding,
+            reader: R,
+        ) -> Pin<Box<dyn AsyncRead + Send + 'a>>
+        where
+            R: AsyncBufRead + Send + 'a,
+        {
+            match encoding {
+                ContentEncoding::Identity => Box::pin(reader),
+                #[cfg(feature = "brotli")]
+                ContentEncoding::Brotli => {
+                    Box::pin(crate::$($mod::)+bufread::BrotliDecoder::new(reader))
+                }
+                #[cfg(feature = "zlib")]
+                ContentEncoding::Deflate => {
+                    Box::pin(crate::$($mod::)+bufread::ZlibDecoder::new(reader))
+                }
+                #[cfg(feature = "gzip")]
+                ContentEncoding::Gzip => {
+                    Box::pin(crate::$($mod::)+bufread::GzipDecoder::new(reader))
+                }
+                #[cfg(feature = "zstd")]
+                ContentEncoding::Zstd => {
+                    Box::pin(crate::$($mod::)+bufread::ZstdDecoder::new(reader))
+                }
+            }
+        }
+
+        /// Wraps `reader` in decoders undoing the codings listed in a `Content-Encoding` header,
+        /// such as `deflate, gzip`, in the reverse of the order they were applied.
+        ///
+        /// # Errors
+        ///
+        /// Returns an [`Unsupported`](io::ErrorKind::Unsupported) error if any of the codings is
+        /// unknown or its crate feature is not enabled, see [`parse_content_encoding`].
+        pub fn content_decoder<'a, R>(
+            content_encoding: &str,
+            reader: R,
+        ) -> io::Result<Pin<Box<dyn AsyncRead + Send + 'a>>>
+        where
+            R: AsyncBufRead + Send + 'a,
+        {
+            let mut codings = parse_content_encoding(content_encoding)?;
+
+            let Some(last) = codings.pop() else {
+                return Ok(Box::pin(reader));
+            };
+
+            let mut decoder = bufread_decoder(last, reader);
+            while let Some(coding) = codings.pop() {
+                let reader = crate::$($mod::)+bufread::BufReader::new(decoder);
+                decoder = bufread_decoder(coding, reader);
+            }
+
+            Ok(decoder)
+        }
+    };
+}
+pub(crate) use impl_negotiate;
@@ -0,0 +1,80 @@
+macro_rules! impl_decoder {
+    () => {
+        use pin_project_lite::pin_project;
+
+        pin_project! {
+            #[derive(Debug)]
+            pub struct Decoder<R, D> {
+                #[pin]
+                inner: BufReadDecoder<BufReader<R>, D>,
+            }
+        }
+
+        impl<R: AsyncRead, D: DecodeV2> Decoder<R, D> {
+            pub fn new(reader: R, decoder: D) -> Self {
+                let reader = BufReader::with_capacity(decoder.input_size_hint(), reader);
+                Self {
+                    inner: BufReadDecoder::new(reader, decoder),
+                }
+            }
+        }
+
+        impl<R, D> Decoder<R, D> {
+            pub fn get_ref(&self) -> &R {
+                self.inner.get_ref().get_ref()
+            }
+
+            pub fn get_mut(&mut self) -> &mut R {
+                self.inner.get_mut().get_mut()
+            }
+
+            pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+                self.project().inner.get_pin_mut().get_pin_mut()
+            }
+
+            pub fn into_inner(self) -> R {
+                self.inner.into_inner().into_inner()
+            }
+
+            pub fn codec(&self) -> &D {
+                self.inner.codec()
+            }
+
+            pub fn multiple_members(&mut self, enabled: bool) {
+                self.inner.multiple_members(enabled);
+            }
+
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            pub fn set_limits(
+                &mut self,
+                limits: crate::codecs::DecoderLimits,
+            ) -> std::io::Result<()>
+            where
+                D: DecodeV2,
+            {
+                self.inner.set_limits(limits)
+            }
+
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            pub fn reset(&mut self, reader: R) -> std::io::Result<R>
+            where
+                D: DecodeV2,
+            {
+                self.inner.reset_codec()?;
+                // The buffer is kept for the new reader.
+                Ok(self.inner.get_mut().replace_inner(reader))
+            }
+        }
+    };
+}
+pub(crate) use impl_decoder;
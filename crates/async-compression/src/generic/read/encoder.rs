@@ -0,0 +1,69 @@
+macro_rules! impl_encoder {
+    () => {
+        use pin_project_lite::pin_project;
+
+        pin_project! {
+            #[derive(Debug)]
+            pub struct Encoder<R, E> {
+                #[pin]
+                inner: BufReadEncoder<BufReader<R>, E>,
+            }
+        }
+
+        impl<R: AsyncRead, E: EncodeV2> Encoder<R, E> {
+            pub fn new(reader: R, encoder: E) -> Self {
+                let reader = BufReader::with_capacity(encoder.input_size_hint(), reader);
+                Self {
+                    inner: BufReadEncoder::new(reader, encoder),
+                }
+            }
+
+            pub fn with_capacity(reader: R, encoder: E, _cap: usize) -> Self {
+                Self::new(reader, encoder)
+            }
+        }
+
+        impl<R, E> Encoder<R, E> {
+            pub fn get_ref(&self) -> &R {
+                self.inner.get_ref().get_ref()
+            }
+
+            pub fn get_mut(&mut self) -> &mut R {
+                self.inner.get_mut().get_mut()
+            }
+
+            pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+                self.project().inner.get_pin_mut().get_pin_mut()
+            }
+
+            pub(crate) fn get_encoder_ref(&self) -> &E {
+                self.inner.get_encoder_ref()
+            }
+
+            pub fn into_inner(self) -> R {
+                self.inner.into_inner().into_inner()
+            }
+
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            pub fn set_flush_mode(&mut self, mode: crate::codecs::FlushMode) -> std::io::Result<()>
+            where
+                E: EncodeV2,
+            {
+                self.inner.set_flush_mode(mode)
+            }
+
+            pub fn reset(&mut self, reader: R) -> std::io::Result<R>
+            where
+                E: EncodeV2,
+            {
+                self.inner.reset_codec()?;
+                // The buffer is kept for the new reader.
+                Ok(self.inner.get_mut().replace_inner(reader))
+            }
+        }
+    };
+}
+pub(crate) use impl_encoder;
@@ -0,0 +1,5 @@
+mod decoder;
+mod encoder;
+
+pub(crate) use decoder::*;
+pub(crate) use encoder::*;
@@ -0,0 +1,433 @@
+use crate::{
+    codecs::{
+        xz::{XzBlock, XzBlockDecoder, XzIndex, XzIndexParser},
+        DecodeV2,
+    },
+    core::util::{PartialBuffer, WriteBuffer},
+};
+use std::{
+    convert::TryInto,
+    io::{self, SeekFrom},
+    task::{ready, Context, Poll},
+};
+
+/// Size of the buffer compressed data is read into.
+const BUFFER_SIZE: usize = 32 * 1024;
+
+/// The operations needed from a seekable reader, implemented by each runtime.
+pub(crate) trait SeekRead {
+    /// Seeks to `pos`, `started` records whether a seek is already in progress between polls.
+    fn poll_seek(
+        &mut self,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+        started: &mut bool,
+    ) -> Poll<io::Result<u64>>;
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+}
+
+fn poll_seek(
+    reader: &mut dyn SeekRead,
+    cx: &mut Context<'_>,
+    pos: SeekFrom,
+    started: &mut bool,
+) -> Poll<io::Result<u64>> {
+    let result = ready!(reader.poll_seek(cx, pos, started));
+    *started = false;
+    Poll::Ready(result)
+}
+
+#[derive(Debug)]
+enum State {
+    /// Finding the size of the file.
+    Size { started: bool },
+    /// Seeking to the next part of the index.
+    IndexSeek {
+        parser: XzIndexParser,
+        started: bool,
+    },
+    /// Reading the next part of the index.
+    IndexRead {
+        parser: XzIndexParser,
+        buffer: Vec<u8>,
+        filled: usize,
+    },
+    /// The index has been read, no block is being decoded.
+    Idle,
+    /// Seeking to the start of the current block.
+    BlockSeek { started: bool },
+    /// Decoding the current block.
+    BlockRead,
+}
+
+#[derive(Debug)]
+struct Block {
+    block: XzBlock,
+    decoder: XzBlockDecoder,
+    /// Offset in the decompressed output of the next byte the decoder will produce.
+    decoded: u64,
+    /// Compressed bytes of the block which have not been read yet.
+    remaining: u64,
+}
+
+impl Block {
+    /// Whether reading at `position` can continue with this block, including when the block has
+    /// been completely output but not yet verified.
+    fn contains(&self, position: u64) -> bool {
+        let end = self.block.uncompressed_offset() + self.block.uncompressed_size();
+        self.decoded <= position && position <= end
+    }
+}
+
+/// Random access decoding of an xz file, decoding only the block containing the position read.
+#[derive(Debug)]
+pub struct XzSeekDecoder {
+    mem_limit: u64,
+    state: State,
+    index: Option<XzIndex>,
+    block: Option<Block>,
+    position: u64,
+    seek: Option<SeekFrom>,
+    buffer: Box<[u8]>,
+    /// Range of `buffer` which has been read but not yet decoded.
+    input: (usize, usize),
+    /// Output decoded in front of the position being read is discarded into this buffer.
+    scratch: Box<[u8]>,
+}
+
+impl XzSeekDecoder {
+    pub fn new(mem_limit: u64) -> Self {
+        Self {
+            mem_limit,
+            state: State::Size { started: false },
+            index: None,
+            block: None,
+            position: 0,
+            seek: None,
+            buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            input: (0, 0),
+            scratch: vec![0; BUFFER_SIZE].into_boxed_slice(),
+        }
+    }
+
+    pub fn index(&self) -> Option<&XzIndex> {
+        self.index.as_ref()
+    }
+
+    pub fn poll_index(
+        &mut self,
+        cx: &mut Context<'_>,
+        reader: &mut dyn SeekRead,
+    ) -> Poll<io::Result<&XzIndex>> {
+        loop {
+            self.state = match &mut self.state {
+                State::Size { started } => {
+                    let size = ready!(poll_seek(reader, cx, SeekFrom::End(0), started))?;
+                    State::IndexSeek {
+                        parser: XzIndexParser::new(size),
+                        started: false,
+                    }
+                }
+
+                State::IndexSeek { parser, started } => {
+                    let (offset, len) = parser.next_read()?.unwrap();
+                    ready!(poll_seek(reader, cx, SeekFrom::Start(offset), started))?;
+
+                    let parser = std::mem::replace(parser, XzIndexParser::new(0));
+                    State::IndexRead {
+                        parser,
+                        buffer: vec![0; len],
+                        filled: 0,
+                    }
+                }
+
+                State::IndexRead {
+                    parser,
+                    buffer,
+                    filled,
+                } => {
+                    while *filled < buffer.len() {
+                        match ready!(reader.poll_read(cx, &mut buffer[*filled..]))? {
+                            0 => return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into())),
+                            read => *filled += read,
+                        }
+                    }
+
+                    parser.feed(buffer)?;
+                    let mut parser = std::mem::replace(parser, XzIndexParser::new(0));
+                    if parser.next_read()?.is_some() {
+                        State::IndexSeek {
+                            parser,
+                            started: false,
+                        }
+                    } else {
+                        self.index = Some(parser.finish()?);
+                        State::Idle
+                    }
+                }
+
+                State::Idle | State::BlockSeek { .. } | State::BlockRead => {
+                    return Poll::Ready(Ok(self.index.as_ref().unwrap()));
+                }
+            };
+        }
+    }
+
+    pub fn start_seek(&mut self, pos: SeekFrom) {
+        self.seek = Some(pos);
+    }
+
+    pub fn poll_complete_seek(
+        &mut self,
+        cx: &mut Context<'_>,
+        reader: &mut dyn SeekRead,
+    ) -> Poll<io::Result<u64>> {
+        let size = ready!(self.poll_index(cx, reader))?.uncompressed_size();
+
+        let position = match self.seek.take() {
+            None => return Poll::Ready(Ok(self.position)),
+            Some(SeekFrom::Start(offset)) => Some(offset),
+            Some(SeekFrom::Current(offset)) => self.position.checked_add_signed(offset),
+            Some(SeekFrom::End(offset)) => size.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Poll::Ready(Ok(self.position))
+    }
+
+    pub fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+        reader: &mut dyn SeekRead,
+        output: &mut WriteBuffer<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let position = self.position;
+            let next = ready!(self.poll_index(cx, reader))?
+                .block_at(position)
+                .copied();
+
+            if !self
+                .block
+                .as_ref()
+                .is_some_and(|block| block.contains(self.position))
+            {
+                let Some(next) = next else {
+                    // Reading at or past the end of the file.
+                    return Poll::Ready(Ok(()));
+                };
+                self.block = Some(Block {
+                    block: next,
+                    decoder: XzBlockDecoder::with_memlimit(&next, self.mem_limit),
+                    decoded: next.uncompressed_offset(),
+                    remaining: next.compressed_size(),
+                });
+                self.input = (0, 0);
+                self.state = State::BlockSeek { started: false };
+            }
+
+            ready!(self.poll_read_block(cx, reader, output))?;
+
+            // Only go on to the next block if the current one ended without producing any
+            // output, returning nothing would signal the end of the file.
+            if output.written_len() > 0 || output.has_no_spare_space() {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+
+    /// Decodes the current block until it ends or some output has been produced.
+    fn poll_read_block(
+        &mut self,
+        cx: &mut Context<'_>,
+        reader: &mut dyn SeekRead,
+        output: &mut WriteBuffer<'_>,
+    ) -> Poll<io::Result<()>> {
+        let block = self.block.as_mut().unwrap();
+
+        loop {
+            match &mut self.state {
+                State::BlockSeek { started } => {
+                    let offset = SeekFrom::Start(block.block.compressed_offset());
+                    ready!(poll_seek(reader, cx, offset, started))?;
+                    self.state = State::BlockRead;
+                }
+
+                State::BlockRead => {
+                    if self.input.0 == self.input.1 && block.remaining > 0 {
+                        let len = block.remaining.try_into().unwrap_or(usize::MAX);
+                        let len = len.min(self.buffer.len());
+                        match reader.poll_read(cx, &mut self.buffer[..len]) {
+                            Poll::Pending if output.written_len() == 0 => return Poll::Pending,
+                            Poll::Pending => return Poll::Ready(Ok(())),
+                            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                            Poll::Ready(Ok(0)) => {
+                                return Poll::Ready(Err(io::Error::new(
+                                    io::ErrorKind::UnexpectedEof,
+                                    "xz block is truncated",
+                                )))
+                            }
+                            Poll::Ready(Ok(read)) => {
+                                self.input = (0, read);
+                                block.remaining -= read as u64;
+                            }
+                        }
+                    }
+
+                    let mut input = PartialBuffer::new(&self.buffer[self.input.0..self.input.1]);
+                    let (done, written) = if block.decoded < self.position {
+                        let skip = (self.position - block.decoded)
+                            .try_into()
+                            .unwrap_or(usize::MAX)
+                            .min(self.scratch.len());
+                        let mut scratch = WriteBuffer::new_initialized(&mut self.scratch[..skip]);
+                        let done = block.decoder.decode(&mut input, &mut scratch)?;
+                        (done, scratch.written_len())
+                    } else {
+                        let prior_written = output.written_len();
+                        let done = block.decoder.decode(&mut input, output)?;
+                        let written = output.written_len() - prior_written;
+                        self.position += written as u64;
+                        (done, written)
+                    };
+                    block.decoded += written as u64;
+                    let read = input.written_len();
+                    self.input.0 += read;
+
+                    if done {
+                        self.block = None;
+                        self.state = State::Idle;
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    if output.written_len() > 0 {
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    if read == 0
+                        && written == 0
+                        && self.input.0 == self.input.1
+                        && block.remaining == 0
+                    {
+                        // Everything has been passed to the decoder without it reaching the end.
+                        return Poll::Ready(Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "xz block did not end",
+                        )));
+                    }
+                }
+
+                State::Size { .. }
+                | State::IndexSeek { .. }
+                | State::IndexRead { .. }
+                | State::Idle => unreachable!(),
+            }
+        }
+    }
+}
+
+macro_rules! impl_xz_decoder {
+    () => {
+        use crate::{codecs::xz::XzIndex, core::util::WriteBuffer, generic::seek::XzSeekDecoder};
+        use pin_project_lite::pin_project;
+        use std::{convert::TryInto, future::poll_fn};
+
+        pin_project! {
+            /// An xz decoder, or decompressor, with random access to the decompressed data.
+            ///
+            /// The index at the end of the file is read first, seeking then only requires decoding
+            /// the block containing the new position. Files written by the multi-threaded encoder
+            /// are split into many blocks, while single-threaded output is a single block per
+            /// stream which always has to be decoded from its start.
+            #[derive(Debug)]
+            pub struct XzDecoder<R> {
+                #[pin]
+                reader: R,
+                inner: XzSeekDecoder,
+            }
+        }
+
+        impl<R> XzDecoder<R> {
+            /// Creates a new decoder which will read compressed data from the given seekable
+            /// stream, starting at the beginning of the decompressed data.
+            pub fn new(reader: R) -> Self {
+                Self::with_mem_limit(reader, usize::MAX.try_into().unwrap())
+            }
+
+            /// Creates a new decoder with the specified limit of memory.
+            ///
+            /// # Errors
+            ///
+            /// An IO error may be returned during decoding if the specified limit is too small.
+            pub fn with_mem_limit(reader: R, memlimit: u64) -> Self {
+                Self {
+                    reader,
+                    inner: XzSeekDecoder::new(memlimit),
+                }
+            }
+
+            /// Acquires a reference to the underlying reader that this decoder is wrapping.
+            pub fn get_ref(&self) -> &R {
+                &self.reader
+            }
+
+            /// Acquires a mutable reference to the underlying reader that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this decoder.
+            pub fn get_mut(&mut self) -> &mut R {
+                &mut self.reader
+            }
+
+            /// Acquires a pinned mutable reference to the underlying reader that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this decoder.
+            pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+                self.project().reader
+            }
+
+            /// Consumes this decoder returning the underlying reader.
+            ///
+            /// Note that this may discard internal state of this decoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> R {
+                self.reader
+            }
+
+            /// Returns the index of the file, if it has been read already.
+            pub fn index(&self) -> Option<&XzIndex> {
+                self.inner.index()
+            }
+        }
+
+        impl<R: AsyncRead + AsyncSeek> XzDecoder<R> {
+            /// Reads the index of the file if that has not happened yet, and returns it.
+            pub fn poll_index(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<std::io::Result<&XzIndex>> {
+                let this = self.project();
+                this.inner.poll_index(cx, &mut Reader(this.reader))
+            }
+
+            /// Reads the index of the file if that has not happened yet, and returns it.
+            pub async fn read_index(&mut self) -> std::io::Result<&XzIndex>
+            where
+                Self: Unpin,
+            {
+                poll_fn(|cx| Pin::new(&mut *self).poll_index(cx).map_ok(|_| ())).await?;
+                Ok(self.inner.index().unwrap())
+            }
+        }
+    };
+}
+pub(crate) use impl_xz_decoder;
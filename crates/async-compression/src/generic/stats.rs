@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+/// Statistics about the data an encoder or decoder has processed so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    bytes_in: u64,
+    bytes_out: u64,
+    flushes: u64,
+    members: u64,
+    codec_time: Duration,
+}
+
+impl Stats {
+    /// Returns the number of bytes consumed by the codec, compressed bytes for a decoder and
+    /// uncompressed bytes for an encoder.
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes_in
+    }
+
+    /// Returns the number of bytes produced by the codec, uncompressed bytes for a decoder and
+    /// compressed bytes for an encoder.
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes_out
+    }
+
+    /// Returns the number of times the codec has completed a flush.
+    pub fn flushes(&self) -> u64 {
+        self.flushes
+    }
+
+    /// Returns the number of members or frames which have been completely encoded or decoded.
+    pub fn members(&self) -> u64 {
+        self.members
+    }
+
+    /// Returns the time spent inside the codec.
+    ///
+    /// The time is only measured with the `codec-time` feature enabled, as it takes reading the
+    /// clock around every call into the codec, and is zero otherwise.
+    pub fn codec_time(&self) -> Duration {
+        self.codec_time
+    }
+
+    /// Runs a call into the codec, adding the time it took.
+    #[cfg(feature = "codec-time")]
+    pub(crate) fn time<T>(&mut self, call: impl FnOnce() -> T) -> T {
+        let start = std::time::Instant::now();
+        let result = call();
+        self.codec_time += start.elapsed();
+        result
+    }
+
+    /// Runs a call into the codec.
+    #[cfg(not(feature = "codec-time"))]
+    pub(crate) fn time<T>(&mut self, call: impl FnOnce() -> T) -> T {
+        call()
+    }
+
+    /// Accounts for `read` bytes consumed and `written` bytes produced by the codec.
+    pub(crate) fn transferred(&mut self, read: usize, written: usize) {
+        self.bytes_in += read as u64;
+        self.bytes_out += written as u64;
+    }
+
+    pub(crate) fn flushed(&mut self) {
+        self.flushes += 1;
+    }
+
+    pub(crate) fn member_done(&mut self) {
+        self.members += 1;
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(feature = "tokio-time")]
+use std::time::Duration;
+#[cfg(feature = "tokio-time")]
+use tokio::time::Instant;
+
+/// When a write encoder flushes on its own, see the `set_auto_flush` method of the write
+/// encoders.
+///
+/// A flush is due once any of the set conditions is met by the data written since the last
+/// flush, and is done as part of the write which made it due, the same as a call to `flush`.
+/// Conditions are only checked when writing, so data written just before the writer goes idle
+/// stays in the encoder until the next write or an explicit flush.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AutoFlush {
+    bytes: Option<u64>,
+    writes: Option<u64>,
+    #[cfg(feature = "tokio-time")]
+    interval: Option<Duration>,
+}
+
+impl AutoFlush {
+    /// Creates a policy which never flushes.
+    pub const fn new() -> Self {
+        Self {
+            bytes: None,
+            writes: None,
+            #[cfg(feature = "tokio-time")]
+            interval: None,
+        }
+    }
+
+    /// Flushes once `bytes` uncompressed bytes have been written since the last flush.
+    pub const fn after_bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    /// Flushes once `writes` writes have been made since the last flush.
+    pub const fn after_writes(mut self, writes: u64) -> Self {
+        self.writes = Some(writes);
+        self
+    }
+
+    /// Flushes once the oldest data written since the last flush is `interval` old, measured
+    /// with the clock of the tokio runtime.
+    #[cfg(feature = "tokio-time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio-time")))]
+    pub const fn after_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+}
+
+/// Tracks the data written since the last flush against an [`AutoFlush`] policy.
+#[derive(Debug, Default)]
+pub(crate) struct AutoFlushState {
+    policy: AutoFlush,
+    bytes: u64,
+    writes: u64,
+    #[cfg(feature = "tokio-time")]
+    since: Option<Instant>,
+    due: bool,
+}
+
+impl AutoFlushState {
+    pub(crate) fn set_policy(&mut self, policy: AutoFlush) {
+        self.policy = policy;
+    }
+
+    /// Accounts for a write of `len` bytes, updating whether a flush is due.
+    pub(crate) fn wrote(&mut self, len: usize) {
+        self.bytes += len as u64;
+        self.writes += 1;
+
+        let AutoFlush { bytes, writes, .. } = self.policy;
+        self.due |= bytes.is_some_and(|bytes| self.bytes >= bytes)
+            || writes.is_some_and(|writes| self.writes >= writes);
+
+        #[cfg(feature = "tokio-time")]
+        if let Some(interval) = self.policy.interval {
+            let since = *self.since.get_or_insert_with(Instant::now);
+            self.due |= since.elapsed() >= interval;
+        }
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.due
+    }
+
+    /// Starts counting again after a flush has completed.
+    pub(crate) fn flushed(&mut self) {
+        *self = Self {
+            policy: self.policy,
+            ..Self::default()
+        };
+    }
+}
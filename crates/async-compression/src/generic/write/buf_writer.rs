@@ -46,6 +46,12 @@ impl BufWriter {
         }
     }
 
+    /// Discards the buffered data.
+    pub fn reset(&mut self) {
+        self.written = 0;
+        self.buffered = 0;
+    }
+
     /// Remove the already written data
     fn remove_written(&mut self) {
         self.buf.copy_within(self.written..self.buffered, 0);
@@ -222,6 +228,14 @@ macro_rules! impl_buf_writer {
             pub fn into_inner(self) -> W {
                 self.writer
             }
+
+            /// Replaces the underlying writer, returning the previous one.
+            ///
+            /// Note that any leftover data in the internal buffer is lost.
+            pub fn reset(&mut self, writer: W) -> W {
+                self.inner.reset();
+                std::mem::replace(&mut self.writer, writer)
+            }
         }
 
         fn get_poll_write<'a, 'b, W: AsyncWrite>(
@@ -1,7 +1,7 @@
 use crate::{
     codecs::DecodeV2,
     core::util::{PartialBuffer, WriteBuffer},
-    generic::write::AsyncBufWrite,
+    generic::{limit::OutputLimit, poll_codec, stats::Stats, write::AsyncBufWrite},
 };
 use std::{
     io,
@@ -19,17 +19,40 @@ enum State {
 #[derive(Debug)]
 pub struct Decoder {
     state: State,
+    limit: OutputLimit,
+    stats: Stats,
 }
 
 impl Default for Decoder {
     fn default() -> Self {
         Self {
             state: State::Decoding,
+            limit: OutputLimit::default(),
+            stats: Stats::default(),
         }
     }
 }
 
 impl Decoder {
+    pub fn max_output_size(&mut self, limit: Option<u64>) {
+        self.limit.max_size(limit);
+    }
+
+    pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+        self.limit.max_ratio(ratio);
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Gets ready for a new stream, keeping the settings.
+    pub fn reset(&mut self) {
+        self.state = State::Decoding;
+        self.limit.reset();
+        self.stats = Stats::default();
+    }
+
     fn do_poll_write(
         &mut self,
         cx: &mut Context<'_>,
@@ -37,13 +60,21 @@ impl Decoder {
         mut writer: Pin<&mut dyn AsyncBufWrite>,
         decoder: &mut dyn DecodeV2,
     ) -> Poll<io::Result<()>> {
+        if let Some(err) = self.limit.error() {
+            return Poll::Ready(Err(err));
+        }
+        decoder.register_waker(cx.waker());
+
         loop {
             let mut output = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
             let output = &mut output.write_buffer;
+            let read = input.written_len();
 
             self.state = match self.state {
                 State::Decoding => {
-                    if decoder.decode(input, output)? {
+                    if ready!(poll_codec(
+                        self.stats.time(|| decoder.decode(input, output))
+                    ))? {
                         State::Finishing
                     } else {
                         State::Decoding
@@ -51,7 +82,8 @@ impl Decoder {
                 }
 
                 State::Finishing => {
-                    if decoder.finish(output)? {
+                    if ready!(poll_codec(self.stats.time(|| decoder.finish(output))))? {
+                        self.stats.member_done();
                         State::Done
                     } else {
                         State::Finishing
@@ -63,6 +95,11 @@ impl Decoder {
                 }
             };
 
+            let read = input.written_len() - read;
+            let checked = self.limit.check(read, output, 0);
+            self.stats.transferred(read, output.written_len());
+            checked?;
+
             if let State::Done = self.state {
                 return Poll::Ready(Ok(()));
             }
@@ -98,18 +135,27 @@ impl Decoder {
         mut writer: Pin<&mut dyn AsyncBufWrite>,
         decoder: &mut dyn DecodeV2,
     ) -> Poll<io::Result<()>> {
+        if let Some(err) = self.limit.error() {
+            return Poll::Ready(Err(err));
+        }
+        decoder.register_waker(cx.waker());
+
         loop {
             let mut output = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
             let output = &mut output.write_buffer;
 
             let (state, done) = match self.state {
                 State::Decoding => {
-                    let done = decoder.flush(output)?;
+                    let done = ready!(poll_codec(self.stats.time(|| decoder.flush(output))))?;
+                    if done {
+                        self.stats.flushed();
+                    }
                     (State::Decoding, done)
                 }
 
                 State::Finishing => {
-                    if decoder.finish(output)? {
+                    if ready!(poll_codec(self.stats.time(|| decoder.finish(output))))? {
+                        self.stats.member_done();
                         (State::Done, false)
                     } else {
                         (State::Finishing, false)
@@ -120,6 +166,9 @@ impl Decoder {
             };
 
             self.state = state;
+            let checked = self.limit.check(0, output, 0);
+            self.stats.transferred(0, output.written_len());
+            checked?;
 
             if done {
                 break Poll::Ready(Ok(()));
@@ -182,6 +231,41 @@ macro_rules! impl_decoder {
             pub fn into_inner(self) -> W {
                 self.writer.into_inner()
             }
+
+            pub fn codec(&self) -> &D {
+                &self.decoder
+            }
+
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            pub fn set_limits(
+                &mut self,
+                limits: crate::codecs::DecoderLimits,
+            ) -> std::io::Result<()>
+            where
+                D: DecodeV2,
+            {
+                self.decoder.set_limits(limits)
+            }
+
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            pub fn reset(&mut self, writer: W) -> std::io::Result<W>
+            where
+                D: DecodeV2,
+            {
+                self.decoder.reset()?;
+                self.inner.reset();
+                Ok(self.writer.reset(writer))
+            }
         }
 
         impl<W: AsyncWrite, D: DecodeV2> Decoder<W, D> {
@@ -1,7 +1,11 @@
 use crate::{
     codecs::EncodeV2,
     core::util::{PartialBuffer, WriteBuffer},
-    generic::write::AsyncBufWrite,
+    generic::{
+        poll_codec,
+        stats::Stats,
+        write::{AsyncBufWrite, AutoFlush, AutoFlushState},
+    },
 };
 use std::{
     io,
@@ -19,17 +23,47 @@ enum State {
 #[derive(Debug)]
 pub struct Encoder {
     state: State,
+    stats: Stats,
+    auto_flush: AutoFlushState,
 }
 
 impl Default for Encoder {
     fn default() -> Self {
         Self {
             state: State::Encoding,
+            stats: Stats::default(),
+            auto_flush: AutoFlushState::default(),
         }
     }
 }
 
 impl Encoder {
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn set_auto_flush(&mut self, policy: AutoFlush) {
+        self.auto_flush.set_policy(policy);
+    }
+
+    /// Returns whether the writes so far have made a flush due, which the caller should complete
+    /// before writing more.
+    pub fn auto_flush_due(&self) -> bool {
+        self.auto_flush.is_due()
+    }
+
+    /// Records that a flush of both the codec and the underlying writer has completed.
+    pub fn flushed(&mut self) {
+        self.auto_flush.flushed();
+    }
+
+    /// Gets ready for a new stream, keeping the auto-flush policy.
+    pub fn reset(&mut self) {
+        self.state = State::Encoding;
+        self.stats = Stats::default();
+        self.auto_flush.flushed();
+    }
+
     fn do_poll_write(
         &mut self,
         cx: &mut Context<'_>,
@@ -37,13 +71,17 @@ impl Encoder {
         mut writer: Pin<&mut dyn AsyncBufWrite>,
         encoder: &mut dyn EncodeV2,
     ) -> Poll<io::Result<()>> {
+        encoder.register_waker(cx.waker());
         loop {
             let mut output = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
             let output = &mut output.write_buffer;
+            let read = input.written_len();
 
             self.state = match self.state {
                 State::Encoding => {
-                    encoder.encode(input, output)?;
+                    ready!(poll_codec(
+                        self.stats.time(|| encoder.encode(input, output))
+                    ))?;
                     State::Encoding
                 }
 
@@ -51,6 +89,8 @@ impl Encoder {
                     break Poll::Ready(Err(io::Error::other("Write after close")));
                 }
             };
+            self.stats
+                .transferred(input.written_len() - read, output.written_len());
 
             if input.unwritten().is_empty() {
                 break Poll::Ready(Ok(()));
@@ -73,7 +113,10 @@ impl Encoder {
 
         match self.do_poll_write(cx, &mut input, writer, encoder)? {
             Poll::Pending if input.written().is_empty() => Poll::Pending,
-            _ => Poll::Ready(Ok(input.written().len())),
+            _ => {
+                self.auto_flush.wrote(input.written().len());
+                Poll::Ready(Ok(input.written().len()))
+            }
         }
     }
 
@@ -83,19 +126,23 @@ impl Encoder {
         mut writer: Pin<&mut dyn AsyncBufWrite>,
         encoder: &mut dyn EncodeV2,
     ) -> Poll<io::Result<()>> {
+        encoder.register_waker(cx.waker());
         loop {
             let mut output = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
             let output = &mut output.write_buffer;
 
             let done = match self.state {
-                State::Encoding => encoder.flush(output)?,
+                State::Encoding => ready!(poll_codec(self.stats.time(|| encoder.flush(output))))?,
 
                 State::Finishing | State::Done => {
                     break Poll::Ready(Err(io::Error::other("Flush after close")));
                 }
             };
 
+            self.stats.transferred(0, output.written_len());
+
             if done {
+                self.stats.flushed();
                 break Poll::Ready(Ok(()));
             }
         }
@@ -107,13 +154,17 @@ impl Encoder {
         mut writer: Pin<&mut dyn AsyncBufWrite>,
         encoder: &mut dyn EncodeV2,
     ) -> Poll<io::Result<()>> {
+        encoder.register_waker(cx.waker());
         loop {
             let mut output = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
             let output = &mut output.write_buffer;
 
             self.state = match self.state {
                 State::Encoding | State::Finishing => {
-                    if encoder.finish(output)? {
+                    let done = ready!(poll_codec(self.stats.time(|| encoder.finish(output))))?;
+                    self.stats.transferred(0, output.written_len());
+                    if done {
+                        self.stats.member_done();
                         State::Done
                     } else {
                         State::Finishing
@@ -182,6 +233,45 @@ macro_rules! impl_encoder {
                     inner: Default::default(),
                 }
             }
+
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            pub fn set_flush_mode(&mut self, mode: crate::codecs::FlushMode) -> std::io::Result<()>
+            where
+                E: EncodeV2,
+            {
+                self.encoder.set_flush_mode(mode)
+            }
+
+            pub fn set_auto_flush(&mut self, policy: crate::generic::write::AutoFlush) {
+                self.inner.set_auto_flush(policy)
+            }
+
+            pub fn reset(&mut self, writer: W) -> std::io::Result<W>
+            where
+                E: EncodeV2,
+            {
+                self.encoder.reinit()?;
+                self.inner.reset();
+                Ok(self.writer.reset(writer))
+            }
+        }
+
+        impl<W: AsyncWrite, E: EncodeV2> Encoder<W, E> {
+            /// Flushes the codec and then the underlying writer, as `poll_flush` does.
+            fn poll_flush_all(
+                mut writer: Pin<&mut BufWriter<W>>,
+                cx: &mut Context<'_>,
+                encoder: &mut E,
+                inner: &mut GenericEncoder,
+            ) -> Poll<io::Result<()>> {
+                ready!(inner.do_poll_flush(cx, writer.as_mut(), encoder))?;
+                ready!(writer.poll_flush(cx))?;
+                inner.flushed();
+                Poll::Ready(Ok(()))
+            }
         }
 
         impl<W: AsyncWrite, E: EncodeV2> AsyncWrite for Encoder<W, E> {
@@ -190,17 +280,38 @@ macro_rules! impl_encoder {
                 cx: &mut Context<'_>,
                 buf: &[u8],
             ) -> Poll<io::Result<usize>> {
-                let this = self.project();
-                this.inner.poll_write(cx, buf, this.writer, this.encoder)
-            }
-
-            fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
                 let mut this = self.project();
 
-                ready!(this
-                    .inner
-                    .do_poll_flush(cx, this.writer.as_mut(), this.encoder))?;
-                this.writer.poll_flush(cx)
+                if this.inner.auto_flush_due() {
+                    ready!(Self::poll_flush_all(
+                        this.writer.as_mut(),
+                        cx,
+                        this.encoder,
+                        this.inner
+                    ))?;
+                }
+
+                let written =
+                    ready!(this
+                        .inner
+                        .poll_write(cx, buf, this.writer.as_mut(), this.encoder))?;
+
+                // The input has been taken by now, so a flush which can't complete yet is
+                // completed at the start of the next write.
+                if this.inner.auto_flush_due() {
+                    if let Poll::Ready(Err(err)) =
+                        Self::poll_flush_all(this.writer, cx, this.encoder, this.inner)
+                    {
+                        return Poll::Ready(Err(err));
+                    }
+                }
+
+                Poll::Ready(Ok(written))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                let this = self.project();
+                Self::poll_flush_all(this.writer, cx, this.encoder, this.inner)
             }
 
             fn $poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -1,8 +1,11 @@
+mod auto_flush;
 mod buf_write;
 mod buf_writer;
 mod decoder;
 mod encoder;
 
+pub use auto_flush::AutoFlush;
+pub(crate) use auto_flush::AutoFlushState;
 pub(crate) use buf_write::*;
 pub(crate) use buf_writer::*;
 pub(crate) use decoder::*;
@@ -0,0 +1,173 @@
+use crate::{codecs::zip::ZipEncoder, generic::write::AsyncBufWrite};
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Completes the current entry of `encoder`, writing the rest of it to `writer`.
+pub(crate) fn poll_finish_entry(
+    cx: &mut Context<'_>,
+    mut writer: Pin<&mut dyn AsyncBufWrite>,
+    encoder: &mut ZipEncoder,
+) -> Poll<io::Result<()>> {
+    loop {
+        let mut output = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
+
+        if encoder.finish_entry(&mut output.write_buffer)? {
+            break Poll::Ready(Ok(()));
+        }
+    }
+}
+
+macro_rules! impl_zip_writer {
+    ($poll_close: tt) => {
+        pub use crate::codecs::zip::{ZipEntry, ZipMethod};
+
+        use crate::{
+            codecs::zip::ZipEncoder,
+            generic::{write::Encoder as GenericEncoder, zip::poll_finish_entry},
+        };
+        use pin_project_lite::pin_project;
+        use std::{future::poll_fn, task::ready};
+
+        pin_project! {
+            /// A writer of ZIP archives, which are written as a stream without seeking back.
+            ///
+            /// Each entry is started with [`start_entry`](Self::start_entry), after which the
+            /// uncompressed data written is compressed into it. Closing the writer completes the
+            /// last entry and writes the central directory, the archive is incomplete until then.
+            ///
+            /// The CRC-32 and sizes of each entry follow its data in a data descriptor, and the
+            /// details of every entry are kept in memory for the central directory. As the sizes
+            /// of an entry aren't known up front, every local header has a ZIP64 extra field, and
+            /// the central directory has ZIP64 fields when sizes, offsets or the number of entries
+            /// need them.
+            #[derive(Debug)]
+            pub struct ZipWriter<W> {
+                #[pin]
+                writer: BufWriter<W>,
+                encoder: ZipEncoder,
+                inner: GenericEncoder,
+            }
+        }
+
+        impl<W> ZipWriter<W> {
+            /// Creates a new writer which will write the archive to the given stream.
+            pub fn new(writer: W) -> Self {
+                Self {
+                    writer: BufWriter::new(writer),
+                    encoder: ZipEncoder::new(),
+                    inner: Default::default(),
+                }
+            }
+
+            /// Creates a new writer with a specific buffer size for the output.
+            pub fn with_capacity(writer: W, cap: usize) -> Self {
+                Self {
+                    writer: BufWriter::with_capacity(cap, writer),
+                    encoder: ZipEncoder::new(),
+                    inner: Default::default(),
+                }
+            }
+
+            /// Acquires a reference to the underlying writer that this archive is written to.
+            pub fn get_ref(&self) -> &W {
+                self.writer.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying writer that this archive is
+            /// written to.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the writer which
+            /// may otherwise corrupt the archive.
+            pub fn get_mut(&mut self) -> &mut W {
+                self.writer.get_mut()
+            }
+
+            /// Acquires a pinned mutable reference to the underlying writer that this archive is
+            /// written to.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the writer which
+            /// may otherwise corrupt the archive.
+            pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+                self.project().writer.get_pin_mut()
+            }
+
+            /// Consumes this writer returning the underlying writer.
+            ///
+            /// Note that this may discard buffered output and the central directory if the writer
+            /// has not been closed, so care should be taken to avoid losing data when this is
+            /// called.
+            pub fn into_inner(self) -> W {
+                self.writer.into_inner()
+            }
+        }
+
+        impl<W: AsyncWrite> ZipWriter<W> {
+            /// Completes the current entry, if any, by finishing its compressed data and writing
+            /// its data descriptor.
+            pub fn poll_finish_entry(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<io::Result<()>> {
+                let this = self.project();
+                poll_finish_entry(cx, this.writer, this.encoder)
+            }
+
+            /// Completes the current entry, if any, by finishing its compressed data and writing
+            /// its data descriptor.
+            pub async fn finish_entry(&mut self) -> io::Result<()>
+            where
+                Self: Unpin,
+            {
+                poll_fn(|cx| Pin::new(&mut *self).poll_finish_entry(cx)).await
+            }
+
+            /// Completes the current entry, if any, and starts a new one which the following
+            /// writes are compressed into.
+            ///
+            /// # Errors
+            ///
+            /// Returns error when the writer has been closed or the name or comment of the entry
+            /// is too long.
+            pub async fn start_entry(&mut self, entry: ZipEntry) -> io::Result<()>
+            where
+                Self: Unpin,
+            {
+                self.finish_entry().await?;
+                self.encoder.start_entry(entry)
+            }
+        }
+
+        impl<W: AsyncWrite> AsyncWrite for ZipWriter<W> {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                let this = self.project();
+                this.inner.poll_write(cx, buf, this.writer, this.encoder)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                let mut this = self.project();
+
+                ready!(this
+                    .inner
+                    .do_poll_flush(cx, this.writer.as_mut(), this.encoder))?;
+                this.writer.poll_flush(cx)
+            }
+
+            fn $poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                let mut this = self.project();
+
+                ready!(this
+                    .inner
+                    .do_poll_close(cx, this.writer.as_mut(), this.encoder))?;
+                this.writer.$poll_close(cx)
+            }
+        }
+    };
+}
+pub(crate) use impl_zip_writer;
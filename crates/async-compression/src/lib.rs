@@ -38,6 +38,22 @@
     not(feature = "futures-io"),
     doc = "`futures-io` (*inactive*) | `futures::io::AsyncBufRead`, `futures::io::AsyncWrite`"
 )]
+#![cfg_attr(
+    feature = "stream",
+    doc = "[`stream`] | [`futures_core::Stream`] of [`bytes::Bytes`]"
+)]
+#![cfg_attr(
+    not(feature = "stream"),
+    doc = "`stream` (*inactive*) | `futures_core::Stream` of `bytes::Bytes`"
+)]
+#![cfg_attr(
+    feature = "sync",
+    doc = "[`sync`] | [`std::io::BufRead`], [`std::io::Write`]"
+)]
+#![cfg_attr(
+    not(feature = "sync"),
+    doc = "`sync` (*inactive*) | `std::io::BufRead`, `std::io::Write`"
+)]
 #![cfg_attr(
     feature = "tokio",
     doc = "[`tokio`] | [`tokio::io::AsyncBufRead`](::tokio::io::AsyncBufRead), [`tokio::io::AsyncWrite`](::tokio::io::AsyncWrite)"
@@ -96,6 +112,14 @@
     not(feature = "lz4"),
     doc = "`lz4` (*inactive*) | `Lz4Encoder`, `Lz4Decoder`"
 )]
+#![cfg_attr(
+    feature = "lzip",
+    doc = "`lzip` | [`LzipEncoder`](?search=LzipEncoder), [`LzipDecoder`](?search=LzipDecoder)"
+)]
+#![cfg_attr(
+    not(feature = "lzip"),
+    doc = "`lzip` (*inactive*) | `LzipEncoder`, `LzipDecoder`"
+)]
 #![cfg_attr(
     feature = "lzma",
     doc = "`lzma` | [`LzmaEncoder`](?search=LzmaEncoder), [`LzmaDecoder`](?search=LzmaDecoder)"
@@ -104,6 +128,22 @@
     not(feature = "lzma"),
     doc = "`lzma` (*inactive*) | `LzmaEncoder`, `LzmaDecoder`"
 )]
+#![cfg_attr(
+    feature = "lzw",
+    doc = "`lzw` | (encoder not implemented), [`CompressZDecoder`](?search=CompressZDecoder)"
+)]
+#![cfg_attr(
+    not(feature = "lzw"),
+    doc = "`lzw` (*inactive*) | (encoder not implemented), `CompressZDecoder`"
+)]
+#![cfg_attr(
+    feature = "snappy",
+    doc = "`snappy` | [`SnappyEncoder`](?search=SnappyEncoder), [`SnappyDecoder`](?search=SnappyDecoder)"
+)]
+#![cfg_attr(
+    not(feature = "snappy"),
+    doc = "`snappy` (*inactive*) | `SnappyEncoder`, `SnappyDecoder`"
+)]
 #![cfg_attr(
     feature = "xz",
     doc = "`xz` | [`XzEncoder`](?search=XzEncoder), [`XzDecoder`](?search=XzDecoder)"
@@ -142,6 +182,81 @@
 //! The `xz` compression algorithm supports multi-threaded compression and decompression.
 //! Enable the `xz-parallel` feature to enable multi-threading support.
 //!
+//! The `gzip` compression algorithm supports multi-threaded compression, enable the
+//! `gzip-parallel` feature to enable it.
+//!
+//! Inputs made of many independent `zstd` frames can be decompressed on multiple threads, enable
+//! the `zstd-parallel` feature to enable it.
+//!
+
+//! ## ZIP archives
+//! Enable the `zip` feature to write streaming ZIP archives with the `ZipWriter` of the `zip`
+//! module of each IO implementation. Entries can be stored or compressed with deflate, and with
+//! bzip2, LZMA or zstd when the corresponding algorithm features are enabled too.
+//!
+
+//! ## Runtime algorithm selection
+//! The `AnyEncoder` and `AnyDecoder` of each module wrap the encoder or decoder of an
+//! [`Algorithm`] chosen at runtime, such as from configuration, among the enabled algorithms.
+//!
+
+//! ## Format detection
+//! The `AutoDecoder` of the `bufread` module of each IO implementation detects the format of its
+//! input from the first bytes, among the gzip, zlib, zstd, xz, lzma, bzip2 and lz4 formats whose
+//! features are enabled, and passes through input in any other format unchanged.
+//!
+
+//! ## Unbuffered readers
+//! The `read` modules of the `tokio` and `futures` implementations have the same types as
+//! their `bufread` modules, but read from an `AsyncRead` through an internal buffer sized for the
+//! codec, so the reader doesn't need to be wrapped in a `BufReader` first.
+//!
+
+//! ## One-shot helpers
+//! The [`oneshot`] module compresses or decompresses a whole buffer at once, and the
+//! `decompress_to_vec` function of the `tokio` and `futures` implementations reads a decoder to
+//! its end. Both refuse to decompress more than a given number of bytes.
+//!
+
+//! ## Output limits
+//! Every decoder has `max_output_size` and `max_expansion_ratio` methods, to stop decompressing
+//! untrusted input which expands too much with an error of kind
+//! [`FileTooLarge`](std::io::ErrorKind::FileTooLarge).
+//!
+
+//! ## Memory limits
+//! Every decoder has a `set_limits` method taking [`DecoderLimits`], to refuse streams needing
+//! too large a window, dictionary or block buffer with an error of kind
+//! [`OutOfMemory`](std::io::ErrorKind::OutOfMemory) before allocating it.
+//!
+
+//! ## Statistics
+//! Every encoder and decoder has a `stats` method returning [`Stats`], with the bytes its codec
+//! consumed and produced, the flushes and members it completed and the time spent in the codec.
+//! The time is only measured with the `codec-time` feature enabled, since reading the clock around
+//! every call into the codec has a cost, and isn't possible on targets without one such as
+//! `wasm32-unknown-unknown`.
+//!
+
+//! ## Flush modes
+//! Every encoder has a `set_flush_mode` method taking a [`FlushMode`], to choose between a sync
+//! flush, a full flush which decoding can start over from, and ending the current block or frame.
+//!
+//! ## Automatic flushing
+//! The encoders of the `write` modules of the `tokio` and `futures` implementations have a
+//! `set_auto_flush` method taking an [`AutoFlush`] policy, to flush on their own after a number of
+//! bytes or writes, such as for streaming protocols. Enable the `tokio-time` feature to also flush
+//! after a time interval.
+//!
+//! ## Reusing encoders and decoders
+//! Every encoder and decoder has a `reset` method which starts a new stream over a new reader,
+//! writer or stream, returning the previous one. The codec and its settings are kept, along with
+//! its allocations where the codec allows it, which saves setting up a new encoder or decoder for
+//! each of many small streams. The brotli encoder can't keep its allocations, as the `brotli`
+//! crate has no way to reset its state, so a new one is set up with the same settings instead.
+//! Resetting drops any data of the previous stream which has not been read or written yet, so
+//! finish it first to keep it.
+//!
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![cfg_attr(not(all), allow(unused))]
@@ -154,13 +269,24 @@ mod generic;
 
 #[cfg(feature = "futures-io")]
 pub mod futures;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "sync")]
+pub mod sync;
 #[cfg(feature = "tokio")]
 pub mod tokio;
 
+pub mod negotiate;
+
 pub use compression_codecs as codecs;
 pub use compression_core as core;
 
 pub use core::Level;
+pub use generic::stats::Stats;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub use generic::write::AutoFlush;
+
+pub use codecs::{any::Algorithm, auto, oneshot, DecoderLimits, FlushMode};
 
 #[cfg(feature = "zstd")]
 pub use codecs::zstd::params as zstd;
@@ -170,3 +296,6 @@ pub use codecs::lz4::params as lz4;
 
 #[cfg(feature = "brotli")]
 pub use codecs::brotli::params as brotli;
+
+#[cfg(feature = "lzma")]
+pub use codecs::lzma::params as lzma;
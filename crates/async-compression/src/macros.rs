@@ -129,6 +129,30 @@ macro_rules! algos {
                     ),
                 }
             }
+
+            /// Creates a new multi-threaded encoder.
+            ///
+            /// The input is split into chunks which are compressed in parallel, each using the
+            /// end of the preceding chunk as its dictionary, and joined into a single gzip member.
+            /// The calling thread never blocks on the worker threads: the asynchronous types
+            /// return `Poll::Pending` while they wait for them, only the blocking `sync` types
+            /// park the thread.
+            ///
+            /// Note that flushing will severely impact multi-threaded performance.
+            ///
+            /// # Errors
+            ///
+            /// Returns error when the worker threads could not be spawned.
+            #[cfg(feature = "gzip-parallel")]
+            pub fn parallel(inner: $inner, level: crate::core::Level, threads: std::num::NonZeroU32) -> ::std::io::Result<Self> {
+                let params = crate::codecs::flate::params::FlateEncoderParams::from(level);
+                Ok(Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codecs::GzipEncoder::parallel(params, threads)?,
+                    ),
+                })
+            }
         }
         { @dec }
         );
@@ -244,6 +268,42 @@ macro_rules! algos {
                     ),
                 })
             }
+
+            /// Creates a new multi-threaded decoder.
+            ///
+            /// The input is split at frame boundaries and up to `threads` frames are decoded at
+            /// the same time, with the decoded frames emitted in order. This only speeds up
+            /// inputs made of many independent frames, and every frame is decoded regardless of
+            /// `multiple_members`. Up to 4 MiB of compressed input is buffered while looking for
+            /// the end of a frame, larger frames are decoded without the worker threads.
+            ///
+            /// # Errors
+            ///
+            /// Returns error when the worker threads could not be spawned.
+            #[cfg(feature = "zstd-parallel")]
+            pub fn parallel(inner: $inner, threads: std::num::NonZeroU32) -> ::std::io::Result<Self> {
+                Self::parallel_with_params(inner, threads, &[])
+            }
+
+            /// Creates a new multi-threaded decoder, using the specified parameters for every
+            /// frame.
+            ///
+            /// # Errors
+            ///
+            /// Returns error when the worker threads could not be spawned.
+            #[cfg(feature = "zstd-parallel")]
+            pub fn parallel_with_params(
+                inner: $inner,
+                threads: std::num::NonZeroU32,
+                params: &[crate::codecs::zstd::params::DParameter],
+            ) -> ::std::io::Result<Self> {
+                Ok(Self {
+                    inner: crate::$($mod::)+generic::Decoder::new(
+                        inner,
+                        crate::codecs::ZstdDecoder::parallel(threads, params)?,
+                    ),
+                })
+            }
         }
         );
 
@@ -259,6 +319,47 @@ macro_rules! algos {
                 }
             }
 
+            /// Creates a new encoder, using the specified parameters, which will read uncompressed
+            /// data from the given stream and emit a compressed stream.
+            ///
+            /// This allows choosing the integrity check and filter chain, or the multi-threaded
+            /// encoder settings when the `xz-parallel` feature is enabled.
+            ///
+            /// # Errors
+            ///
+            /// Returns error when `params` do not describe an xz stream or are not supported.
+            pub fn with_params(
+                inner: $inner,
+                params: crate::codecs::lzma::params::LzmaEncoderParams,
+            ) -> ::std::io::Result<Self> {
+                Ok(Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codecs::XzEncoder::with_params(params)?,
+                    ),
+                })
+            }
+
+            /// Creates a new single-threaded encoder which will read uncompressed data from the
+            /// given stream and emit a compressed stream, starting a new block after every
+            /// `block_size` bytes of input, like `xz --block-size`.
+            ///
+            /// The sizes of each block are recorded in its header, so the output can be decoded
+            /// by a multi-threaded decoder. Up to one compressed block is buffered in memory, and
+            /// flushing ends the current block.
+            pub fn with_block_size(
+                inner: $inner,
+                level: crate::core::Level,
+                block_size: std::num::NonZeroU64,
+            ) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codecs::XzEncoder::with_block_size(level, block_size),
+                    ),
+                }
+            }
+
             /// Creates a new multi-threaded encoder.
             ///
             /// Note that flushing will severely impact multi-threaded performance.
@@ -287,6 +388,27 @@ macro_rules! algos {
                 }
             }
 
+            /// Creates a new decoder with the specified options, which allow ignoring the integrity
+            /// check, decoding concatenated streams, or rejecting streams without a usable check.
+            pub fn with_options(
+                read: $inner,
+                options: crate::codecs::lzma::params::LzmaDecoderOptions,
+            ) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Decoder::new(
+                        read,
+                        crate::codecs::XzDecoder::with_options(options),
+                    ),
+                }
+            }
+
+            /// Returns the integrity check type of the stream being decoded, or `None` if the
+            /// stream header has not been read yet. With multiple members this is `None` again
+            /// once a member is done, until the header of the next one has been read.
+            pub fn check(&self) -> Option<crate::codecs::lzma::params::StreamCheck> {
+                self.inner.codec().check()
+            }
+
             /// Creates a new multi-threaded decoder.
             #[cfg(feature = "xz-parallel")]
             pub fn parallel(read: $inner, threads: std::num::NonZeroU32) -> Self {
@@ -327,6 +449,45 @@ macro_rules! algos {
                     inner
                 }
             }
+
+            /// Creates a new encoder, using the specified parameters, which will read uncompressed
+            /// data from the given stream and emit a compressed stream.
+            ///
+            /// This allows using a custom raw filter chain instead of the `.lzma` format.
+            ///
+            /// # Errors
+            ///
+            /// Returns error when `params` describe an xz stream or are not supported.
+            pub fn with_params(
+                inner: $inner,
+                params: crate::codecs::lzma::params::LzmaEncoderParams,
+            ) -> ::std::io::Result<Self> {
+                Ok(Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codecs::LzmaEncoder::with_params(params)?,
+                    ),
+                })
+            }
+
+            /// Creates a new encoder which will read uncompressed data from the given stream and
+            /// emit a raw stream, without any header, compressed by the given filter chain.
+            ///
+            /// This is the format used by ZIP and 7z entries, which store the LZMA1 properties
+            /// out of band, see [`Lzma1Props`](crate::codecs::lzma::params::Lzma1Props).
+            ///
+            /// # Errors
+            ///
+            /// Returns error when the filter chain is not supported.
+            pub fn with_filters(
+                inner: $inner,
+                filters: crate::codecs::lzma::params::LzmaFilters,
+            ) -> ::std::io::Result<Self> {
+                Self::with_params(
+                    inner,
+                    crate::codecs::lzma::params::LzmaEncoderParams::Raw { filters },
+                )
+            }
         }
         { @dec
             /// Creates a new decoder with the specified limit of memory.
@@ -343,9 +504,102 @@ macro_rules! algos {
                 }
             }
 
+            /// Creates a new decoder with the specified options, which allow ignoring the integrity
+            /// check, decoding concatenated streams, or rejecting streams without a usable check.
+            pub fn with_options(
+                read: $inner,
+                options: crate::codecs::lzma::params::LzmaDecoderOptions,
+            ) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Decoder::new(
+                        read,
+                        crate::codecs::LzmaDecoder::with_options(options),
+                    ),
+                }
+            }
+
+            /// Creates a new decoder which will read a raw stream, without any header,
+            /// compressed by the given filter chain.
+            ///
+            /// This is the format used by ZIP and 7z entries, which store the LZMA1 properties
+            /// out of band, see [`Lzma1Props`](crate::codecs::lzma::params::Lzma1Props).
+            ///
+            /// # Errors
+            ///
+            /// Returns error when the filter chain is not supported.
+            pub fn with_filters(
+                read: $inner,
+                filters: crate::codecs::lzma::params::LzmaFilters,
+            ) -> ::std::io::Result<Self> {
+                Ok(Self {
+                    inner: crate::$($mod::)+generic::Decoder::new(
+                        read,
+                        crate::codecs::LzmaDecoder::with_params(
+                            crate::codecs::lzma::params::LzmaDecoderParams::Raw { filters },
+                        )?,
+                    ),
+                })
+            }
+
+            /// Returns the integrity check type of the stream being decoded, or `None` if the
+            /// stream header has not been read yet. With multiple members this is `None` again
+            /// once a member is done, until the header of the next one has been read.
+            pub fn check(&self) -> Option<crate::codecs::lzma::params::StreamCheck> {
+                self.inner.codec().check()
+            }
+
         }
         );
 
+        algos!(@algo lzip ["lzip"] LzipDecoder LzipEncoder <$inner>
+        { @enc
+
+            pub fn with_quality(inner: $inner, level: crate::core::Level) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codecs::LzipEncoder::new(level),
+                    ),
+                }
+            }
+        }
+        { @dec
+            /// Creates a new decoder with the specified limit of memory.
+            ///
+            /// # Errors
+            ///
+            /// An IO error may be returned during decoding if the specified limit is too small.
+            pub fn with_mem_limit(read: $inner, memlimit: u64) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Decoder::new(
+                        read,
+                        crate::codecs::LzipDecoder::with_memlimit(memlimit),
+                    ),
+                }
+            }
+        }
+        );
+
+        algos!(@algo lzw ["lzw"] CompressZDecoder CompressZEncoder <$inner>
+        { @dec }
+        );
+
+        algos!(@algo snappy ["snappy"] SnappyDecoder SnappyEncoder <$inner>
+        { @enc
+            /// Snappy has no compression levels, so `level` is ignored.
+            pub fn with_quality(inner: $inner, level: crate::core::Level) -> Self {
+                let _ = level;
+                Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codecs::SnappyEncoder::new(),
+                    ),
+                }
+            }
+        }
+        { @dec }
+        );
+
         algos!(@algo lz4 ["lz4"] Lz4Decoder Lz4Encoder <$inner>
         { @enc
 
@@ -375,5 +629,66 @@ macro_rules! algos {
         { @dec }
         );
 
+        encoder! {
+            /// An encoder, or compressor, for an [`Algorithm`](crate::Algorithm) chosen at
+            /// runtime.
+            AnyEncoder<$inner> {
+                /// # Errors
+                ///
+                /// Returns an error for algorithms which can only be decoded, see
+                /// [`Algorithm::can_encode`](crate::Algorithm::can_encode).
+                pub fn new(inner: $inner, algorithm: crate::Algorithm) -> ::std::io::Result<Self> {
+                    Self::with_quality(inner, algorithm, crate::core::Level::Default)
+                }
+            }
+
+            {
+                /// # Errors
+                ///
+                /// Returns an error for algorithms which can only be decoded, see
+                /// [`Algorithm::can_encode`](crate::Algorithm::can_encode).
+                pub fn with_quality(
+                    inner: $inner,
+                    algorithm: crate::Algorithm,
+                    level: crate::core::Level,
+                ) -> ::std::io::Result<Self> {
+                    Ok(Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codecs::AnyEncoder::new(algorithm, level)?,
+                        ),
+                    })
+                }
+
+                /// Returns the algorithm of this encoder.
+                pub fn algorithm(&self) -> crate::Algorithm {
+                    self.inner.get_encoder_ref().algorithm()
+                }
+            }
+        }
+
+        decoder! {
+            /// A decoder, or decompressor, for an [`Algorithm`](crate::Algorithm) chosen at
+            /// runtime.
+            AnyDecoder<$inner> @new {
+                /// Creates a new decoder for the given algorithm, which will decompress data from
+                /// the given stream.
+                pub fn new(inner: $inner, algorithm: crate::Algorithm) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codecs::AnyDecoder::new(algorithm),
+                        ),
+                    }
+                }
+            }
+
+            {
+                /// Returns the algorithm of this decoder.
+                pub fn algorithm(&self) -> crate::Algorithm {
+                    self.inner.codec().algorithm()
+                }
+            }
+        }
     }
 }
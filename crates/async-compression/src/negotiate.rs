@@ -0,0 +1,197 @@
+//! Negotiation of the content coding of HTTP responses from the `Accept-Encoding` request header,
+//! as specified by [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-12.5.3), and parsing
+//! of the `Content-Encoding` header.
+//!
+//! The `negotiate` module of each IO implementation turns these into boxed encoders and decoders.
+
+use std::io;
+
+/// An HTTP content coding supported by the enabled crate features.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentEncoding {
+    /// No encoding.
+    Identity,
+    /// The `br` coding.
+    #[cfg(feature = "brotli")]
+    Brotli,
+    /// The `deflate` coding, which is the zlib format.
+    #[cfg(feature = "zlib")]
+    Deflate,
+    /// The `gzip` coding.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// The `zstd` coding.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl ContentEncoding {
+    /// Every supported coding, in the order used by [`negotiate_default`] to choose between them.
+    pub const ALL: &'static [Self] = &[
+        #[cfg(feature = "zstd")]
+        Self::Zstd,
+        #[cfg(feature = "brotli")]
+        Self::Brotli,
+        #[cfg(feature = "gzip")]
+        Self::Gzip,
+        #[cfg(feature = "zlib")]
+        Self::Deflate,
+        Self::Identity,
+    ];
+
+    /// Returns the token of this coding, as used in the `Content-Encoding` header.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Identity => "identity",
+            #[cfg(feature = "brotli")]
+            Self::Brotli => "br",
+            #[cfg(feature = "zlib")]
+            Self::Deflate => "deflate",
+            #[cfg(feature = "gzip")]
+            Self::Gzip => "gzip",
+            #[cfg(feature = "zstd")]
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Parses a coding token, ignoring case. `x-gzip` is accepted as an alias of `gzip`.
+    ///
+    /// Returns `None` for codings which are unknown or whose crate feature is not enabled.
+    pub fn from_token(token: &str) -> Option<Self> {
+        let token = if token.eq_ignore_ascii_case("x-gzip") {
+            "gzip"
+        } else {
+            token
+        };
+
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|coding| token.eq_ignore_ascii_case(coding.as_str()))
+    }
+}
+
+/// Weight of a coding in thousandths, `0` meaning it is not acceptable.
+type Weight = u16;
+
+/// Parses a weight such as `0.5`, rejecting values which RFC 9110 does not allow.
+fn parse_weight(value: &str) -> Option<Weight> {
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 3 || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let fraction = format!("{fraction:0<3}").parse::<Weight>().unwrap();
+    match integer {
+        "0" => Some(fraction),
+        "1" if fraction == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+/// Parses one element of the header into its coding and weight.
+fn parse_element(element: &str) -> Option<(&str, Weight)> {
+    let mut parts = element.split(';');
+    let coding = parts.next()?.trim();
+    if coding.is_empty() {
+        return None;
+    }
+
+    let mut weight = 1000;
+    for parameter in parts {
+        let (name, value) = parameter.split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("q") {
+            weight = parse_weight(value.trim())?;
+        }
+    }
+
+    Some((coding, weight))
+}
+
+/// Chooses the content coding of a response, given the value of the `Accept-Encoding` request
+/// header and the codings the server is willing to use in order of preference.
+///
+/// The coding with the highest weight in the header is chosen, ties being broken by the order of
+/// `preference`. `*` gives a weight to every coding not listed explicitly, and a weight of `0`
+/// makes a coding unacceptable. [`Identity`](ContentEncoding::Identity) is acceptable unless it is
+/// excluded by `identity;q=0` or `*;q=0`, and is only chosen ahead of the other codings if it is
+/// in `preference`. Elements of the header which can't be parsed are ignored.
+///
+/// An empty header means only identity is acceptable. When the header is absent any coding is
+/// acceptable, which is the same as passing `"*"`.
+///
+/// Returns `None` if none of the codings are acceptable, which a server would usually answer
+/// with `406 Not Acceptable`.
+pub fn negotiate(accept_encoding: &str, preference: &[ContentEncoding]) -> Option<ContentEncoding> {
+    let elements: Vec<_> = accept_encoding
+        .split(',')
+        .filter_map(parse_element)
+        .collect();
+
+    let explicit_weight = |coding: ContentEncoding| {
+        elements
+            .iter()
+            .filter(|(token, _)| ContentEncoding::from_token(token) == Some(coding))
+            .map(|&(_, weight)| weight)
+            .max()
+    };
+    let wildcard_weight = elements
+        .iter()
+        .filter(|(token, _)| *token == "*")
+        .map(|&(_, weight)| weight)
+        .max();
+
+    let weight = |coding: ContentEncoding| {
+        explicit_weight(coding)
+            .or(wildcard_weight)
+            .unwrap_or(match coding {
+                // Acceptable by default, but only as a last resort.
+                ContentEncoding::Identity => 1,
+                _ => 0,
+            })
+    };
+
+    let identity = (!preference.contains(&ContentEncoding::Identity))
+        .then_some(ContentEncoding::Identity)
+        .map(|coding| (coding, weight(coding).min(1)));
+
+    preference
+        .iter()
+        .map(|&coding| (coding, weight(coding)))
+        .chain(identity)
+        .filter(|&(_, weight)| weight > 0)
+        // `max_by_key` returns the last maximum, so the order is reversed to prefer earlier ones.
+        .rev()
+        .max_by_key(|&(_, weight)| weight)
+        .map(|(coding, _)| coding)
+}
+
+/// Chooses the content coding of a response like [`negotiate`], preferring the codings in the
+/// order of [`ContentEncoding::ALL`].
+pub fn negotiate_default(accept_encoding: &str) -> Option<ContentEncoding> {
+    negotiate(accept_encoding, ContentEncoding::ALL)
+}
+
+/// Parses the value of a `Content-Encoding` header into the codings applied to the content, in
+/// the order they were applied. `identity` is skipped as it does not change the content.
+///
+/// # Errors
+///
+/// Returns an [`Unsupported`](io::ErrorKind::Unsupported) error naming the first coding which is
+/// unknown or whose crate feature is not enabled.
+pub fn parse_content_encoding(content_encoding: &str) -> io::Result<Vec<ContentEncoding>> {
+    content_encoding
+        .split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .filter_map(|token| match ContentEncoding::from_token(token) {
+            Some(ContentEncoding::Identity) => None,
+            Some(coding) => Some(Ok(coding)),
+            None => Some(Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("unsupported content coding `{token}`"),
+            ))),
+        })
+        .collect()
+}
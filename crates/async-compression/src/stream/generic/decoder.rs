@@ -0,0 +1,144 @@
+use super::{Input, Output};
+use crate::{
+    codecs::{DecodeV2, DecoderLimits},
+    core::util::{PartialBuffer, WriteBuffer},
+    generic::bufread::{poll_result, Decoder as GenericDecoder},
+    Stats,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use std::{
+    io::Result,
+    ops::ControlFlow,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Decoder<S, D> {
+        #[pin]
+        stream: S,
+        decoder: D,
+        inner: GenericDecoder,
+        input: Input,
+        output: Output,
+    }
+}
+
+impl<S: Stream<Item = Result<Bytes>>, D: DecodeV2> Decoder<S, D> {
+    pub fn new(stream: S, decoder: D) -> Self {
+        Self {
+            stream,
+            decoder,
+            inner: GenericDecoder::default(),
+            input: Default::default(),
+            output: Default::default(),
+        }
+    }
+}
+
+impl<S, D> Decoder<S, D> {
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().stream
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    pub fn codec(&self) -> &D {
+        &self.decoder
+    }
+
+    pub fn multiple_members(&mut self, enabled: bool) {
+        self.inner.multiple_members(enabled);
+    }
+
+    pub fn max_output_size(&mut self, limit: Option<u64>) {
+        self.inner.max_output_size(limit);
+    }
+
+    pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+        self.inner.max_expansion_ratio(ratio);
+    }
+
+    pub fn set_chunk_size(&mut self, size: usize) {
+        self.output.set_chunk_size(size);
+    }
+
+    pub fn set_limits(&mut self, limits: DecoderLimits) -> Result<()>
+    where
+        D: DecodeV2,
+    {
+        self.decoder.set_limits(limits)
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    pub fn reset(&mut self, stream: S) -> Result<S>
+    where
+        D: DecodeV2,
+    {
+        self.decoder.reset()?;
+        self.inner.reset();
+        self.input = Input::default();
+        Ok(std::mem::replace(&mut self.stream, stream))
+    }
+}
+
+fn do_poll_next<S: Stream<Item = Result<Bytes>>>(
+    inner: &mut GenericDecoder,
+    decoder: &mut dyn DecodeV2,
+    mut stream: Pin<&mut S>,
+    input: &mut Input,
+    cx: &mut Context<'_>,
+    output: &mut WriteBuffer<'_>,
+) -> Poll<Result<()>> {
+    decoder.register_waker(cx.waker());
+    if let ControlFlow::Break(res) =
+        inner.do_poll_read(output, decoder, &mut PartialBuffer::new(&[][..]), true)
+    {
+        return poll_result(res);
+    }
+
+    loop {
+        let mut chunk = PartialBuffer::new(match input.poll_fill_buf(stream.as_mut(), cx)? {
+            Poll::Ready(chunk) => chunk,
+            Poll::Pending if output.written().is_empty() => return Poll::Pending,
+            _ => return Poll::Ready(Ok(())),
+        });
+
+        let control_flow = inner.do_poll_read(output, decoder, &mut chunk, false);
+
+        let len = chunk.written().len();
+        input.consume(len);
+
+        if let ControlFlow::Break(res) = control_flow {
+            break poll_result(res);
+        }
+    }
+}
+
+impl<S: Stream<Item = Result<Bytes>>, D: DecodeV2> Stream for Decoder<S, D> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let (inner, decoder, stream, input) = (this.inner, this.decoder, this.stream, this.input);
+
+        this.output
+            .poll_next(|output| do_poll_next(inner, decoder, stream, input, cx, output))
+    }
+}
@@ -0,0 +1,142 @@
+use super::{Input, Output};
+use crate::{
+    codecs::{EncodeV2, FlushMode},
+    core::util::{PartialBuffer, WriteBuffer},
+    generic::bufread::{poll_result, Encoder as GenericEncoder},
+    Stats,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use std::{
+    io::Result,
+    ops::ControlFlow,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Encoder<S, E> {
+        #[pin]
+        stream: S,
+        encoder: E,
+        inner: GenericEncoder,
+        input: Input,
+        output: Output,
+    }
+}
+
+impl<S: Stream<Item = Result<Bytes>>, E: EncodeV2> Encoder<S, E> {
+    pub fn new(stream: S, encoder: E) -> Self {
+        Self {
+            stream,
+            encoder,
+            inner: Default::default(),
+            input: Default::default(),
+            output: Default::default(),
+        }
+    }
+
+    pub fn with_capacity(stream: S, encoder: E, cap: usize) -> Self {
+        let mut this = Self::new(stream, encoder);
+        this.output.set_chunk_size(cap);
+        this
+    }
+}
+
+impl<S, E> Encoder<S, E> {
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().stream
+    }
+
+    pub(crate) fn get_encoder_ref(&self) -> &E {
+        &self.encoder
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    pub fn set_chunk_size(&mut self, size: usize) {
+        self.output.set_chunk_size(size);
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    pub fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()>
+    where
+        E: EncodeV2,
+    {
+        self.encoder.set_flush_mode(mode)
+    }
+
+    pub fn reset(&mut self, stream: S) -> Result<S>
+    where
+        E: EncodeV2,
+    {
+        self.encoder.reinit()?;
+        self.inner.reset();
+        self.input = Input::default();
+        Ok(std::mem::replace(&mut self.stream, stream))
+    }
+}
+
+fn do_poll_next<S: Stream<Item = Result<Bytes>>>(
+    inner: &mut GenericEncoder,
+    encoder: &mut dyn EncodeV2,
+    mut stream: Pin<&mut S>,
+    input: &mut Input,
+    cx: &mut Context<'_>,
+    output: &mut WriteBuffer<'_>,
+) -> Poll<Result<()>> {
+    // Unlike the `bufread` encoders this doesn't flush at the start of each poll, only once the
+    // input stream is pending, so that the flush points end up at the end of a chunk.
+    encoder.register_waker(cx.waker());
+    loop {
+        let (control_flow, is_pending) = match input.poll_fill_buf(stream.as_mut(), cx)? {
+            Poll::Ready(chunk) => {
+                let mut chunk = PartialBuffer::new(chunk);
+                let control_flow = inner.do_poll_read(output, encoder, Some(&mut chunk));
+                let len = chunk.written().len();
+                input.consume(len);
+                (control_flow, false)
+            }
+            Poll::Pending => (inner.do_poll_read(output, encoder, None), true),
+        };
+
+        if let ControlFlow::Break(res) = control_flow {
+            break poll_result(res);
+        }
+
+        if is_pending {
+            if output.written().is_empty() {
+                return Poll::Pending;
+            } else {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
+
+impl<S: Stream<Item = Result<Bytes>>, E: EncodeV2> Stream for Encoder<S, E> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let (inner, encoder, stream, input) = (this.inner, this.encoder, this.stream, this.input);
+
+        this.output
+            .poll_next(|output| do_poll_next(inner, encoder, stream, input, cx, output))
+    }
+}
@@ -0,0 +1,95 @@
+mod decoder;
+mod encoder;
+
+pub use self::{decoder::Decoder, encoder::Encoder};
+
+use crate::core::util::WriteBuffer;
+use bytes::{Buf, Bytes, BytesMut};
+use futures_core::Stream;
+use std::{
+    io::Result,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+const DEFAULT_CHUNK_SIZE: usize = 8192;
+
+/// The current chunk of the input stream.
+#[derive(Debug, Default)]
+struct Input {
+    chunk: Bytes,
+    eof: bool,
+}
+
+impl Input {
+    /// Like `AsyncBufRead::poll_fill_buf`, returns the rest of the current chunk, polling the
+    /// stream for the next non-empty chunk once it has been consumed, and an empty slice once the
+    /// stream has ended.
+    fn poll_fill_buf<S: Stream<Item = Result<Bytes>>>(
+        &mut self,
+        mut stream: Pin<&mut S>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<&[u8]>> {
+        while self.chunk.is_empty() && !self.eof {
+            match ready!(stream.as_mut().poll_next(cx)) {
+                Some(chunk) => self.chunk = chunk?,
+                None => self.eof = true,
+            }
+        }
+
+        Poll::Ready(Ok(&self.chunk))
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.chunk.advance(amt);
+    }
+}
+
+/// The buffer the output chunks are written to.
+#[derive(Debug)]
+struct Output {
+    buf: BytesMut,
+    chunk_size: usize,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            buf: BytesMut::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl Output {
+    fn set_chunk_size(&mut self, size: usize) {
+        assert!(size > 0, "the chunk size must be non-zero");
+        self.chunk_size = size;
+    }
+
+    /// Runs `poll` with room for up to one chunk, returning what it wrote as the next chunk, or
+    /// `None` if it completed without writing anything.
+    fn poll_next(
+        &mut self,
+        poll: impl FnOnce(&mut WriteBuffer<'_>) -> Poll<Result<()>>,
+    ) -> Poll<Option<Result<Bytes>>> {
+        debug_assert!(self.buf.is_empty());
+        self.buf.reserve(self.chunk_size);
+
+        let mut output =
+            WriteBuffer::new_uninitialized(&mut self.buf.spare_capacity_mut()[..self.chunk_size]);
+        let res = poll(&mut output);
+        let written = output.written_len();
+
+        match ready!(res) {
+            Ok(()) if written == 0 => Poll::Ready(None),
+            Ok(()) => {
+                // Safety: `WriteBuffer` only counts the bytes written to it, which it has
+                // initialized.
+                unsafe { self.buf.set_len(written) };
+                Poll::Ready(Some(Ok(self.buf.split().freeze())))
+            }
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+macro_rules! encoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        pin_project_lite::pin_project! {
+            $(#[$attr])*
+            ///
+            /// This structure implements a [`Stream`](futures_core::Stream) interface and will
+            /// read chunks of uncompressed data from an underlying stream and emit chunks of
+            /// compressed data.
+            ///
+            /// Whenever the underlying stream is pending the encoder is flushed, so that the data
+            /// read so far can be decoded from the chunks emitted so far.
+            #[derive(Debug)]
+            pub struct $name<$inner> {
+                #[pin]
+                inner: crate::stream::Encoder<$inner, crate::codecs::$name>,
+            }
+        }
+
+        impl<$inner: futures_core::Stream<Item = std::io::Result<bytes::Bytes>>> $name<$inner> {
+            $(
+                /// Creates a new encoder which will read chunks of uncompressed data from the
+                /// given stream and emit chunks of compressed data.
+                ///
+                $($inherent_methods)*
+            )*
+
+            /// Creates a new encoder with the given codec, which will read chunks of uncompressed
+            /// data from the given stream and emit chunks of compressed data.
+            pub fn with_codec(stream: $inner, codec: crate::codecs::$name) -> $name<$inner> {
+                $name {
+                   inner: crate::stream::Encoder::new(stream, codec)
+                }
+            }
+        }
+
+        impl<$inner> $name<$inner> {
+            /// Returns statistics about the data this encoder has compressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Sets how flushing this encoder ends the data compressed so far, see
+            /// [`FlushMode`](crate::FlushMode) for the modes each algorithm supports.
+            ///
+            /// Returns an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported) if the
+            /// algorithm doesn't support `mode`.
+            pub fn set_flush_mode(&mut self, mode: crate::FlushMode) -> std::io::Result<()> {
+                self.inner.set_flush_mode(mode)
+            }
+
+            /// Sets the maximum size of the emitted chunks, 8 KiB by default, or the size of a
+            /// compressed block for LZ4.
+            ///
+            /// Smaller chunks are emitted when the underlying stream is pending, or at its end.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `size` is zero.
+            pub fn set_chunk_size(&mut self, size: usize) {
+                self.inner.set_chunk_size(size);
+            }
+
+            /// Resets this encoder to compress a new stream of chunks from `stream`, returning the
+            /// previous stream.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this encoder are kept and its statistics start
+            /// over. Any data of the previous stream which hasn't been compressed yet is discarded.
+            pub fn reset(&mut self, stream: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(stream)
+            }
+
+            /// Acquires a reference to the underlying stream that this encoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying stream that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the stream which
+            /// may otherwise confuse this encoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Acquires a pinned mutable reference to the underlying stream that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the stream which
+            /// may otherwise confuse this encoder.
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
+                self.project().inner.get_pin_mut()
+            }
+
+            /// Consumes this encoder returning the underlying stream.
+            ///
+            /// Note that this may discard internal state of this encoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: futures_core::Stream<Item = std::io::Result<bytes::Bytes>>> futures_core::Stream
+            for $name<$inner>
+        {
+            type Item = std::io::Result<bytes::Bytes>;
+
+            fn poll_next(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<Self::Item>> {
+                self.project().inner.poll_next(cx)
+            }
+        }
+
+        const _: () = {
+            use crate::core::util::{_assert_send, _assert_sync};
+            use core::pin::Pin;
+            use futures_core::Stream;
+
+            type Item = std::io::Result<bytes::Bytes>;
+
+            _assert_send::<$name<Pin<Box<dyn Stream<Item = Item> + Send>>>>();
+            _assert_sync::<$name<Pin<Box<dyn Stream<Item = Item> + Sync>>>>();
+        };
+    }
+}
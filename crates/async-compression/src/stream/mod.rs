@@ -0,0 +1,29 @@
+//! Types which operate over [`Stream`](futures_core::Stream)s of [`Bytes`](bytes::Bytes) chunks,
+//! both encoders and decoders for various formats.
+//!
+//! The encoders and decoders only poll the underlying stream when they are polled themselves, so
+//! backpressure from their consumer propagates to the underlying stream. To write the output to a
+//! `Sink`, forward the stream into it.
+
+#[macro_use]
+mod macros;
+mod generic;
+
+pub(crate) use generic::{Decoder, Encoder};
+
+algos!(stream<S>);
+
+decoder! {
+    /// A decoder which detects the compression format of its input from the first bytes, and
+    /// decodes it with the decoder for that format. Only the formats whose crate features are
+    /// enabled are detected, input in any other format is passed through unchanged.
+    ///
+    /// See [`AutoDecoder`](crate::codecs::AutoDecoder) for how each format is recognised.
+    AutoDecoder<S> {
+        /// Returns the detected format, or `None` if not enough input has been read to detect
+        /// it yet.
+        pub fn format(&self) -> Option<crate::auto::Format> {
+            self.inner.codec().format()
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    codecs::{DecodeV2, DecoderLimits},
+    core::util::{PartialBuffer, WriteBuffer},
+    generic::bufread::Decoder as GenericDecoder,
+    Stats,
+};
+use std::{
+    io::{BufRead, Read, Result},
+    ops::ControlFlow,
+};
+
+#[derive(Debug)]
+pub struct Decoder<R, D> {
+    reader: R,
+    decoder: D,
+    inner: GenericDecoder,
+}
+
+impl<R: BufRead, D: DecodeV2> Decoder<R, D> {
+    pub fn new(reader: R, decoder: D) -> Self {
+        Self {
+            reader,
+            decoder,
+            inner: GenericDecoder::default(),
+        }
+    }
+}
+
+impl<R, D> Decoder<R, D> {
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    pub fn codec(&self) -> &D {
+        &self.decoder
+    }
+
+    pub fn multiple_members(&mut self, enabled: bool) {
+        self.inner.multiple_members(enabled);
+    }
+
+    pub fn max_output_size(&mut self, limit: Option<u64>) {
+        self.inner.max_output_size(limit);
+    }
+
+    pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+        self.inner.max_expansion_ratio(ratio);
+    }
+
+    pub fn set_limits(&mut self, limits: DecoderLimits) -> Result<()>
+    where
+        D: DecodeV2,
+    {
+        self.decoder.set_limits(limits)
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    pub fn reset(&mut self, reader: R) -> Result<R>
+    where
+        D: DecodeV2,
+    {
+        self.decoder.reset()?;
+        self.inner.reset();
+        Ok(std::mem::replace(&mut self.reader, reader))
+    }
+}
+
+impl<R: BufRead, D: DecodeV2> Decoder<R, D> {
+    fn do_read(&mut self, output: &mut WriteBuffer<'_>) -> Result<()> {
+        if let ControlFlow::Break(res) = self.inner.do_poll_read(
+            output,
+            &mut self.decoder,
+            &mut PartialBuffer::new(&[][..]),
+            true,
+        ) {
+            return res;
+        }
+
+        loop {
+            let mut input = PartialBuffer::new(self.reader.fill_buf()?);
+
+            let control_flow =
+                self.inner
+                    .do_poll_read(output, &mut self.decoder, &mut input, false);
+
+            let bytes_read = input.written().len();
+            self.reader.consume(bytes_read);
+
+            if let ControlFlow::Break(res) = control_flow {
+                break res;
+            }
+
+            // Like other blocking readers, return what is available rather than blocking on the
+            // reader again to fill the rest of the buffer.
+            if output.written_len() > 0 {
+                break Ok(());
+            }
+        }
+    }
+}
+
+impl<R: BufRead, D: DecodeV2> Read for Decoder<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut output = WriteBuffer::new_initialized(buf);
+        crate::sync::block_on_codec(|waker| {
+            if let Some(waker) = waker {
+                self.decoder.register_waker(waker);
+            }
+            self.do_read(&mut output)
+        })?;
+        Ok(output.written_len())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    codecs::{EncodeV2, FlushMode},
+    core::util::{PartialBuffer, WriteBuffer},
+    generic::bufread::Encoder as GenericEncoder,
+    Stats,
+};
+use std::{
+    io::{BufRead, Read, Result},
+    ops::ControlFlow,
+};
+
+#[derive(Debug)]
+pub struct Encoder<R, E> {
+    reader: R,
+    encoder: E,
+    inner: GenericEncoder,
+}
+
+impl<R: BufRead, E: EncodeV2> Encoder<R, E> {
+    pub fn new(reader: R, encoder: E) -> Self {
+        Self {
+            reader,
+            encoder,
+            inner: Default::default(),
+        }
+    }
+
+    pub fn with_capacity(reader: R, encoder: E, _cap: usize) -> Self {
+        Self::new(reader, encoder)
+    }
+}
+
+impl<R, E> Encoder<R, E> {
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub(crate) fn get_encoder_ref(&self) -> &E {
+        &self.encoder
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    pub fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()>
+    where
+        E: EncodeV2,
+    {
+        self.encoder.set_flush_mode(mode)
+    }
+
+    pub fn reset(&mut self, reader: R) -> Result<R>
+    where
+        E: EncodeV2,
+    {
+        self.encoder.reinit()?;
+        self.inner.reset();
+        Ok(std::mem::replace(&mut self.reader, reader))
+    }
+}
+
+impl<R: BufRead, E: EncodeV2> Encoder<R, E> {
+    fn do_read(&mut self, output: &mut WriteBuffer<'_>) -> Result<()> {
+        // The input is never pending, so unlike the asynchronous encoders this only flushes the
+        // encoder when finishing, and returns as soon as some output is available instead.
+        loop {
+            let mut input = PartialBuffer::new(self.reader.fill_buf()?);
+
+            let control_flow = self
+                .inner
+                .do_poll_read(output, &mut self.encoder, Some(&mut input));
+
+            let len = input.written().len();
+            self.reader.consume(len);
+
+            if let ControlFlow::Break(res) = control_flow {
+                break res;
+            }
+
+            if output.written_len() > 0 {
+                break Ok(());
+            }
+        }
+    }
+}
+
+impl<R: BufRead, E: EncodeV2> Read for Encoder<R, E> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut output = WriteBuffer::new_initialized(buf);
+        crate::sync::block_on_codec(|waker| {
+            if let Some(waker) = waker {
+                self.encoder.register_waker(waker);
+            }
+            self.do_read(&mut output)
+        })?;
+        Ok(output.written_len())
+    }
+}
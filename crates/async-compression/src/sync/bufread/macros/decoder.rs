@@ -0,0 +1,144 @@
+macro_rules! decoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> @new { $($new:tt)* } $({ $($inherent_methods:tt)* })*) => {
+        $(#[$attr])*
+        ///
+        /// This structure implements a [`Read`](std::io::Read) interface and will read compressed
+        /// data from an underlying stream and emit a stream of uncompressed data.
+        #[derive(Debug)]
+        pub struct $name<$inner> {
+            inner: crate::sync::bufread::Decoder<$inner, crate::codecs::$name>,
+        }
+
+        impl<$inner: std::io::BufRead> $name<$inner> {
+            $($new)*
+
+            /// Creates a new decoder with the given codec, which will read compressed data from the given stream and
+            /// emit an uncompressed stream.
+            pub fn with_codec(read: $inner, codec: crate::codecs::$name) -> $name<$inner> {
+                $name {
+                    inner: crate::sync::bufread::Decoder::new(read, codec)
+                }
+            }
+
+            $($($inherent_methods)*)*
+        }
+
+        impl<$inner> $name<$inner> {
+            /// Configure multi-member/frame decoding, if enabled this will reset the decoder state
+            /// when reaching the end of a compressed member/frame and expect either EOF or another
+            /// compressed member/frame to follow it in the stream.
+            pub fn multiple_members(&mut self, enabled: bool) {
+                self.inner.multiple_members(enabled);
+            }
+
+            /// Limits the decompressed data to `limit` bytes in total, or removes the limit if `None`.
+            ///
+            /// Once the limit is reached, reading returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) instead of more data.
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            /// Limits the decompressed data to `ratio` times the compressed data consumed so far, or
+            /// removes the limit if `None`.
+            ///
+            /// Once the limit is reached, reading returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) instead of more data.
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            /// Limits the memory used to decode the streams started from now on, such as the window
+            /// announced by their header.
+            ///
+            /// Streams needing more memory fail with an error of kind
+            /// [`OutOfMemory`](std::io::ErrorKind::OutOfMemory). Returns an error if this decoder
+            /// can't enforce `limits`, or if every stream of its format needs more memory than
+            /// they allow.
+            pub fn set_limits(&mut self, limits: crate::DecoderLimits) -> std::io::Result<()> {
+                self.inner.set_limits(limits)
+            }
+
+            /// Returns statistics about the data this decoder has decompressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Resets this decoder to decode a new stream read from `reader`, returning the
+            /// previous reader.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this decoder are kept and its statistics start
+            /// over. Any data of the previous stream which hasn't been decoded yet is discarded.
+            pub fn reset(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(reader)
+            }
+
+            /// Acquires a reference to the underlying reader that this decoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying reader that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this decoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Consumes this decoder returning the underlying reader.
+            ///
+            /// Note that this may discard internal state of this decoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::Read for $name<$inner> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                std::io::Read::read(&mut self.inner, buf)
+            }
+        }
+
+        impl<$inner: std::io::Write> std::io::Write for $name<$inner> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.get_mut().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.get_mut().flush()
+            }
+
+            fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+                self.get_mut().write_vectored(bufs)
+            }
+        }
+
+        const _: () = {
+            use crate::core::util::{_assert_send, _assert_sync};
+            use std::io::BufRead;
+
+            _assert_send::<$name<Box<dyn BufRead + Send>>>();
+            _assert_sync::<$name<Box<dyn BufRead + Sync>>>();
+        };
+    };
+
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        decoder! {
+            $(#[$attr])*
+            $name<$inner> @new {
+                /// Creates a new decoder which will read compressed data from the given stream and
+                /// emit an uncompressed stream.
+                pub fn new(read: $inner) -> $name<$inner> {
+                    $name {
+                        inner: crate::sync::bufread::Decoder::new(read, crate::codecs::$name::new()),
+                    }
+                }
+            }
+            $({ $($inherent_methods)* })*
+        }
+    };
+}
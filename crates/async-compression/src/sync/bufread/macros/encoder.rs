@@ -0,0 +1,105 @@
+macro_rules! encoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        $(#[$attr])*
+        ///
+        /// This structure implements a [`Read`](std::io::Read) interface and will read
+        /// uncompressed data from an underlying stream and emit a stream of compressed data.
+        #[derive(Debug)]
+        pub struct $name<$inner> {
+            inner: crate::sync::bufread::Encoder<$inner, crate::codecs::$name>,
+        }
+
+        impl<$inner: std::io::BufRead> $name<$inner> {
+            $(
+                /// Creates a new encoder which will read uncompressed data from the given stream
+                /// and emit an compressed stream.
+                ///
+                $($inherent_methods)*
+            )*
+
+            /// Creates a new encoder with the given codec, which will read uncompressed data from the given stream
+            /// and emit an compressed stream.
+            pub fn with_codec(read: $inner, codec: crate::codecs::$name) -> $name<$inner> {
+                $name {
+                   inner: crate::sync::bufread::Encoder::new(read, codec)
+                }
+            }
+        }
+
+        impl<$inner> $name<$inner> {
+            /// Returns statistics about the data this encoder has compressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Sets how flushing this encoder ends the data compressed so far, see
+            /// [`FlushMode`](crate::FlushMode) for the modes each algorithm supports.
+            ///
+            /// Returns an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported) if the
+            /// algorithm doesn't support `mode`.
+            pub fn set_flush_mode(&mut self, mode: crate::FlushMode) -> std::io::Result<()> {
+                self.inner.set_flush_mode(mode)
+            }
+
+            /// Resets this encoder to compress a new stream read from `reader`, returning the
+            /// previous reader.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this encoder are kept and its statistics start
+            /// over. Any data of the previous stream which hasn't been compressed yet is discarded.
+            pub fn reset(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(reader)
+            }
+
+            /// Acquires a reference to the underlying reader that this encoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying reader that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this encoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Consumes this encoder returning the underlying reader.
+            ///
+            /// Note that this may discard internal state of this encoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::Read for $name<$inner> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                std::io::Read::read(&mut self.inner, buf)
+            }
+        }
+
+        impl<$inner: std::io::Write> std::io::Write for $name<$inner> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.get_mut().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.get_mut().flush()
+            }
+
+            fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+                self.get_mut().write_vectored(bufs)
+            }
+        }
+
+        const _: () = {
+            use crate::core::util::{_assert_send, _assert_sync};
+            use std::io::BufRead;
+
+            _assert_send::<$name<Box<dyn BufRead + Send>>>();
+            _assert_sync::<$name<Box<dyn BufRead + Sync>>>();
+        };
+    }
+}
@@ -0,0 +1,25 @@
+//! Types which operate over [`BufRead`](std::io::BufRead) streams, both encoders and decoders for
+//! various formats.
+
+#[macro_use]
+mod macros;
+mod generic;
+
+pub(crate) use generic::{Decoder, Encoder};
+
+algos!(sync::bufread<R>);
+
+decoder! {
+    /// A decoder which detects the compression format of its input from the first bytes, and
+    /// decodes it with the decoder for that format. Only the formats whose crate features are
+    /// enabled are detected, input in any other format is passed through unchanged.
+    ///
+    /// See [`AutoDecoder`](crate::codecs::AutoDecoder) for how each format is recognised.
+    AutoDecoder<R> {
+        /// Returns the detected format, or `None` if not enough input has been read to detect
+        /// it yet.
+        pub fn format(&self) -> Option<crate::auto::Format> {
+            self.inner.codec().format()
+        }
+    }
+}
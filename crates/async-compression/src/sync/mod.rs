@@ -0,0 +1,45 @@
+//! Implementations for the blocking [`std::io`] traits.
+//!
+//! The types in these modules drive the same state machines as the asynchronous implementations,
+//! so they take the same parameters and decode multiple members/frames the same way.
+
+pub mod bufread;
+pub mod write;
+
+use crate::generic::is_pending;
+use std::{
+    io::Result,
+    sync::Arc,
+    task::{Wake, Waker},
+    thread::{self, Thread},
+};
+
+/// Unparks the thread blocked on a codec waiting for its worker threads.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Returns a waker which unparks the current thread.
+fn thread_waker() -> Waker {
+    Waker::from(Arc::new(ThreadWaker(thread::current())))
+}
+
+/// Runs `f` until it stops returning the error of a codec waiting for its worker threads, parking
+/// the thread in between. Once it has returned that error, `f` is given a waker to register with
+/// the codec before trying again.
+fn block_on_codec<T>(mut f: impl FnMut(Option<&Waker>) -> Result<T>) -> Result<T> {
+    let mut waker = None;
+    loop {
+        match f(waker.as_ref()) {
+            Err(err) if is_pending(&err) => match waker {
+                None => waker = Some(thread_waker()),
+                Some(_) => thread::park(),
+            },
+            res => return res,
+        }
+    }
+}
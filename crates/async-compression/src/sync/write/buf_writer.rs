@@ -0,0 +1,131 @@
+use crate::generic::write::{AsyncBufWrite, BufWriter as GenericBufWriter, Buffer};
+use std::{
+    io::{self, Write},
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+#[derive(Debug)]
+pub struct BufWriter<W> {
+    writer: W,
+    inner: GenericBufWriter,
+}
+
+impl<W> BufWriter<W> {
+    /// Creates a new `BufWriter` with a default buffer capacity. The default is currently 8 KB,
+    /// but may change in the future.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            inner: GenericBufWriter::new(),
+        }
+    }
+
+    /// Creates a new `BufWriter` with the specified buffer capacity.
+    pub fn with_capacity(cap: usize, writer: W) -> Self {
+        Self {
+            writer,
+            inner: GenericBufWriter::with_capacity(cap),
+        }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// It is inadvisable to directly write to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consumes this `BufWriter`, returning the underlying writer.
+    ///
+    /// Note that any leftover data in the internal buffer is lost.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Replaces the underlying writer, returning the previous one.
+    ///
+    /// Note that any leftover data in the internal buffer is lost.
+    pub fn reset(&mut self, writer: W) -> W {
+        self.inner.reset();
+        std::mem::replace(&mut self.writer, writer)
+    }
+}
+
+fn get_poll_write<W: Write>(
+    writer: &mut W,
+) -> impl for<'buf> FnMut(&'buf [u8]) -> Poll<io::Result<usize>> + '_ {
+    move |buf| loop {
+        match writer.write(buf) {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            res => break Poll::Ready(res),
+        }
+    }
+}
+
+impl<W: Write> BufWriter<W> {
+    /// Writes the buffered data out and flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        block_on(|_| self.inner.flush_buf(&mut get_poll_write(&mut self.writer)))?;
+        self.writer.flush()
+    }
+
+    /// Runs `poll` to completion with this writer as an [`AsyncBufWrite`].
+    pub fn block_on<T>(
+        &mut self,
+        mut poll: impl FnMut(&mut Context<'_>, Pin<&mut dyn AsyncBufWrite>) -> Poll<io::Result<T>>,
+    ) -> io::Result<T> {
+        block_on(|cx| {
+            let mut this = &mut *self;
+            let writer: Pin<&mut &mut Self> = Pin::new(&mut this);
+            poll(cx, writer)
+        })
+    }
+}
+
+// Implemented on `&mut BufWriter<W>`, which is always `Unpin`, so that the generic encoders and
+// decoders can write to it without requiring `W: Unpin`.
+impl<W: Write> AsyncBufWrite for &mut BufWriter<W> {
+    fn poll_partial_flush_buf(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<io::Result<Buffer<'_>>> {
+        let this = &mut **self.get_mut();
+        this.inner
+            .poll_partial_flush_buf(&mut get_poll_write(&mut this.writer))
+    }
+}
+
+/// Runs `poll` to completion. The underlying writer blocks rather than returning
+/// `Poll::Pending`, so only codecs waiting for their worker threads do, in which case the thread
+/// is parked until they wake it.
+fn block_on<T>(mut poll: impl FnMut(&mut Context<'_>) -> Poll<io::Result<T>>) -> io::Result<T> {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+
+    // Safety: the functions of `VTABLE` never use the data pointer.
+    let waker = unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) };
+    if let Poll::Ready(res) = poll(&mut Context::from_waker(&waker)) {
+        return res;
+    }
+
+    // Polling again registers the waker of this thread with the codec before parking on it.
+    let waker = crate::sync::thread_waker();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match poll(&mut cx) {
+            Poll::Ready(res) => return res,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{
+    codecs::{DecodeV2, DecoderLimits},
+    generic::write::{AsyncBufWrite, Decoder as GenericDecoder},
+    sync::write::BufWriter,
+    Stats,
+};
+use std::{
+    io::{self, Write},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[derive(Debug)]
+pub struct Decoder<W, D> {
+    writer: BufWriter<W>,
+    decoder: D,
+    inner: GenericDecoder,
+}
+
+impl<W: Write, D: DecodeV2> Decoder<W, D> {
+    pub fn new(writer: W, decoder: D) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            decoder,
+            inner: Default::default(),
+        }
+    }
+
+    fn block_on<T>(
+        &mut self,
+        mut poll: impl FnMut(
+            &mut GenericDecoder,
+            &mut Context<'_>,
+            Pin<&mut dyn AsyncBufWrite>,
+            &mut D,
+        ) -> Poll<io::Result<T>>,
+    ) -> io::Result<T> {
+        let Self {
+            writer,
+            decoder,
+            inner,
+        } = self;
+        writer.block_on(|cx, writer| poll(inner, cx, writer, decoder))
+    }
+}
+
+impl<W, D> Decoder<W, D> {
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    pub fn codec(&self) -> &D {
+        &self.decoder
+    }
+
+    pub fn max_output_size(&mut self, limit: Option<u64>) {
+        self.inner.max_output_size(limit);
+    }
+
+    pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+        self.inner.max_expansion_ratio(ratio);
+    }
+
+    pub fn set_limits(&mut self, limits: DecoderLimits) -> io::Result<()>
+    where
+        D: DecodeV2,
+    {
+        self.decoder.set_limits(limits)
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    pub fn reset(&mut self, writer: W) -> io::Result<W>
+    where
+        D: DecodeV2,
+    {
+        self.decoder.reset()?;
+        self.inner.reset();
+        Ok(self.writer.reset(writer))
+    }
+}
+
+impl<W: Write, D: DecodeV2> Decoder<W, D> {
+    fn do_flush(&mut self) -> io::Result<()> {
+        self.block_on(|inner, cx, writer, decoder| inner.do_poll_flush(cx, writer, decoder))
+    }
+
+    pub fn try_finish(&mut self) -> io::Result<()> {
+        self.inner.do_close();
+
+        self.do_flush()?;
+
+        if self.inner.is_done() {
+            self.writer.flush()
+        } else {
+            Err(io::Error::other("Attempt to close before finishing input"))
+        }
+    }
+}
+
+impl<W: Write, D: DecodeV2> Write for Decoder<W, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.block_on(|inner, cx, writer, decoder| inner.poll_write(cx, buf, writer, decoder))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.do_flush()?;
+        self.writer.flush()
+    }
+}
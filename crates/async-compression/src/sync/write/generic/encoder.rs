@@ -0,0 +1,107 @@
+use crate::{
+    codecs::{EncodeV2, FlushMode},
+    generic::write::{AsyncBufWrite, Encoder as GenericEncoder},
+    sync::write::BufWriter,
+    Stats,
+};
+use std::{
+    io::{self, Write},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[derive(Debug)]
+pub struct Encoder<W, E> {
+    writer: BufWriter<W>,
+    encoder: E,
+    inner: GenericEncoder,
+}
+
+impl<W, E> Encoder<W, E> {
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    pub(crate) fn get_encoder_ref(&self) -> &E {
+        &self.encoder
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    pub fn new(writer: W, encoder: E) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            encoder,
+            inner: Default::default(),
+        }
+    }
+
+    pub fn with_capacity(writer: W, encoder: E, cap: usize) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(cap, writer),
+            encoder,
+            inner: Default::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    pub fn set_flush_mode(&mut self, mode: FlushMode) -> io::Result<()>
+    where
+        E: EncodeV2,
+    {
+        self.encoder.set_flush_mode(mode)
+    }
+
+    pub fn reset(&mut self, writer: W) -> io::Result<W>
+    where
+        E: EncodeV2,
+    {
+        self.encoder.reinit()?;
+        self.inner.reset();
+        Ok(self.writer.reset(writer))
+    }
+}
+
+impl<W: Write, E: EncodeV2> Encoder<W, E> {
+    fn block_on<T>(
+        &mut self,
+        mut poll: impl FnMut(
+            &mut GenericEncoder,
+            &mut Context<'_>,
+            Pin<&mut dyn AsyncBufWrite>,
+            &mut E,
+        ) -> Poll<io::Result<T>>,
+    ) -> io::Result<T> {
+        let Self {
+            writer,
+            encoder,
+            inner,
+        } = self;
+        writer.block_on(|cx, writer| poll(inner, cx, writer, encoder))
+    }
+
+    pub fn try_finish(&mut self) -> io::Result<()> {
+        self.block_on(|inner, cx, writer, encoder| inner.do_poll_close(cx, writer, encoder))?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write, E: EncodeV2> Write for Encoder<W, E> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.block_on(|inner, cx, writer, encoder| inner.poll_write(cx, buf, writer, encoder))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.block_on(|inner, cx, writer, encoder| inner.do_poll_flush(cx, writer, encoder))?;
+        self.writer.flush()
+    }
+}
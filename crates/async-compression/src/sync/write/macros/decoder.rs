@@ -0,0 +1,163 @@
+macro_rules! decoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> @new { $($new:tt)* } $({ $($inherent_methods:tt)* })*) => {
+        $(#[$attr])*
+        ///
+        /// This structure implements a [`Write`](std::io::Write) interface and will take in
+        /// compressed data and write it uncompressed to an underlying stream.
+        #[derive(Debug)]
+        pub struct $name<$inner> {
+            inner: crate::sync::write::Decoder<$inner, crate::codecs::$name>,
+        }
+
+        impl<$inner: std::io::Write> $name<$inner> {
+            $($new)*
+
+            /// Creates a new decoder which will take in compressed data and write it, uncompressed,
+            /// to the given stream.
+            pub fn with_codec(read: $inner, codec: crate::codecs::$name) -> $name<$inner> {
+                $name {
+                   inner: crate::sync::write::Decoder::new(read, codec)
+                }
+            }
+
+            $($($inherent_methods)*)*
+        }
+
+        impl<$inner> $name<$inner> {
+            /// Limits the decompressed data to `limit` bytes in total, or removes the limit if `None`.
+            ///
+            /// Once the limit is reached, writing returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) and the data past it is discarded.
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            /// Limits the decompressed data to `ratio` times the compressed data consumed so far, or
+            /// removes the limit if `None`.
+            ///
+            /// Once the limit is reached, writing returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) and the data past it is discarded.
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            /// Limits the memory used to decode the streams started from now on, such as the window
+            /// announced by their header.
+            ///
+            /// Streams needing more memory fail with an error of kind
+            /// [`OutOfMemory`](std::io::ErrorKind::OutOfMemory). Returns an error if this decoder
+            /// can't enforce `limits`, or if every stream of its format needs more memory than
+            /// they allow.
+            pub fn set_limits(&mut self, limits: crate::DecoderLimits) -> std::io::Result<()> {
+                self.inner.set_limits(limits)
+            }
+
+            /// Returns statistics about the data this decoder has decompressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Resets this decoder to decode a new stream into `writer`, returning the previous
+            /// writer.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this decoder are kept and its statistics start
+            /// over. Any decoded data not yet written to the previous writer is discarded, so the
+            /// previous stream should be finished first.
+            pub fn reset(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(writer)
+            }
+
+            /// Acquires a reference to the underlying writer that this decoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying writer that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the writer which
+            /// may otherwise confuse this decoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Consumes this decoder returning the underlying writer.
+            ///
+            /// Note that this may discard internal state of this decoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: std::io::Write> $name<$inner> {
+            /// Finishes decoding, writing any remaining data to the underlying writer and flushing
+            /// it, without consuming this decoder.
+            ///
+            /// This must be called, or [`finish`](Self::finish), once all the data has been
+            /// written, and returns an error if the compressed stream written so far is
+            /// incomplete.
+            pub fn try_finish(&mut self) -> std::io::Result<()> {
+                self.inner.try_finish()
+            }
+
+            /// Finishes decoding like [`try_finish`](Self::try_finish), returning the
+            /// underlying writer.
+            pub fn finish(mut self) -> std::io::Result<$inner> {
+                self.try_finish()?;
+                Ok(self.into_inner())
+            }
+        }
+
+        impl<$inner: std::io::Write> std::io::Write for $name<$inner> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                std::io::Write::write(&mut self.inner, buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                std::io::Write::flush(&mut self.inner)
+            }
+        }
+
+        impl<$inner: std::io::Read> std::io::Read for $name<$inner> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.get_mut().read(buf)
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::BufRead for $name<$inner> {
+            fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+                self.get_mut().fill_buf()
+            }
+
+            fn consume(&mut self, amt: usize) {
+                self.get_mut().consume(amt)
+            }
+        }
+
+        const _: () = {
+            use crate::core::util::{_assert_send, _assert_sync};
+            use std::io::Write;
+
+            _assert_send::<$name<Box<dyn Write + Send>>>();
+            _assert_sync::<$name<Box<dyn Write + Sync>>>();
+        };
+    };
+
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        decoder! {
+            $(#[$attr])*
+            $name<$inner> @new {
+                /// Creates a new decoder which will take in compressed data and write it, uncompressed,
+                /// to the given stream.
+                pub fn new(read: $inner) -> $name<$inner> {
+                    $name {
+                        inner: crate::sync::write::Decoder::new(read, crate::codecs::$name::new()),
+                    }
+                }
+            }
+            $({ $($inherent_methods)* })*
+        }
+    };
+}
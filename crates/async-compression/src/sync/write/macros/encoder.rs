@@ -0,0 +1,130 @@
+macro_rules! encoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        $(#[$attr])*
+        ///
+        /// This structure implements a [`Write`](std::io::Write) interface and will take in
+        /// uncompressed data and write it compressed to an underlying stream.
+        #[derive(Debug)]
+        pub struct $name<$inner> {
+            inner: crate::sync::write::Encoder<$inner, crate::codecs::$name>,
+        }
+
+        impl<$inner: std::io::Write> $name<$inner> {
+            $(
+                /// Creates a new encoder which will take in uncompressed data and write it
+                /// compressed to the given stream.
+                ///
+                $($inherent_methods)*
+            )*
+
+            /// Creates a new encoder with the given codec, which will take in uncompressed data and write it,
+            /// compressed, to the given stream.
+            pub fn with_codec(read: $inner, codec: crate::codecs::$name) -> $name<$inner> {
+                $name {
+                   inner: crate::sync::write::Encoder::new(read, codec)
+                }
+            }
+        }
+
+        impl<$inner> $name<$inner> {
+            /// Returns statistics about the data this encoder has compressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Sets how flushing this encoder ends the data compressed so far, see
+            /// [`FlushMode`](crate::FlushMode) for the modes each algorithm supports.
+            ///
+            /// Returns an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported) if the
+            /// algorithm doesn't support `mode`.
+            pub fn set_flush_mode(&mut self, mode: crate::FlushMode) -> std::io::Result<()> {
+                self.inner.set_flush_mode(mode)
+            }
+
+            /// Resets this encoder to compress a new stream into `writer`, returning the previous
+            /// writer.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this encoder are kept and its statistics start
+            /// over. Any compressed data not yet written to the previous writer is discarded, so
+            /// the previous stream should be finished first.
+            pub fn reset(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(writer)
+            }
+
+            /// Acquires a reference to the underlying writer that this encoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying writer that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the writer which
+            /// may otherwise confuse this encoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Consumes this encoder returning the underlying writer.
+            ///
+            /// Note that this may discard internal state of this encoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: std::io::Write> $name<$inner> {
+            /// Finishes the compressed stream, writing any remaining data to the underlying writer and
+            /// flushing it, without consuming this encoder.
+            ///
+            /// This must be called, or [`finish`](Self::finish), once all the data has been
+            /// written, as the stream is not finished when this encoder is dropped.
+            pub fn try_finish(&mut self) -> std::io::Result<()> {
+                self.inner.try_finish()
+            }
+
+            /// Finishes the compressed stream like [`try_finish`](Self::try_finish), returning the
+            /// underlying writer.
+            pub fn finish(mut self) -> std::io::Result<$inner> {
+                self.try_finish()?;
+                Ok(self.into_inner())
+            }
+        }
+
+        impl<$inner: std::io::Write> std::io::Write for $name<$inner> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                std::io::Write::write(&mut self.inner, buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                std::io::Write::flush(&mut self.inner)
+            }
+        }
+
+        impl<$inner: std::io::Read> std::io::Read for $name<$inner> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.get_mut().read(buf)
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::BufRead for $name<$inner> {
+            fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+                self.get_mut().fill_buf()
+            }
+
+            fn consume(&mut self, amt: usize) {
+                self.get_mut().consume(amt)
+            }
+        }
+
+        const _: () = {
+            use crate::core::util::{_assert_send, _assert_sync};
+            use std::io::Write;
+
+            _assert_send::<$name<Box<dyn Write + Send>>>();
+            _assert_sync::<$name<Box<dyn Write + Sync>>>();
+        };
+    }
+}
@@ -0,0 +1,18 @@
+//! Types which operate over [`Write`](std::io::Write) streams, both encoders and decoders for
+//! various formats.
+//!
+//! Like [`flate2`]'s writers, the encoders and decoders here do not finish the stream when
+//! dropped, `finish` or `try_finish` must be called once all data has been written.
+//!
+//! [`flate2`]: https://docs.rs/flate2
+
+#[macro_use]
+mod macros;
+mod generic;
+
+mod buf_writer;
+
+pub(crate) use self::buf_writer::BufWriter;
+use self::generic::{Decoder, Encoder};
+
+algos!(sync::write<W>);
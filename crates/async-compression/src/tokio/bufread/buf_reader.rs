@@ -0,0 +1,97 @@
+// Originally sourced from `tokio::io::BufReader`, which needs the `io-util` feature of tokio, and
+// reduced to what is needed to chain decoders and to buffer the input of the `read` types.
+
+use pin_project_lite::pin_project;
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+pin_project! {
+    #[derive(Debug)]
+    pub(crate) struct BufReader<R> {
+        #[pin]
+        inner: R,
+        buf: Box<[u8]>,
+        pos: usize,
+        cap: usize,
+    }
+}
+
+impl<R> BufReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    pub(crate) fn with_capacity(capacity: usize, inner: R) -> Self {
+        Self {
+            inner,
+            buf: vec![0; capacity].into_boxed_slice(),
+            pos: 0,
+            cap: 0,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub(crate) fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    /// Note that any leftover data in the internal buffer is lost.
+    pub(crate) fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Replaces the underlying reader, keeping the internal buffer but losing any leftover data
+    /// in it.
+    pub(crate) fn replace_inner(&mut self, inner: R) -> R {
+        self.pos = 0;
+        self.cap = 0;
+        std::mem::replace(&mut self.inner, inner)
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for BufReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let rem = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let amt = rem.len().min(buf.remaining());
+        buf.put_slice(&rem[..amt]);
+        self.consume(amt);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R: AsyncRead> AsyncBufRead for BufReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.project();
+
+        if *this.pos >= *this.cap {
+            let mut buf = ReadBuf::new(this.buf);
+            ready!(this.inner.poll_read(cx, &mut buf))?;
+            *this.cap = buf.filled().len();
+            *this.pos = 0;
+        }
+
+        Poll::Ready(Ok(&this.buf[*this.pos..*this.cap]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.project();
+        *this.pos = (*this.pos + amt).min(*this.cap);
+    }
+}
@@ -1,5 +1,5 @@
 macro_rules! decoder {
-    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> @new { $($new:tt)* } $({ $($inherent_methods:tt)* })*) => {
         pin_project_lite::pin_project! {
             $(#[$attr])*
             ///
@@ -13,13 +13,7 @@ macro_rules! decoder {
         }
 
         impl<$inner: tokio::io::AsyncBufRead> $name<$inner> {
-            /// Creates a new decoder which will read compressed data from the given stream and
-            /// emit an uncompressed stream.
-            pub fn new(read: $inner) -> $name<$inner> {
-                $name {
-                    inner: crate::tokio::bufread::Decoder::new(read, crate::codecs::$name::new()),
-                }
-            }
+            $($new)*
 
             /// Creates a new decoder with the given codec, which will read compressed data from the given stream and
             /// emit an uncompressed stream.
@@ -40,6 +34,49 @@ macro_rules! decoder {
                 self.inner.multiple_members(enabled);
             }
 
+            /// Limits the decompressed data to `limit` bytes in total, or removes the limit if `None`.
+            ///
+            /// Once the limit is reached, reading returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) instead of more data.
+            pub fn max_output_size(&mut self, limit: Option<u64>) {
+                self.inner.max_output_size(limit);
+            }
+
+            /// Limits the decompressed data to `ratio` times the compressed data consumed so far, or
+            /// removes the limit if `None`.
+            ///
+            /// Once the limit is reached, reading returns an error of kind
+            /// [`FileTooLarge`](std::io::ErrorKind::FileTooLarge) instead of more data.
+            pub fn max_expansion_ratio(&mut self, ratio: Option<u64>) {
+                self.inner.max_expansion_ratio(ratio);
+            }
+
+            /// Limits the memory used to decode the streams started from now on, such as the window
+            /// announced by their header.
+            ///
+            /// Streams needing more memory fail with an error of kind
+            /// [`OutOfMemory`](std::io::ErrorKind::OutOfMemory). Returns an error if this decoder
+            /// can't enforce `limits`, or if every stream of its format needs more memory than
+            /// they allow.
+            pub fn set_limits(&mut self, limits: crate::DecoderLimits) -> std::io::Result<()> {
+                self.inner.set_limits(limits)
+            }
+
+            /// Returns statistics about the data this decoder has decompressed so far.
+            pub fn stats(&self) -> crate::Stats {
+                self.inner.stats()
+            }
+
+            /// Resets this decoder to decode a new stream read from `reader`, returning the
+            /// previous reader.
+            ///
+            /// The codec is reset rather than created again, keeping its allocations where the
+            /// algorithm allows it. The settings of this decoder are kept and its statistics start
+            /// over. Any data of the previous stream which hasn't been decoded yet is discarded.
+            pub fn reset(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset(reader)
+            }
+
             /// Acquires a reference to the underlying reader that this decoder is wrapping.
             pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
@@ -126,5 +163,21 @@ macro_rules! decoder {
             _assert_send::<$name<Pin<Box<dyn AsyncBufRead + Send>>>>();
             _assert_sync::<$name<Pin<Box<dyn AsyncBufRead + Sync>>>>();
         };
-    }
+    };
+
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($inherent_methods:tt)* })*) => {
+        decoder! {
+            $(#[$attr])*
+            $name<$inner> @new {
+                /// Creates a new decoder which will read compressed data from the given stream and
+                /// emit an uncompressed stream.
+                pub fn new(read: $inner) -> $name<$inner> {
+                    $name {
+                        inner: crate::tokio::bufread::Decoder::new(read, crate::codecs::$name::new()),
+                    }
+                }
+            }
+            $({ $($inherent_methods)* })*
+        }
+    };
 }
@@ -3,8 +3,25 @@
 
 #[macro_use]
 mod macros;
+mod buf_reader;
 mod generic;
 
+pub(crate) use buf_reader::BufReader;
 pub(crate) use generic::{Decoder, Encoder};
 
 algos!(tokio::bufread<R>);
+
+decoder! {
+    /// A decoder which detects the compression format of its input from the first bytes, and
+    /// decodes it with the decoder for that format. Only the formats whose crate features are
+    /// enabled are detected, input in any other format is passed through unchanged.
+    ///
+    /// See [`AutoDecoder`](crate::codecs::AutoDecoder) for how each format is recognised.
+    AutoDecoder<R> {
+        /// Returns the detected format, or `None` if not enough input has been read to detect
+        /// it yet.
+        pub fn format(&self) -> Option<crate::auto::Format> {
+            self.inner.codec().format()
+        }
+    }
+}
@@ -1,4 +1,13 @@
 //! Implementations for IO traits exported by [`tokio` v1.x](::tokio).
 
 pub mod bufread;
+pub mod negotiate;
+mod oneshot;
+pub mod read;
+#[cfg(feature = "xz")]
+pub mod seek;
 pub mod write;
+#[cfg(feature = "zip")]
+pub mod zip;
+
+pub use self::oneshot::decompress_to_vec;
@@ -0,0 +1,55 @@
+use std::{
+    future::poll_fn,
+    io::{Error, ErrorKind, Result},
+    pin::pin,
+    task::{ready, Poll},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Reads `reader`, such as a decoder of the [`bufread`](super::bufread) module, to its end and
+/// returns the data read.
+///
+/// See [`oneshot`](crate::oneshot) for compressing and decompressing data which is already in
+/// memory.
+///
+/// # Errors
+///
+/// Returns a [`FileTooLarge`](ErrorKind::FileTooLarge) error if `reader` returns more than
+/// `max_len` bytes, without reading more than one byte past that limit.
+pub async fn decompress_to_vec<R: AsyncRead>(reader: R, max_len: usize) -> Result<Vec<u8>> {
+    let mut reader = pin!(reader);
+    let mut output = Vec::new();
+    // One more byte than allowed is read to tell whether the limit is exceeded.
+    let limit = max_len.saturating_add(1);
+
+    let mut filled = 0;
+
+    poll_fn(|cx| {
+        while filled < limit {
+            if filled == output.len() {
+                output.resize(filled + filled.max(4096).min(limit - filled), 0);
+            }
+
+            let mut buf = ReadBuf::new(&mut output[filled..]);
+            ready!(reader.as_mut().poll_read(cx, &mut buf))?;
+            let read = buf.filled().len();
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+
+        Poll::Ready(Ok::<_, Error>(()))
+    })
+    .await?;
+    output.truncate(filled);
+
+    if output.len() > max_len {
+        return Err(Error::new(
+            ErrorKind::FileTooLarge,
+            format!("decompressed data is longer than the limit of {max_len} bytes"),
+        ));
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,68 @@
+//! Types which operate over seekable [`AsyncRead`] streams, decoding only
+//! the parts of the stream which are needed.
+
+use crate::generic::seek::{impl_xz_decoder, SeekRead};
+use std::{
+    io::{self, SeekFrom},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+struct Reader<'a, R>(Pin<&'a mut R>);
+
+impl<R: AsyncRead + AsyncSeek> SeekRead for Reader<'_, R> {
+    fn poll_seek(
+        &mut self,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+        started: &mut bool,
+    ) -> Poll<io::Result<u64>> {
+        if !*started {
+            self.0.as_mut().start_seek(pos)?;
+            *started = true;
+        }
+        self.0.as_mut().poll_complete(cx)
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut buf = ReadBuf::new(buf);
+        ready!(self.0.as_mut().poll_read(cx, &mut buf))?;
+        Poll::Ready(Ok(buf.filled().len()))
+    }
+}
+
+impl_xz_decoder!();
+
+impl<R: AsyncRead + AsyncSeek> AsyncRead for XzDecoder<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let this = self.project();
+        let mut output = WriteBuffer::new_initialized(buf.initialize_unfilled());
+        let res = this
+            .inner
+            .poll_read(cx, &mut Reader(this.reader), &mut output);
+        let written = output.written_len();
+        buf.advance(written);
+        res
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek> AsyncSeek for XzDecoder<R> {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        self.project().inner.start_seek(position);
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.project();
+        this.inner.poll_complete_seek(cx, &mut Reader(this.reader))
+    }
+}
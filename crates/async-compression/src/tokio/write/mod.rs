@@ -7,10 +7,8 @@ mod generic;
 
 mod buf_writer;
 
-use self::{
-    buf_writer::BufWriter,
-    generic::{Decoder, Encoder},
-};
+pub(crate) use self::buf_writer::BufWriter;
+use self::generic::{Decoder, Encoder};
 use crate::generic::write::AsyncBufWrite;
 
 algos!(tokio::write<W>);
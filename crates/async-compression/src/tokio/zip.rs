@@ -0,0 +1,11 @@
+//! Types which write ZIP archives to [`AsyncWrite`] streams.
+
+use crate::{generic::zip::impl_zip_writer, tokio::write::BufWriter};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::AsyncWrite;
+
+impl_zip_writer!(poll_shutdown);
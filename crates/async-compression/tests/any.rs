@@ -0,0 +1,86 @@
+use async_compression::{
+    tokio::{bufread, write},
+    Algorithm, Level,
+};
+use std::io::ErrorKind;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+async fn write_encode(algorithm: Algorithm, data: &[u8]) -> Vec<u8> {
+    let mut encoder =
+        write::AnyEncoder::with_quality(Vec::new(), algorithm, Level::Fastest).unwrap();
+    assert_eq!(encoder.algorithm(), algorithm);
+    encoder.write_all(data).await.unwrap();
+    encoder.shutdown().await.unwrap();
+    encoder.into_inner()
+}
+
+async fn bufread_encode(algorithm: Algorithm, data: &[u8]) -> Vec<u8> {
+    let mut encoder = bufread::AnyEncoder::new(data, algorithm).unwrap();
+    let mut output = Vec::new();
+    encoder.read_to_end(&mut output).await.unwrap();
+    output
+}
+
+async fn bufread_decode(algorithm: Algorithm, data: &[u8]) -> Vec<u8> {
+    let mut decoder = bufread::AnyDecoder::new(data, algorithm);
+    assert_eq!(decoder.algorithm(), algorithm);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    output
+}
+
+async fn write_decode(algorithm: Algorithm, data: &[u8]) -> Vec<u8> {
+    let mut decoder = write::AnyDecoder::new(Vec::new(), algorithm);
+    decoder.write_all(data).await.unwrap();
+    decoder.shutdown().await.unwrap();
+    decoder.into_inner()
+}
+
+#[tokio::test]
+async fn round_trip() {
+    for &algorithm in Algorithm::ALL {
+        if !algorithm.can_encode() {
+            continue;
+        }
+
+        let compressed = write_encode(algorithm, source()).await;
+        assert_eq!(bufread_decode(algorithm, &compressed).await, source());
+
+        let compressed = bufread_encode(algorithm, source()).await;
+        assert_eq!(write_decode(algorithm, &compressed).await, source());
+    }
+}
+
+#[tokio::test]
+async fn matches_static_types() {
+    let compressed = write_encode(Algorithm::Gzip, source()).await;
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, source());
+}
+
+#[tokio::test]
+async fn decode_only() {
+    let error = write::AnyEncoder::new(Vec::new(), Algorithm::Lzw).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+
+    let compressed = include_bytes!("./artifacts/lib.rs.Z");
+    assert_eq!(bufread_decode(Algorithm::Lzw, compressed).await, source());
+}
+
+#[test]
+fn names() {
+    for &algorithm in Algorithm::ALL {
+        assert_eq!(Algorithm::from_name(algorithm.name()), Some(algorithm));
+    }
+
+    assert_eq!(Algorithm::from_name("ZSTD"), Some(Algorithm::Zstd));
+    assert_eq!(Algorithm::from_name("rar"), None);
+    assert_eq!(Algorithm::Gzip.to_string(), "gzip");
+}
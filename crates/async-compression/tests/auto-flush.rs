@@ -0,0 +1,98 @@
+use async_compression::{tokio::write::DeflateEncoder, AutoFlush};
+use std::{io::Write as _, time::Duration};
+use tokio::io::AsyncWriteExt as _;
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+/// Decodes as much of a possibly unfinished deflate stream as is available.
+fn inflate(compressed: &[u8]) -> Vec<u8> {
+    let mut decoder = flate2::write::DeflateDecoder::new(Vec::new());
+    decoder.write_all(compressed).unwrap();
+    decoder.flush().unwrap();
+    decoder.get_ref().clone()
+}
+
+#[tokio::test]
+async fn after_bytes() {
+    let mut encoder = DeflateEncoder::new(Vec::new());
+    encoder.set_auto_flush(AutoFlush::new().after_bytes(1000));
+
+    encoder.write_all(&source()[..600]).await.unwrap();
+    assert!(inflate(encoder.get_ref()).is_empty());
+
+    encoder.write_all(&source()[600..1200]).await.unwrap();
+    assert_eq!(inflate(encoder.get_ref()), &source()[..1200]);
+    assert_eq!(encoder.stats().flushes(), 1);
+}
+
+#[tokio::test]
+async fn after_writes() {
+    let mut encoder = DeflateEncoder::new(Vec::new());
+    encoder.set_auto_flush(AutoFlush::new().after_writes(2));
+
+    for writes in 1..=10 {
+        encoder
+            .write_all(&source()[(writes - 1) * 100..writes * 100])
+            .await
+            .unwrap();
+
+        let flushed = writes / 2 * 200;
+        assert_eq!(inflate(encoder.get_ref()), &source()[..flushed]);
+    }
+}
+
+#[tokio::test]
+async fn explicit_flush_restarts_count() {
+    let mut encoder = DeflateEncoder::new(Vec::new());
+    encoder.set_auto_flush(AutoFlush::new().after_bytes(1000));
+
+    encoder.write_all(&source()[..600]).await.unwrap();
+    encoder.flush().await.unwrap();
+    encoder.write_all(&source()[600..1200]).await.unwrap();
+
+    assert_eq!(inflate(encoder.get_ref()), &source()[..600]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn after_interval() {
+    let mut encoder = DeflateEncoder::new(Vec::new());
+    encoder.set_auto_flush(AutoFlush::new().after_interval(Duration::from_secs(1)));
+
+    encoder.write_all(&source()[..100]).await.unwrap();
+    tokio::time::advance(Duration::from_millis(500)).await;
+    encoder.write_all(&source()[100..200]).await.unwrap();
+    assert!(inflate(encoder.get_ref()).is_empty());
+
+    tokio::time::advance(Duration::from_millis(500)).await;
+    encoder.write_all(&source()[200..300]).await.unwrap();
+    assert_eq!(inflate(encoder.get_ref()), &source()[..300]);
+}
+
+#[tokio::test]
+async fn round_trip() {
+    let mut encoder = DeflateEncoder::new(Vec::new());
+    encoder.set_auto_flush(AutoFlush::new().after_writes(1));
+
+    for chunk in source().chunks(1000) {
+        encoder.write_all(chunk).await.unwrap();
+    }
+    encoder.shutdown().await.unwrap();
+
+    assert_eq!(inflate(encoder.get_ref()), source());
+}
+
+#[test]
+fn futures_after_bytes() {
+    use futures::io::{AsyncWriteExt as _, Cursor};
+
+    futures::executor::block_on(async {
+        let mut encoder =
+            async_compression::futures::write::DeflateEncoder::new(Cursor::new(Vec::new()));
+        encoder.set_auto_flush(AutoFlush::new().after_bytes(500));
+
+        encoder.write_all(&source()[..600]).await.unwrap();
+        assert_eq!(inflate(encoder.get_ref().get_ref()), &source()[..600]);
+    });
+}
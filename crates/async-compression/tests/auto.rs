@@ -0,0 +1,205 @@
+use async_compression::{auto::Format, tokio::bufread::AutoDecoder};
+use std::{convert::TryFrom, io::Write as _};
+use tokio::io::{AsyncReadExt as _, BufReader};
+
+/// Decodes `input` read a byte at a time, returning the output and the detected format.
+async fn decode(input: &[u8]) -> (Vec<u8>, Format) {
+    let mut decoder = AutoDecoder::new(BufReader::with_capacity(1, input));
+    assert_eq!(decoder.format(), None);
+
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    (output, decoder.format().unwrap())
+}
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+async fn assert_detects(compressed: &[u8], format: Format) {
+    assert_eq!(decode(compressed).await, (source().to_vec(), format));
+}
+
+#[tokio::test]
+async fn gzip() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(source()).unwrap();
+
+    assert_detects(&encoder.finish().unwrap(), Format::Gzip).await;
+}
+
+#[tokio::test]
+async fn zlib() {
+    for level in [0, 1, 6, 9] {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::new(level));
+        encoder.write_all(source()).unwrap();
+
+        assert_detects(&encoder.finish().unwrap(), Format::Zlib).await;
+    }
+}
+
+#[tokio::test]
+async fn zlib_lookalike_text() {
+    // Text starting with any of the printable pairs of characters which make valid zlib headers.
+    for header in ["x^", "HK", "hC", "(S", "8O", "XG"] {
+        let input = [header.as_bytes(), source()].concat();
+        assert_eq!(decode(&input).await, (input.clone(), Format::Uncompressed));
+    }
+
+    let input = b"x^2 + y^2 = z^2 is the equation of a cone rather than a zlib stream.\n";
+    assert_eq!(decode(input).await, (input.to_vec(), Format::Uncompressed));
+}
+
+#[tokio::test]
+async fn zstd() {
+    assert_detects(&libzstd::encode_all(source(), 0).unwrap(), Format::Zstd).await;
+}
+
+fn skippable_frame(content: &[u8]) -> Vec<u8> {
+    let len = u32::try_from(content.len()).unwrap().to_le_bytes();
+    [&[0x5a, 0x2a, 0x4d, 0x18], &len, content].concat()
+}
+
+#[tokio::test]
+async fn zstd_skippable_frame() {
+    let mut compressed = skippable_frame(b"metadata");
+    compressed.extend(libzstd::encode_all(source(), 0).unwrap());
+
+    assert_detects(&compressed, Format::Zstd).await;
+}
+
+#[tokio::test]
+async fn skippable_frames_only() {
+    // Skippable frames not followed by a zstd or LZ4 frame are passed through.
+    let input = [skippable_frame(b"a"), skippable_frame(b"")].concat();
+    assert_eq!(decode(&input).await, (input.clone(), Format::Uncompressed));
+
+    let input = [skippable_frame(b"metadata"), source().to_vec()].concat();
+    assert_eq!(decode(&input).await, (input.clone(), Format::Uncompressed));
+}
+
+#[tokio::test]
+async fn truncated_skippable_frame() {
+    let input = skippable_frame(b"metadata");
+    let input = &input[..input.len() - 1];
+
+    assert_eq!(decode(input).await, (input.to_vec(), Format::Uncompressed));
+}
+
+#[tokio::test]
+async fn xz() {
+    let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(source()).unwrap();
+
+    assert_detects(&encoder.finish().unwrap(), Format::Xz).await;
+}
+
+#[tokio::test]
+async fn lzma() {
+    let options = liblzma::stream::LzmaOptions::new_preset(6).unwrap();
+    let stream = liblzma::stream::Stream::new_lzma_encoder(&options).unwrap();
+    let mut encoder = liblzma::write::XzEncoder::new_stream(Vec::new(), stream);
+    encoder.write_all(source()).unwrap();
+
+    assert_detects(&encoder.finish().unwrap(), Format::Lzma).await;
+}
+
+#[tokio::test]
+async fn bzip2() {
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(source()).unwrap();
+
+    assert_detects(&encoder.finish().unwrap(), Format::Bzip2).await;
+}
+
+#[tokio::test]
+async fn lz4() {
+    let mut encoder = lz4::EncoderBuilder::new().build(Vec::new()).unwrap();
+    encoder.write_all(source()).unwrap();
+    let (compressed, result) = encoder.finish();
+    result.unwrap();
+
+    assert_detects(&compressed, Format::Lz4).await;
+    assert_detects(
+        &[skippable_frame(b"metadata"), compressed].concat(),
+        Format::Lz4,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn lz4_legacy() {
+    let compressed = include_bytes!("./artifacts/lib.rs.lz4-legacy");
+
+    assert_detects(compressed, Format::Lz4Legacy).await;
+
+    // Concatenated legacy streams are decoded as one.
+    let (output, _) = decode(&[&compressed[..], compressed].concat()).await;
+    assert_eq!(output, [source(), source()].concat());
+}
+
+#[tokio::test]
+async fn lz4_legacy_truncated() {
+    let compressed = include_bytes!("./artifacts/lib.rs.lz4-legacy");
+    let input = &compressed[..compressed.len() - 1];
+
+    let mut decoder = AutoDecoder::new(input);
+    let mut output = Vec::new();
+    assert!(decoder.read_to_end(&mut output).await.is_err());
+}
+
+#[tokio::test]
+async fn lz4_legacy_memory_limit() {
+    use async_compression::DecoderLimits;
+    use std::io::ErrorKind;
+
+    let compressed = include_bytes!("./artifacts/lib.rs.lz4-legacy");
+
+    // The limit is checked against the size of each block rather than the largest possible one.
+    let mut decoder = AutoDecoder::new(&compressed[..]);
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(2 * 1024 * 1024))
+        .unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, source());
+
+    let mut decoder = AutoDecoder::new(&compressed[..]);
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(1024))
+        .unwrap();
+    let mut output = Vec::new();
+    let err = decoder.read_to_end(&mut output).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+}
+
+#[tokio::test]
+async fn uncompressed() {
+    assert_detects(source(), Format::Uncompressed).await;
+}
+
+#[tokio::test]
+async fn short_uncompressed() {
+    for input in [&b""[..], b"x", b"BZh", b"]\0\0\x80\0"] {
+        assert_eq!(decode(input).await, (input.to_vec(), Format::Uncompressed));
+    }
+}
+
+#[tokio::test]
+async fn multiple_members() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"hello ").unwrap();
+    let mut compressed = encoder.finish().unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"world").unwrap();
+    compressed.extend(encoder.finish().unwrap());
+
+    let mut decoder = AutoDecoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+
+    assert_eq!(output, b"hello world");
+    assert_eq!(decoder.format(), Some(Format::Gzip));
+}
@@ -0,0 +1,122 @@
+use async_compression::{tokio::write, FlushMode};
+use std::io::{ErrorKind, Read as _};
+use tokio::io::{AsyncWrite, AsyncWriteExt as _};
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+/// Writes both halves of the source with a flush in between, returning the `output` length at the
+/// flush.
+async fn write_flushed<W: AsyncWrite + Unpin>(
+    encoder: &mut W,
+    output: impl Fn(&W) -> usize,
+) -> usize {
+    let (first, second) = source().split_at(source().len() / 2);
+    encoder.write_all(first).await.unwrap();
+    encoder.flush().await.unwrap();
+    let flushed = output(encoder);
+    encoder.write_all(second).await.unwrap();
+    encoder.shutdown().await.unwrap();
+    flushed
+}
+
+fn second_half() -> &'static [u8] {
+    &source()[source().len() / 2..]
+}
+
+#[tokio::test]
+async fn deflate_full_flush_restarts() {
+    let mut encoder = write::DeflateEncoder::new(Vec::new());
+    encoder.set_flush_mode(FlushMode::Full).unwrap();
+    let flushed = write_flushed(&mut encoder, |encoder| encoder.get_ref().len()).await;
+    let compressed = encoder.into_inner();
+
+    let mut output = Vec::new();
+    flate2::read::DeflateDecoder::new(&compressed[flushed..])
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, second_half());
+
+    let mut output = Vec::new();
+    flate2::read::DeflateDecoder::new(&compressed[..])
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, source());
+}
+
+#[tokio::test]
+async fn gzip_full_flush_round_trip() {
+    let mut encoder = write::GzipEncoder::new(Vec::new());
+    encoder.set_flush_mode(FlushMode::Full).unwrap();
+    write_flushed(&mut encoder, |encoder| encoder.get_ref().len()).await;
+
+    let mut output = Vec::new();
+    flate2::read::GzDecoder::new(&encoder.get_ref()[..])
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, source());
+}
+
+#[tokio::test]
+async fn deflate_block_end_unsupported() {
+    let mut encoder = write::DeflateEncoder::new(Vec::new());
+    let err = encoder.set_flush_mode(FlushMode::BlockEnd).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+#[tokio::test]
+async fn zstd_block_end_ends_frame() {
+    let mut encoder = write::ZstdEncoder::new(Vec::new());
+    encoder.set_flush_mode(FlushMode::BlockEnd).unwrap();
+    let flushed = write_flushed(&mut encoder, |encoder| encoder.get_ref().len()).await;
+    let compressed = encoder.into_inner();
+
+    assert_eq!(
+        libzstd::decode_all(&compressed[flushed..]).unwrap(),
+        second_half()
+    );
+    assert_eq!(libzstd::decode_all(&compressed[..]).unwrap(), source());
+}
+
+#[tokio::test]
+async fn zstd_repeated_flushes_add_no_frames() {
+    let mut encoder = write::ZstdEncoder::new(Vec::new());
+    encoder.set_flush_mode(FlushMode::Full).unwrap();
+    encoder.write_all(source()).await.unwrap();
+    encoder.flush().await.unwrap();
+    let len = encoder.get_ref().len();
+
+    encoder.flush().await.unwrap();
+    encoder.shutdown().await.unwrap();
+
+    assert_eq!(encoder.get_ref().len(), len);
+    assert_eq!(
+        libzstd::decode_all(&encoder.get_ref()[..]).unwrap(),
+        source()
+    );
+}
+
+#[tokio::test]
+async fn xz_modes_round_trip() {
+    for mode in [FlushMode::Sync, FlushMode::Full, FlushMode::BlockEnd] {
+        let mut encoder = write::XzEncoder::new(Vec::new());
+        encoder.set_flush_mode(mode).unwrap();
+        write_flushed(&mut encoder, |encoder| encoder.get_ref().len()).await;
+
+        let mut output = Vec::new();
+        liblzma::read::XzDecoder::new(&encoder.get_ref()[..])
+            .read_to_end(&mut output)
+            .unwrap();
+        assert_eq!(output, source(), "{mode:?}");
+    }
+}
+
+#[tokio::test]
+async fn brotli_only_syncs() {
+    let mut encoder = write::BrotliEncoder::new(Vec::new());
+    encoder.set_flush_mode(FlushMode::Sync).unwrap();
+
+    let err = encoder.set_flush_mode(FlushMode::Full).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
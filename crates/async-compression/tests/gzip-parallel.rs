@@ -0,0 +1,142 @@
+use async_compression::Level;
+use std::{io::Read, num::NonZeroU32};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+const THREADS: NonZeroU32 = NonZeroU32::new(4).unwrap();
+
+fn input() -> Vec<u8> {
+    // Large enough to be split over multiple chunks, with plenty of matches across chunk
+    // boundaries.
+    include_bytes!("./artifacts/lib.rs").repeat(64)
+}
+
+fn decompress(compressed: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    flate2::read::GzDecoder::new(compressed)
+        .read_to_end(&mut output)
+        .unwrap();
+    output
+}
+
+#[tokio::test]
+async fn gzip_parallel_write_roundtrip() {
+    let input = input();
+
+    let mut encoder =
+        async_compression::tokio::write::GzipEncoder::parallel(Vec::new(), Level::Default, THREADS)
+            .unwrap();
+    for chunk in input.chunks(10_000) {
+        encoder.write_all(chunk).await.unwrap();
+    }
+    encoder.shutdown().await.unwrap();
+    let compressed = encoder.into_inner();
+
+    assert!(compressed.len() < input.len() / 10);
+    assert_eq!(decompress(&compressed), input);
+}
+
+#[tokio::test]
+async fn gzip_parallel_write_flush() {
+    let input = input();
+    let (first, second) = input.split_at(200_000);
+
+    let mut encoder =
+        async_compression::tokio::write::GzipEncoder::parallel(Vec::new(), Level::Fastest, THREADS)
+            .unwrap();
+    encoder.write_all(first).await.unwrap();
+    encoder.flush().await.unwrap();
+
+    // Everything written before the flush must be decodable without the rest of the stream.
+    let mut partial = Vec::new();
+    let mut decoder = flate2::read::GzDecoder::new(&encoder.get_ref()[..]);
+    let mut buf = [0; 4096];
+    while partial.len() < first.len() {
+        let len = decoder.read(&mut buf).unwrap();
+        assert_ne!(len, 0);
+        partial.extend_from_slice(&buf[..len]);
+    }
+    assert_eq!(partial, first);
+
+    encoder.flush().await.unwrap();
+    encoder.write_all(second).await.unwrap();
+    encoder.shutdown().await.unwrap();
+
+    assert_eq!(decompress(encoder.get_ref()), input);
+}
+
+#[tokio::test]
+async fn gzip_parallel_write_empty() {
+    let mut encoder =
+        async_compression::tokio::write::GzipEncoder::parallel(Vec::new(), Level::Default, THREADS)
+            .unwrap();
+    encoder.shutdown().await.unwrap();
+
+    assert_eq!(decompress(encoder.get_ref()), b"");
+}
+
+#[tokio::test]
+async fn gzip_parallel_bufread_roundtrip() {
+    let input = input();
+
+    let mut encoder =
+        async_compression::tokio::bufread::GzipEncoder::parallel(&input[..], Level::Best, THREADS)
+            .unwrap();
+    let mut compressed = Vec::new();
+    encoder.read_to_end(&mut compressed).await.unwrap();
+
+    let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+
+    assert_eq!(output, input);
+}
+
+#[test]
+fn gzip_parallel_wakes_task() {
+    // `block_on` only polls again once woken, so this hangs if the encoder returns
+    // `Poll::Pending` without its workers waking the task.
+    let input = input();
+
+    let compressed = futures::executor::block_on(async {
+        let mut encoder = async_compression::tokio::write::GzipEncoder::parallel(
+            Vec::new(),
+            Level::Best,
+            NonZeroU32::MIN,
+        )
+        .unwrap();
+        for chunk in input.chunks(100_000) {
+            encoder.write_all(chunk).await.unwrap();
+            encoder.flush().await.unwrap();
+        }
+        encoder.shutdown().await.unwrap();
+        encoder.into_inner()
+    });
+
+    assert_eq!(decompress(&compressed), input);
+}
+
+#[test]
+fn gzip_parallel_sync_write() {
+    use std::io::Write as _;
+
+    let input = input();
+
+    let mut encoder =
+        async_compression::sync::write::GzipEncoder::parallel(Vec::new(), Level::Best, THREADS)
+            .unwrap();
+    encoder.write_all(&input).unwrap();
+    encoder.try_finish().unwrap();
+
+    assert_eq!(decompress(encoder.get_ref()), input);
+}
+
+#[test]
+fn gzip_parallel_oneshot() {
+    let input = input();
+
+    let mut encoder =
+        async_compression::codecs::GzipEncoder::parallel(Level::Default.into(), THREADS).unwrap();
+    let compressed = async_compression::oneshot::encode_all(&mut encoder, &input).unwrap();
+
+    assert_eq!(decompress(&compressed), input);
+}
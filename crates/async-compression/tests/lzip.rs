@@ -0,0 +1,43 @@
+#[allow(unused)]
+use futures::{executor::block_on, io::AsyncReadExt};
+
+#[macro_use]
+mod utils;
+
+test_cases!(lzip);
+
+#[allow(unused)]
+use utils::{algos::lzip::sync, InputStream};
+
+#[cfg(feature = "futures-io")]
+use utils::algos::lzip::futures::bufread;
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_invalid_crc() {
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let crc = compressed.len() - 20;
+    compressed[crc] ^= 0xff;
+
+    let input = InputStream::from(vec![compressed]);
+
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = Vec::new();
+    assert!(block_on(decoder.read_to_end(&mut output)).is_err());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_invalid_member_size() {
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let member_size = compressed.len() - 8;
+    compressed[member_size] ^= 0xff;
+
+    let input = InputStream::from(vec![compressed]);
+
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = Vec::new();
+    assert!(block_on(decoder.read_to_end(&mut output)).is_err());
+}
@@ -2,3 +2,72 @@
 mod utils;
 
 test_cases!(lzma);
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn write_with_params_raw() {
+    use async_compression::lzma::{LzmaEncoderParams, LzmaFilter, LzmaFilters, LzmaOptions};
+    use futures::{executor::block_on, io::AsyncWriteExt};
+    use liblzma::stream::{Filters, Stream};
+    use std::io::Read;
+
+    let filters = LzmaFilters::default()
+        .add_filter(LzmaFilter::X86(None))
+        .add_filter(LzmaFilter::Lzma2(LzmaOptions::default().preset(1)));
+    let mut encoder = async_compression::futures::write::LzmaEncoder::with_params(
+        Vec::new(),
+        LzmaEncoderParams::Raw { filters },
+    )
+    .unwrap();
+    block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    block_on(encoder.close()).unwrap();
+    let compressed = encoder.into_inner();
+
+    let mut filters = Filters::new();
+    filters
+        .x86()
+        .lzma2(&liblzma::stream::LzmaOptions::new_preset(1).unwrap());
+    let stream = Stream::new_raw_decoder(&filters).unwrap();
+    let mut output = Vec::new();
+    liblzma::read::XzDecoder::new_stream(&compressed[..], stream)
+        .read_to_end(&mut output)
+        .unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn raw_lzma1_zip_entry() {
+    use async_compression::lzma::Lzma1Props;
+    use futures::{
+        executor::block_on,
+        io::{AsyncReadExt, AsyncWriteExt},
+    };
+
+    let props = Lzma1Props {
+        literal_context_bits: 3,
+        literal_position_bits: 0,
+        position_bits: 2,
+        dict_size: 1 << 16,
+    };
+    let mut encoder =
+        async_compression::futures::write::LzmaEncoder::with_filters(Vec::new(), props.filters())
+            .unwrap();
+    block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    block_on(encoder.close()).unwrap();
+    let entry = [&props.to_zip_header()[..], &encoder.into_inner()].concat();
+
+    let props = Lzma1Props::parse_zip_header(&entry).unwrap();
+    let mut decoder = async_compression::futures::bufread::LzmaDecoder::with_filters(
+        &entry[Lzma1Props::ZIP_HEADER_LEN..],
+        props.filters(),
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+}
@@ -0,0 +1,105 @@
+//! The `.Z` artifacts are meant to be written by ncompress, with:
+//!
+//! ```text
+//! compress -c lib.rs > lib.rs.Z
+//! compress -b12 -c lib.rs-random > lib.rs-random-12bit.Z
+//! compress -C -b12 -c lib.rs > lib.rs-noblock.Z
+//! ```
+//!
+//! - `lib.rs.Z`: default 16 bit block mode.
+//! - `lib.rs-random-12bit.Z`: 12 bit block mode, where `lib.rs-random` is `lib.rs`, 4096 bytes
+//!   from [`lcg`] and `lib.rs` again, and the random data makes the encoder emit a `CLEAR` code.
+//! - `lib.rs-noblock.Z`: 12 bit codes without block mode.
+//!
+//! The checked-in files have not been regenerated that way yet: they were written by a port of
+//! the ncompress 4.2 `compress()` algorithm, and have only been checked to decode with
+//! `gzip -dc`, whose LZW decoder is independent of both.
+
+use async_compression::tokio::{
+    bufread::CompressZDecoder, write::CompressZDecoder as WriteDecoder,
+};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+fn lcg(len: usize) -> Vec<u8> {
+    let mut state = 1u32;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345) & 0x7fff_ffff;
+            (state >> 16) as u8
+        })
+        .collect()
+}
+
+async fn decode(compressed: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoder = CompressZDecoder::new(compressed);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await?;
+    Ok(output)
+}
+
+#[tokio::test]
+async fn bufread_16_bit() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let compressed = include_bytes!("./artifacts/lib.rs.Z");
+
+    assert_eq!(decode(compressed).await.unwrap(), source);
+}
+
+#[tokio::test]
+async fn bufread_clear() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let compressed = include_bytes!("./artifacts/lib.rs-random-12bit.Z");
+
+    let expected = [&source[..], &lcg(4096), source].concat();
+    assert_eq!(decode(compressed).await.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn bufread_no_block_mode() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let compressed = include_bytes!("./artifacts/lib.rs-noblock.Z");
+
+    assert_eq!(decode(compressed).await.unwrap(), source);
+}
+
+#[tokio::test]
+async fn write_chunked() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let compressed = include_bytes!("./artifacts/lib.rs-random-12bit.Z");
+
+    let mut decoder = WriteDecoder::new(Vec::new());
+    for chunk in compressed.chunks(7) {
+        decoder.write_all(chunk).await.unwrap();
+    }
+    decoder.shutdown().await.unwrap();
+
+    let expected = [&source[..], &lcg(4096), source].concat();
+    assert_eq!(decoder.into_inner(), expected);
+}
+
+#[tokio::test]
+async fn empty() {
+    assert!(decode(&[0x1f, 0x9d, 0x90]).await.unwrap().is_empty());
+    assert!(decode(&[]).await.is_err());
+}
+
+#[tokio::test]
+async fn invalid_header() {
+    assert!(decode(&[0x1f, 0x8b, 0x90, 0x41, 0x00]).await.is_err());
+    // 17 bit codes are not supported.
+    assert!(decode(&[0x1f, 0x9d, 0x91, 0x41, 0x00]).await.is_err());
+}
+
+#[tokio::test]
+async fn truncated() {
+    let compressed = include_bytes!("./artifacts/lib.rs.Z");
+
+    // Only the first byte of the first 9 bit code.
+    assert!(decode(&compressed[..4]).await.is_err());
+}
+
+#[tokio::test]
+async fn invalid_code() {
+    // The first code must be a literal.
+    assert!(decode(&[0x1f, 0x9d, 0x90, 0x01, 0x03]).await.is_err());
+}
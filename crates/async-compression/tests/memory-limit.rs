@@ -0,0 +1,162 @@
+use async_compression::{
+    tokio::{bufread, write},
+    DecoderLimits,
+};
+use std::io::{self, ErrorKind, Write as _};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+const MIB: u64 = 1024 * 1024;
+
+fn data() -> Vec<u8> {
+    (0..100_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+fn zstd(data: &[u8], window_log: u32) -> Vec<u8> {
+    let mut encoder = libzstd::stream::Encoder::new(Vec::new(), 3).unwrap();
+    encoder.window_log(window_log).unwrap();
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+async fn read_to_end(mut reader: impl tokio::io::AsyncRead + Unpin) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    reader.read_to_end(&mut output).await?;
+    Ok(output)
+}
+
+#[tokio::test]
+async fn zstd_window_over_limit() {
+    let compressed = zstd(&data(), 22);
+
+    let mut decoder = bufread::ZstdDecoder::new(&compressed[..]);
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(MIB))
+        .unwrap();
+    let err = read_to_end(decoder).await.unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+}
+
+#[tokio::test]
+async fn zstd_window_within_limit() {
+    let data = data();
+    let compressed = zstd(&data, 22);
+
+    let mut decoder = bufread::ZstdDecoder::new(&compressed[..]);
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(4 * MIB))
+        .unwrap();
+
+    assert_eq!(read_to_end(decoder).await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn zstd_limit_below_window() {
+    let mut decoder = bufread::ZstdDecoder::new(&[][..]);
+    let err = decoder
+        .set_limits(DecoderLimits::new().max_memory(512))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(1024))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn gzip_limit_below_window() {
+    let mut decoder = bufread::GzipDecoder::new(&[][..]);
+    let err = decoder
+        .set_limits(DecoderLimits::new().max_memory(16 * 1024))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(32 * 1024))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn bzip2_block_size() {
+    let data = data();
+    let compress = |level| {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::new(level));
+        encoder.write_all(&data).unwrap();
+        encoder.finish().unwrap()
+    };
+    let limits = DecoderLimits::new().max_memory(MIB);
+
+    let compressed = compress(9);
+    let mut decoder = bufread::BzDecoder::new(&compressed[..]);
+    decoder.set_limits(limits).unwrap();
+    let err = read_to_end(decoder).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+
+    let compressed = compress(1);
+    let mut decoder = bufread::BzDecoder::new(&compressed[..]);
+    decoder.set_limits(limits).unwrap();
+    assert_eq!(read_to_end(decoder).await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn brotli_window_size() {
+    let data = data();
+    let compress = |lgwin| {
+        let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, lgwin);
+        encoder.write_all(&data).unwrap();
+        encoder.into_inner()
+    };
+    let limits = DecoderLimits::new().max_memory(MIB);
+
+    let compressed = compress(22);
+    let mut decoder = bufread::BrotliDecoder::new(&compressed[..]);
+    decoder.set_limits(limits).unwrap();
+    let err = read_to_end(decoder).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+
+    let compressed = compress(16);
+    let mut decoder = bufread::BrotliDecoder::new(&compressed[..]);
+    decoder.set_limits(limits).unwrap();
+    assert_eq!(read_to_end(decoder).await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn xz_dictionary_over_limit() {
+    let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), 9);
+    encoder.write_all(&data()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut decoder = bufread::XzDecoder::new(&compressed[..]);
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(16 * MIB))
+        .unwrap();
+    let err = read_to_end(decoder).await.unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+}
+
+#[tokio::test]
+async fn write_decoder_over_limit() {
+    let compressed = zstd(&data(), 22);
+
+    let mut decoder = write::ZstdDecoder::new(Vec::new());
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(MIB))
+        .unwrap();
+    let err = decoder.write_all(&compressed).await.unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+}
+
+#[tokio::test]
+async fn auto_decoder_applies_limits() {
+    let compressed = zstd(&data(), 22);
+
+    let mut decoder = bufread::AutoDecoder::new(&compressed[..]);
+    decoder
+        .set_limits(DecoderLimits::new().max_memory(MIB))
+        .unwrap();
+    let err = read_to_end(decoder).await.unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+}
@@ -0,0 +1,206 @@
+use async_compression::{
+    negotiate::{negotiate, negotiate_default, parse_content_encoding, ContentEncoding},
+    tokio::negotiate::{content_decoder, negotiate_bufread, negotiate_write},
+    Level,
+};
+use std::io::{ErrorKind, Read as _};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+use ContentEncoding::{Brotli, Deflate, Gzip, Identity, Zstd};
+
+#[test]
+fn tokens() {
+    for &coding in ContentEncoding::ALL {
+        assert_eq!(ContentEncoding::from_token(coding.as_str()), Some(coding));
+    }
+
+    assert_eq!(ContentEncoding::from_token("GZip"), Some(Gzip));
+    assert_eq!(ContentEncoding::from_token("x-gzip"), Some(Gzip));
+    assert_eq!(ContentEncoding::from_token("compress"), None);
+    assert_eq!(ContentEncoding::from_token("*"), None);
+}
+
+#[test]
+fn server_preference_breaks_ties() {
+    assert_eq!(
+        negotiate("gzip, br, zstd", &[Zstd, Brotli, Gzip]),
+        Some(Zstd)
+    );
+    assert_eq!(negotiate("gzip, br, zstd", &[Gzip, Zstd]), Some(Gzip));
+    assert_eq!(negotiate_default("gzip, deflate, br"), Some(Brotli));
+}
+
+#[test]
+fn weights() {
+    assert_eq!(
+        negotiate("gzip;q=0.5, br;q=0.8, zstd;q=0.1", &[Zstd, Brotli, Gzip]),
+        Some(Brotli)
+    );
+    assert_eq!(negotiate("br;q=0, gzip", &[Brotli, Gzip]), Some(Gzip));
+    assert_eq!(negotiate("GZIP ; Q=1.000", &[Brotli, Gzip]), Some(Gzip));
+    assert_eq!(negotiate("x-gzip;q=0.3", &[Gzip]), Some(Gzip));
+}
+
+#[test]
+fn invalid_elements_are_ignored() {
+    assert_eq!(negotiate("br;q=2, gzip", &[Brotli, Gzip]), Some(Gzip));
+    assert_eq!(negotiate("br;q=0.5000, gzip", &[Brotli, Gzip]), Some(Gzip));
+    assert_eq!(negotiate("br;q, , gzip;q=0.1", &[Brotli, Gzip]), Some(Gzip));
+}
+
+#[test]
+fn wildcard() {
+    assert_eq!(negotiate("*", &[Zstd, Gzip]), Some(Zstd));
+    assert_eq!(negotiate("zstd;q=0, *;q=0.5", &[Zstd, Gzip]), Some(Gzip));
+    assert_eq!(
+        negotiate("gzip;q=0.2, *;q=0.5", &[Gzip, Deflate]),
+        Some(Deflate)
+    );
+}
+
+#[test]
+fn identity() {
+    // Nothing acceptable is offered, identity is acceptable by default.
+    assert_eq!(negotiate("compress", &[Gzip]), Some(Identity));
+    assert_eq!(negotiate("", &[Gzip]), Some(Identity));
+
+    // Identity is only a last resort unless the server prefers it.
+    assert_eq!(negotiate("identity, gzip;q=0.5", &[Gzip]), Some(Gzip));
+    assert_eq!(
+        negotiate("identity, gzip;q=0.5", &[Identity, Gzip]),
+        Some(Identity)
+    );
+
+    // Excluded explicitly, or by the wildcard without a more specific entry.
+    assert_eq!(negotiate("identity;q=0", &[Gzip]), None);
+    assert_eq!(negotiate("*;q=0", &[Gzip]), None);
+    assert_eq!(negotiate("*;q=0, identity", &[Gzip]), Some(Identity));
+    assert_eq!(negotiate("gzip, *;q=0", &[Gzip]), Some(Gzip));
+}
+
+#[tokio::test]
+async fn write() {
+    let mut compressed = Vec::new();
+    let (encoding, mut encoder) = negotiate_write(
+        "gzip;q=0.9, deflate",
+        &[Gzip, Deflate],
+        &mut compressed,
+        Level::Default,
+    )
+    .unwrap();
+    assert_eq!(encoding.as_str(), "deflate");
+
+    encoder.write_all(b"hello world").await.unwrap();
+    encoder.shutdown().await.unwrap();
+    drop(encoder);
+
+    let mut output = String::new();
+    flate2::read::ZlibDecoder::new(&compressed[..])
+        .read_to_string(&mut output)
+        .unwrap();
+    assert_eq!(output, "hello world");
+}
+
+#[tokio::test]
+async fn bufread() {
+    let (encoding, mut encoder) =
+        negotiate_bufread("gzip", &[Zstd, Gzip], &b"hello world"[..], Level::Default).unwrap();
+    assert_eq!(encoding, Gzip);
+
+    let mut compressed = Vec::new();
+    encoder.read_to_end(&mut compressed).await.unwrap();
+
+    let mut output = String::new();
+    flate2::read::GzDecoder::new(&compressed[..])
+        .read_to_string(&mut output)
+        .unwrap();
+    assert_eq!(output, "hello world");
+}
+
+#[tokio::test]
+async fn bufread_identity() {
+    let (encoding, mut encoder) =
+        negotiate_bufread("compress", &[Gzip], &b"hello world"[..], Level::Default).unwrap();
+    assert_eq!(encoding, Identity);
+
+    let mut output = Vec::new();
+    encoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, b"hello world");
+}
+
+#[test]
+fn not_acceptable() {
+    assert!(negotiate_bufread("*;q=0", &[Gzip], &b""[..], Level::Default).is_none());
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    brotli::BrotliCompress(&mut &data[..], &mut output, &Default::default()).unwrap();
+    output
+}
+
+async fn decode(content_encoding: &str, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoder = content_decoder(content_encoding, data)?;
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await?;
+    Ok(output)
+}
+
+#[test]
+fn parse_content_encodings() {
+    assert_eq!(
+        parse_content_encoding(" deflate ,GZIP,, identity").unwrap(),
+        [Deflate, Gzip]
+    );
+    assert!(parse_content_encoding("").unwrap().is_empty());
+
+    let error = parse_content_encoding("gzip, compress").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+    assert!(error.to_string().contains("`compress`"));
+}
+
+#[tokio::test]
+async fn content_decoder_stacked() {
+    let source = include_bytes!("./artifacts/lib.rs");
+
+    let compressed = gzip(&zlib(source));
+    assert_eq!(decode("deflate, gzip", &compressed).await.unwrap(), source);
+
+    let compressed = brotli(&gzip(&brotli(source)));
+    assert_eq!(decode("br, gzip, br", &compressed).await.unwrap(), source);
+}
+
+#[tokio::test]
+async fn content_decoder_single_and_identity() {
+    let source = include_bytes!("./artifacts/lib.rs");
+
+    assert_eq!(decode("gzip", &gzip(source)).await.unwrap(), source);
+    assert_eq!(decode("identity", source).await.unwrap(), source);
+    assert_eq!(decode("", source).await.unwrap(), source);
+}
+
+#[tokio::test]
+async fn content_decoder_unsupported() {
+    let error = decode("gzip, compress", b"").await.unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+}
+
+#[tokio::test]
+async fn content_decoder_wrong_order() {
+    let compressed = gzip(&zlib(b"hello world"));
+
+    assert!(decode("gzip, deflate", &compressed).await.is_err());
+}
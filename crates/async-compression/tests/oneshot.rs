@@ -0,0 +1,49 @@
+use async_compression::{
+    oneshot::{compress, decompress},
+    tokio::{bufread, decompress_to_vec},
+    Algorithm, Level,
+};
+use std::io::ErrorKind;
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+#[test]
+fn gzip_round_trip() {
+    let compressed = compress(Algorithm::Gzip, Level::Default, source()).unwrap();
+    let decompressed = decompress(Algorithm::Gzip, &compressed, source().len()).unwrap();
+    assert_eq!(decompressed, source());
+}
+
+#[test]
+fn zstd_matches_libzstd() {
+    let compressed = compress(Algorithm::Zstd, Level::Precise(3), source()).unwrap();
+    assert_eq!(compressed, libzstd::bulk::compress(source(), 3).unwrap());
+
+    let decompressed = decompress(Algorithm::Zstd, &compressed, source().len()).unwrap();
+    assert_eq!(decompressed, source());
+}
+
+#[test]
+fn decompress_over_limit() {
+    let compressed = compress(Algorithm::Zstd, Level::Default, source()).unwrap();
+    let err = decompress(Algorithm::Zstd, &compressed, source().len() - 1).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+}
+
+#[tokio::test]
+async fn decompress_to_vec_within_limit() {
+    let compressed = compress(Algorithm::Gzip, Level::Default, source()).unwrap();
+    let decoder = bufread::GzipDecoder::new(&compressed[..]);
+    let decompressed = decompress_to_vec(decoder, source().len()).await.unwrap();
+    assert_eq!(decompressed, source());
+}
+
+#[tokio::test]
+async fn decompress_to_vec_over_limit() {
+    let compressed = compress(Algorithm::Gzip, Level::Default, source()).unwrap();
+    let decoder = bufread::GzipDecoder::new(&compressed[..]);
+    let err = decompress_to_vec(decoder, 100).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+}
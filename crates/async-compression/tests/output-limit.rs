@@ -0,0 +1,102 @@
+use async_compression::tokio::{bufread, write};
+use std::io::{self, ErrorKind, Write as _};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+fn flate2_gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Reads `reader` to its end, returning what was read before any error too.
+async fn read_all(mut reader: impl tokio::io::AsyncRead + Unpin) -> (Vec<u8>, io::Result<()>) {
+    let mut output = Vec::new();
+    loop {
+        let mut buf = [0; 1000];
+        match reader.read(&mut buf).await {
+            Ok(0) => return (output, Ok(())),
+            Ok(len) => output.extend_from_slice(&buf[..len]),
+            Err(err) => return (output, Err(err)),
+        }
+    }
+}
+
+#[tokio::test]
+async fn bufread_within_size_limit() {
+    let data = vec![7; 100_000];
+    let compressed = flate2_gzip(&data);
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.max_output_size(Some(100_000));
+    let (output, res) = read_all(decoder).await;
+
+    res.unwrap();
+    assert_eq!(output, data);
+}
+
+#[tokio::test]
+async fn bufread_over_size_limit() {
+    let compressed = flate2_gzip(&vec![7; 100_000]);
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.max_output_size(Some(12_345));
+    let (output, res) = read_all(decoder).await;
+
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::FileTooLarge);
+    assert_eq!(output.len(), 12_345);
+}
+
+#[tokio::test]
+async fn bufread_over_ratio_limit() {
+    let compressed = flate2_gzip(&vec![7; 1_000_000]);
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.max_expansion_ratio(Some(100));
+    let (output, res) = read_all(decoder).await;
+
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::FileTooLarge);
+    assert!(output.len() <= compressed.len() * 100);
+}
+
+#[tokio::test]
+async fn bufread_within_ratio_limit() {
+    let data = b"hello world, hello world, hello world".repeat(10);
+    let compressed = flate2_gzip(&data);
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.max_expansion_ratio(Some(100));
+    let (output, res) = read_all(decoder).await;
+
+    res.unwrap();
+    assert_eq!(output, data);
+}
+
+#[tokio::test]
+async fn write_over_size_limit() {
+    let compressed = flate2_gzip(&vec![7; 100_000]);
+
+    let mut decoder = write::GzipDecoder::new(Vec::new());
+    decoder.max_output_size(Some(12_345));
+    let err = async {
+        decoder.write_all(&compressed).await?;
+        decoder.shutdown().await
+    }
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+    assert!(decoder.get_ref().len() <= 12_345);
+}
+
+#[tokio::test]
+async fn write_within_size_limit() {
+    let data = vec![7; 100_000];
+    let compressed = flate2_gzip(&data);
+
+    let mut decoder = write::GzipDecoder::new(Vec::new());
+    decoder.max_output_size(Some(100_000));
+    decoder.write_all(&compressed).await.unwrap();
+    decoder.shutdown().await.unwrap();
+
+    assert_eq!(decoder.into_inner(), data);
+}
@@ -0,0 +1,112 @@
+use async_compression::{futures, tokio::read};
+use std::{
+    io::{self, Write},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncReadExt as _, ReadBuf};
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+fn flate2_gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// A reader which only implements `AsyncRead`, and returns at most a few bytes from each read.
+struct Trickle<'a>(&'a [u8]);
+
+impl AsyncRead for Trickle<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let len = self.0.len().min(buf.remaining()).min(3);
+        buf.put_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Poll::Ready(Ok(()))
+    }
+}
+
+async fn read_to_end(mut reader: impl AsyncRead + Unpin) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    reader.read_to_end(&mut output).await?;
+    Ok(output)
+}
+
+#[tokio::test]
+async fn gzip_matches_flate2() {
+    let compressed = read_to_end(read::GzipEncoder::new(Trickle(source())))
+        .await
+        .unwrap();
+    let mut decompressed = Vec::new();
+    io::Read::read_to_end(
+        &mut flate2::read::GzDecoder::new(&compressed[..]),
+        &mut decompressed,
+    )
+    .unwrap();
+    assert_eq!(decompressed, source());
+
+    let compressed = flate2_gzip(source());
+    let decompressed = read_to_end(read::GzipDecoder::new(Trickle(&compressed)))
+        .await
+        .unwrap();
+    assert_eq!(decompressed, source());
+}
+
+#[tokio::test]
+async fn zstd_round_trip() {
+    let compressed = read_to_end(read::ZstdEncoder::new(Trickle(source())))
+        .await
+        .unwrap();
+    assert_eq!(libzstd::decode_all(&compressed[..]).unwrap(), source());
+
+    let decompressed = read_to_end(read::ZstdDecoder::new(Trickle(&compressed)))
+        .await
+        .unwrap();
+    assert_eq!(decompressed, source());
+}
+
+#[tokio::test]
+async fn multiple_members() {
+    let mut compressed = flate2_gzip(b"hello ");
+    compressed.extend(flate2_gzip(b"world"));
+
+    let decompressed = read_to_end(read::GzipDecoder::new(Trickle(&compressed)))
+        .await
+        .unwrap();
+    assert_eq!(decompressed, b"hello ");
+
+    let mut decoder = read::GzipDecoder::new(Trickle(&compressed));
+    decoder.multiple_members(true);
+    assert_eq!(read_to_end(decoder).await.unwrap(), b"hello world");
+}
+
+#[tokio::test]
+async fn truncated() {
+    let compressed = flate2_gzip(source());
+    let truncated = &compressed[..compressed.len() / 2];
+
+    let err = read_to_end(read::GzipDecoder::new(Trickle(truncated)))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn futures_round_trip() {
+    use ::futures::{executor::block_on, io::AsyncReadExt as _};
+
+    let mut compressed = Vec::new();
+    block_on(futures::read::ZstdEncoder::new(source()).read_to_end(&mut compressed)).unwrap();
+
+    let mut decoder = futures::read::ZstdDecoder::new(&compressed[..]);
+    let mut decompressed = Vec::new();
+    block_on(decoder.read_to_end(&mut decompressed)).unwrap();
+    assert_eq!(decompressed, source());
+    assert!(decoder.into_inner().is_empty());
+}
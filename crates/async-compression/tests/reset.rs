@@ -0,0 +1,147 @@
+use async_compression::{
+    tokio::{bufread, write},
+    Algorithm, Level,
+};
+use futures::StreamExt as _;
+use std::io::{Read as _, Write as _};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+fn flate2_gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[tokio::test]
+async fn every_algorithm_after_unfinished_stream() {
+    for &algorithm in Algorithm::ALL.iter().filter(|a| a.can_encode()) {
+        let mut encoder =
+            write::AnyEncoder::with_quality(Vec::new(), algorithm, Level::Fastest).unwrap();
+        encoder.write_all(&source()[..1000]).await.unwrap();
+        encoder.flush().await.unwrap();
+
+        let mut decoder = write::AnyDecoder::new(Vec::new(), algorithm);
+        for data in [source(), &source()[..500]] {
+            encoder.reset(Vec::new()).unwrap();
+            encoder.write_all(data).await.unwrap();
+            encoder.shutdown().await.unwrap();
+
+            decoder.reset(Vec::new()).unwrap();
+            decoder.write_all(encoder.get_ref()).await.unwrap();
+            decoder.shutdown().await.unwrap();
+            assert_eq!(decoder.get_ref(), data, "{algorithm:?}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn write_encoder_returns_previous_writer() {
+    let mut encoder = write::GzipEncoder::new(Vec::new());
+    encoder.write_all(&source()[..1000]).await.unwrap();
+    encoder.shutdown().await.unwrap();
+    assert_eq!(encoder.stats().members(), 1);
+
+    let first = encoder.reset(Vec::new()).unwrap();
+    assert_eq!(encoder.stats(), Default::default());
+    encoder.write_all(&source()[1000..]).await.unwrap();
+    encoder.shutdown().await.unwrap();
+
+    let mut output = Vec::new();
+    flate2::read::GzDecoder::new(&first[..])
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, &source()[..1000]);
+
+    let mut output = Vec::new();
+    flate2::read::GzDecoder::new(&encoder.get_ref()[..])
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, &source()[1000..]);
+}
+
+#[tokio::test]
+async fn bufread_decoder_keeps_settings() {
+    let compressed = [
+        flate2_gzip(&source()[..1000]),
+        flate2_gzip(&source()[1000..]),
+    ]
+    .concat();
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, source());
+
+    decoder.reset(&compressed[..]).unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, source());
+    assert_eq!(decoder.stats().members(), 2);
+}
+
+#[tokio::test]
+async fn auto_decoder_detects_again() {
+    let gzip = flate2_gzip(source());
+    let zstd = libzstd::encode_all(source(), 0).unwrap();
+
+    let mut decoder = bufread::AutoDecoder::new(&gzip[..]);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, source());
+
+    decoder.reset(&zstd[..]).unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, source());
+}
+
+#[tokio::test]
+async fn read_decoder_drops_buffered_input() {
+    let compressed = flate2_gzip(source());
+
+    let mut decoder = async_compression::tokio::read::GzipDecoder::new(&compressed[..]);
+    let mut output = [0; 100];
+    decoder.read_exact(&mut output).await.unwrap();
+
+    let previous = decoder.reset(&compressed[..]).unwrap();
+    assert!(previous.len() < compressed.len());
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, source());
+
+    let previous = decoder.reset(&[][..]).unwrap();
+    assert!(previous.is_empty());
+}
+
+#[test]
+fn sync_read_encoder() {
+    let mut encoder = async_compression::sync::bufread::ZstdEncoder::new(source());
+    let mut first = Vec::new();
+    encoder.read_to_end(&mut first).unwrap();
+
+    encoder.reset(source()).unwrap();
+    let mut second = Vec::new();
+    encoder.read_to_end(&mut second).unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(libzstd::decode_all(&second[..]).unwrap(), source());
+}
+
+#[tokio::test]
+async fn stream_encoder() {
+    let chunks = || futures::stream::iter(source().chunks(1000).map(|chunk| Ok(chunk.into())));
+
+    let mut encoder = async_compression::stream::ZstdEncoder::new(chunks());
+    let first: Vec<_> = (&mut encoder).map(Result::unwrap).collect().await;
+
+    let _ = encoder.reset(chunks()).unwrap();
+    let second: Vec<_> = (&mut encoder).map(Result::unwrap).collect().await;
+
+    assert_eq!(first, second);
+    assert_eq!(libzstd::decode_all(&second.concat()[..]).unwrap(), source());
+}
@@ -0,0 +1,175 @@
+//! The snappy framing format has no end of stream marker, so unlike the other algorithms data
+//! following a stream can't be left unread, and the shared `test_cases!` don't apply.
+
+#[allow(unused)]
+use futures::{executor::block_on, io::AsyncReadExt};
+
+#[macro_use]
+mod utils;
+
+#[allow(unused)]
+use utils::{algos::snappy::sync, one_to_six, one_to_six_stream, InputStream};
+
+#[cfg(feature = "futures-io")]
+use utils::algos::snappy::futures::{bufread, read, write};
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_compress() {
+    let compressed = bufread::compress(bufread::from(&one_to_six_stream()));
+
+    assert_eq!(sync::decompress(&compressed), one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_compress_empty() {
+    let input = InputStream::new(vec![]);
+    let compressed = bufread::compress(bufread::from(&input));
+
+    // Only the stream identifier.
+    assert_eq!(compressed, b"\xff\x06\x00\x00sNaPpY");
+    assert!(sync::decompress(&compressed).is_empty());
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "futures-io")]
+fn bufread_compress_long() {
+    let bytes: Vec<u8> = (0..200_000).map(|i| (i % 7) as u8).collect();
+    let input = InputStream::from(bytes.chunks(1024));
+    let compressed = bufread::compress(bufread::from(&input));
+
+    assert_eq!(sync::decompress(&compressed), bytes);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn write_compress() {
+    let compressed = write::compress(&[vec![1, 2, 3], vec![4, 5, 6]], 65_536);
+
+    assert_eq!(sync::decompress(&compressed), one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_decompress() {
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let input = InputStream::from(compressed.chunks(2));
+    let output = bufread::decompress(bufread::from(&input));
+
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_decompress_empty() {
+    let input = InputStream::new(vec![]);
+    let output = bufread::decompress(bufread::from(&input));
+
+    assert!(output.is_empty());
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "futures-io")]
+fn bufread_decompress_long() {
+    let bytes: Vec<u8> = (0..65_536 * 3)
+        .map(|i| if i % 3 == 0 { rand::random() } else { 0 })
+        .collect();
+    let compressed = sync::compress(&bytes);
+
+    let input = InputStream::from(compressed.chunks(1000));
+    let output = bufread::decompress(bufread::from(&input));
+
+    assert_eq!(output, bytes);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn write_decompress() {
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let output = write::decompress(&[compressed], 4);
+
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_decompress_concatenated_with_skippable() {
+    let compressed = [
+        sync::compress(&[1, 2, 3, 4, 5, 6]),
+        // Padding and a reserved skippable chunk.
+        vec![0xfe, 0x02, 0x00, 0x00, 0x00, 0x00],
+        vec![0x80, 0x01, 0x00, 0x00, 0xaa],
+        sync::compress(&[6, 5, 4, 3, 2, 1]),
+    ]
+    .concat();
+
+    let input = InputStream::new(vec![compressed]);
+    let output = bufread::decompress(bufread::from(&input));
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6, 6, 5, 4, 3, 2, 1][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_decompress_invalid_checksum() {
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    // The masked CRC-32C follows the stream identifier and the chunk header.
+    compressed[14] ^= 0xff;
+
+    let input = InputStream::new(vec![compressed]);
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = Vec::new();
+
+    assert!(block_on(decoder.read_to_end(&mut output)).is_err());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_decompress_truncated() {
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let input = InputStream::new(vec![compressed[..compressed.len() - 2].to_vec()]);
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = Vec::new();
+
+    assert!(block_on(decoder.read_to_end(&mut output)).is_err());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_decompress_missing_identifier() {
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let input = InputStream::new(vec![compressed[10..].to_vec()]);
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = Vec::new();
+
+    assert!(block_on(decoder.read_to_end(&mut output)).is_err());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_round_trip() {
+    let compressed = bufread::compress(bufread::from(&one_to_six_stream()));
+
+    let input = InputStream::new(vec![compressed]);
+    let output = read::to_vec(bufread::Decoder::new(bufread::from(&input)));
+
+    assert_eq!(output, one_to_six());
+}
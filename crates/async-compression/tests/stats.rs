@@ -0,0 +1,93 @@
+use async_compression::tokio::{bufread, write};
+use std::io::{Read as _, Write as _};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+fn flate2_gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[tokio::test]
+async fn bufread_encoder() {
+    let mut encoder = bufread::GzipEncoder::new(source());
+    let mut compressed = Vec::new();
+    encoder.read_to_end(&mut compressed).await.unwrap();
+
+    let stats = encoder.stats();
+    assert_eq!(stats.bytes_in(), source().len() as u64);
+    assert_eq!(stats.bytes_out(), compressed.len() as u64);
+    assert_eq!(stats.members(), 1);
+    assert_eq!(stats.flushes(), 0);
+}
+
+#[tokio::test]
+async fn bufread_decoder_multiple_members() {
+    let compressed = [flate2_gzip(source()), flate2_gzip(source())].concat();
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+
+    let stats = decoder.stats();
+    assert_eq!(stats.bytes_in(), compressed.len() as u64);
+    assert_eq!(stats.bytes_out(), output.len() as u64);
+    assert_eq!(stats.bytes_out(), 2 * source().len() as u64);
+    assert_eq!(stats.members(), 2);
+}
+
+#[tokio::test]
+async fn write_encoder_flushes() {
+    let mut encoder = write::GzipEncoder::new(Vec::new());
+    encoder.write_all(&source()[..1000]).await.unwrap();
+    encoder.flush().await.unwrap();
+    encoder.write_all(&source()[1000..]).await.unwrap();
+    encoder.flush().await.unwrap();
+    assert_eq!(encoder.stats().flushes(), 2);
+    assert_eq!(encoder.stats().members(), 0);
+
+    encoder.shutdown().await.unwrap();
+
+    let stats = encoder.stats();
+    assert_eq!(stats.bytes_in(), source().len() as u64);
+    assert_eq!(stats.bytes_out(), encoder.get_ref().len() as u64);
+    assert_eq!(stats.members(), 1);
+}
+
+#[tokio::test]
+async fn write_decoder() {
+    let compressed = flate2_gzip(source());
+
+    let mut decoder = write::GzipDecoder::new(Vec::new());
+    decoder.write_all(&compressed).await.unwrap();
+    decoder.shutdown().await.unwrap();
+
+    let stats = decoder.stats();
+    assert_eq!(stats.bytes_in(), compressed.len() as u64);
+    assert_eq!(stats.bytes_out(), source().len() as u64);
+    assert_eq!(stats.members(), 1);
+    if cfg!(feature = "codec-time") {
+        assert!(stats.codec_time() > std::time::Duration::ZERO);
+    } else {
+        assert_eq!(stats.codec_time(), std::time::Duration::ZERO);
+    }
+}
+
+#[test]
+fn sync_bufread_decoder() {
+    let compressed = flate2_gzip(source());
+
+    let mut decoder = async_compression::sync::bufread::GzipDecoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).unwrap();
+
+    let stats = decoder.stats();
+    assert_eq!(stats.bytes_in(), compressed.len() as u64);
+    assert_eq!(stats.bytes_out(), source().len() as u64);
+    assert_eq!(stats.members(), 1);
+}
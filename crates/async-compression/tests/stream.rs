@@ -0,0 +1,153 @@
+use async_compression::stream;
+use bytes::Bytes;
+use futures::{
+    executor::block_on,
+    stream::{self as futures_stream, Stream, StreamExt as _, TryStreamExt as _},
+};
+use futures_test::{stream::StreamTestExt as _, task::noop_context};
+use std::{
+    io::{self, Read, Write},
+    pin::pin,
+    task::Poll,
+};
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+fn chunks(data: &[u8], size: usize) -> impl Stream<Item = io::Result<Bytes>> {
+    futures_stream::iter(
+        data.chunks(size)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn try_concat(stream: impl Stream<Item = io::Result<Bytes>>) -> io::Result<Vec<u8>> {
+    Ok(block_on(stream.try_collect::<Vec<_>>())?.concat())
+}
+
+fn concat(stream: impl Stream<Item = io::Result<Bytes>>) -> Vec<u8> {
+    try_concat(stream).unwrap()
+}
+
+fn flate2_gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn gzip_matches_flate2() {
+    let compressed = concat(stream::GzipEncoder::new(chunks(source(), 1000)));
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(&compressed[..])
+        .read_to_end(&mut decompressed)
+        .unwrap();
+    assert_eq!(decompressed, source());
+
+    let compressed = flate2_gzip(source());
+    let decompressed = concat(stream::GzipDecoder::new(chunks(&compressed, 1000)));
+    assert_eq!(decompressed, source());
+}
+
+#[test]
+fn chunk_size() {
+    let mut encoder = stream::ZstdEncoder::new(chunks(source(), 1000));
+    encoder.set_chunk_size(100);
+    let compressed: Vec<Bytes> = block_on(encoder.try_collect()).unwrap();
+    assert!(compressed.iter().all(|chunk| chunk.len() <= 100));
+    assert!(compressed.len() > 1);
+    let compressed = compressed.concat();
+
+    let mut decoder = stream::ZstdDecoder::new(chunks(&compressed, 7));
+    decoder.set_chunk_size(1000);
+    let decompressed: Vec<Bytes> = block_on(decoder.try_collect()).unwrap();
+    assert!(decompressed.iter().all(|chunk| chunk.len() <= 1000));
+    assert_eq!(decompressed.concat(), source());
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn lz4_block_sized_chunks() {
+    // Incompressible input, so each block is emitted as one chunk of about the block size.
+    let mut state = 1u32;
+    let input: Vec<u8> = (0..256 * 1024)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 24) as u8
+        })
+        .collect();
+
+    let compressed: Vec<Bytes> =
+        block_on(stream::Lz4Encoder::new(chunks(&input, 1000)).try_collect()).unwrap();
+    assert!(compressed.iter().any(|chunk| chunk.len() > 64 * 1024));
+
+    let mut decompressed = Vec::new();
+    lz4::Decoder::new(&compressed.concat()[..])
+        .unwrap()
+        .read_to_end(&mut decompressed)
+        .unwrap();
+    assert_eq!(decompressed, input);
+}
+
+#[test]
+fn flushes_when_pending() {
+    let input = chunks(b"hello world", 5).interleave_pending();
+    let mut encoder = pin!(stream::GzipEncoder::new(input));
+    let mut cx = noop_context();
+
+    let chunk = loop {
+        match encoder.as_mut().poll_next(&mut cx) {
+            Poll::Ready(Some(chunk)) => break chunk.unwrap(),
+            Poll::Ready(None) => panic!("unexpected end of stream"),
+            Poll::Pending => {}
+        }
+    };
+
+    // The first chunk ends at the flush done when the input became pending.
+    let mut output = [0; 5];
+    flate2::read::GzDecoder::new(&chunk[..])
+        .read_exact(&mut output)
+        .unwrap();
+    assert_eq!(&output, b"hello");
+}
+
+#[test]
+fn backpressure() {
+    let input = futures_stream::repeat_with(|| Ok(Bytes::from_static(b"hello world")));
+    let mut encoder = stream::GzipEncoder::new(input);
+    encoder.set_chunk_size(16);
+
+    // Only as much input as needed to emit the taken chunks is read from the endless stream.
+    let compressed: Vec<Bytes> = block_on(encoder.take(3).try_collect()).unwrap();
+    assert_eq!(compressed.len(), 3);
+}
+
+#[test]
+fn multiple_members() {
+    let mut compressed = flate2_gzip(b"hello ");
+    compressed.extend(flate2_gzip(b"world"));
+
+    let decompressed = concat(stream::GzipDecoder::new(chunks(&compressed, 3)));
+    assert_eq!(decompressed, b"hello ");
+
+    let mut decoder = stream::GzipDecoder::new(chunks(&compressed, 3));
+    decoder.multiple_members(true);
+    assert_eq!(concat(decoder), b"hello world");
+}
+
+#[test]
+fn errors() {
+    let compressed = flate2_gzip(source());
+    let truncated = &compressed[..compressed.len() / 2];
+    let err = try_concat(stream::GzipDecoder::new(chunks(truncated, 100))).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    let input = futures_stream::iter(vec![
+        Ok(Bytes::from_static(b"hello")),
+        Err(io::Error::other("input failed")),
+    ]);
+    let err = try_concat(stream::GzipEncoder::new(input)).unwrap_err();
+    assert_eq!(err.to_string(), "input failed");
+}
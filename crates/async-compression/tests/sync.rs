@@ -0,0 +1,133 @@
+use async_compression::{
+    sync::{bufread, write},
+    Algorithm, Level,
+};
+use std::io::{BufReader, ErrorKind, Read, Write};
+
+fn source() -> &'static [u8] {
+    include_bytes!("./artifacts/lib.rs")
+}
+
+fn flate2_gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn read_to_end(mut reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    reader.read_to_end(&mut output)?;
+    Ok(output)
+}
+
+#[test]
+fn bufread_gzip_matches_flate2() {
+    let compressed = read_to_end(bufread::GzipEncoder::new(source())).unwrap();
+    let decompressed = read_to_end(flate2::read::GzDecoder::new(&compressed[..])).unwrap();
+    assert_eq!(decompressed, source());
+
+    let decompressed = read_to_end(bufread::GzipDecoder::new(&flate2_gzip(source())[..])).unwrap();
+    assert_eq!(decompressed, source());
+}
+
+#[test]
+fn bufread_small_reads() {
+    let reader = BufReader::with_capacity(1, source());
+    let compressed = read_to_end(bufread::ZstdEncoder::new(reader)).unwrap();
+
+    let mut decoder = bufread::ZstdDecoder::new(BufReader::with_capacity(1, &compressed[..]));
+    let mut output = Vec::new();
+    let mut buf = [0; 7];
+    loop {
+        match decoder.read(&mut buf).unwrap() {
+            0 => break,
+            len => output.extend_from_slice(&buf[..len]),
+        }
+    }
+    assert_eq!(output, source());
+}
+
+#[test]
+fn write_zstd_matches_libzstd() {
+    let mut encoder = write::ZstdEncoder::with_quality(Vec::new(), Level::Precise(3));
+    for chunk in source().chunks(1000) {
+        encoder.write_all(chunk).unwrap();
+    }
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(libzstd::decode_all(&compressed[..]).unwrap(), source());
+
+    let compressed = libzstd::encode_all(source(), 3).unwrap();
+    let mut decoder = write::ZstdDecoder::new(Vec::new());
+    decoder.write_all(&compressed).unwrap();
+    assert_eq!(decoder.finish().unwrap(), source());
+}
+
+#[test]
+fn write_flush() {
+    let mut encoder = write::GzipEncoder::new(Vec::new());
+    encoder.write_all(b"hello").unwrap();
+    encoder.flush().unwrap();
+
+    let mut decoder = flate2::read::GzDecoder::new(&encoder.get_ref()[..]);
+    let mut output = [0; 5];
+    decoder.read_exact(&mut output).unwrap();
+    assert_eq!(&output, b"hello");
+}
+
+#[test]
+fn multiple_members() {
+    let mut compressed = flate2_gzip(b"hello ");
+    compressed.extend(flate2_gzip(b"world"));
+
+    let decompressed = read_to_end(bufread::GzipDecoder::new(&compressed[..])).unwrap();
+    assert_eq!(decompressed, b"hello ");
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    assert_eq!(read_to_end(decoder).unwrap(), b"hello world");
+}
+
+#[test]
+fn truncated() {
+    let compressed = flate2_gzip(source());
+    let truncated = &compressed[..compressed.len() / 2];
+
+    let err = read_to_end(bufread::GzipDecoder::new(truncated)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    let mut decoder = write::GzipDecoder::new(Vec::new());
+    decoder.write_all(truncated).unwrap();
+    assert!(decoder.try_finish().is_err());
+}
+
+#[test]
+fn write_after_finish() {
+    let mut encoder = write::GzipEncoder::new(Vec::new());
+    encoder.write_all(b"hello").unwrap();
+    encoder.try_finish().unwrap();
+    assert!(encoder.write_all(b"world").is_err());
+}
+
+#[test]
+fn any() {
+    for &algorithm in &[Algorithm::Gzip, Algorithm::Zstd] {
+        let mut encoder = write::AnyEncoder::new(Vec::new(), algorithm).unwrap();
+        encoder.write_all(source()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let decoder = bufread::AnyDecoder::new(&compressed[..], algorithm);
+        assert_eq!(decoder.algorithm(), algorithm);
+        assert_eq!(read_to_end(decoder).unwrap(), source());
+    }
+}
+
+#[test]
+fn auto() {
+    let compressed = flate2_gzip(source());
+    let mut decoder = bufread::AutoDecoder::new(&compressed[..]);
+    assert_eq!(read_to_end(&mut decoder).unwrap(), source());
+    assert_eq!(
+        decoder.format(),
+        Some(async_compression::auto::Format::Gzip)
+    );
+}
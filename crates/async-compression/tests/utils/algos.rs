@@ -164,6 +164,24 @@ algos! {
         }
     }
 
+    pub mod snappy("snappy", SnappyEncoder, SnappyDecoder) {
+        pub mod sync {
+            pub use crate::utils::impls::sync::to_vec;
+
+            pub fn compress(bytes: &[u8]) -> Vec<u8> {
+                use snap::read::FrameEncoder;
+
+                to_vec(FrameEncoder::new(bytes))
+            }
+
+            pub fn decompress(bytes: &[u8]) -> Vec<u8> {
+                use snap::read::FrameDecoder;
+
+                to_vec(FrameDecoder::new(bytes))
+            }
+        }
+    }
+
     pub mod xz("xz", XzEncoder, XzDecoder) {
         pub mod sync {
             pub use crate::utils::impls::sync::to_vec;
@@ -208,6 +226,46 @@ algos! {
         }
     }
 
+    pub mod lzip("lzip", LzipEncoder, LzipDecoder) {
+        pub mod sync {
+            pub use crate::utils::impls::sync::to_vec;
+
+            pub fn compress(bytes: &[u8]) -> Vec<u8> {
+                use liblzma::bufread::XzEncoder;
+                use liblzma::stream::{Filters, LzmaOptions, Stream};
+
+                // liblzma can only decode lzip, so assemble a member around a raw LZMA1 stream.
+                let mut options = LzmaOptions::new_preset(0).unwrap();
+                options.dict_size(1 << 20);
+                let stream = Stream::new_raw_encoder(Filters::new().lzma1(&options)).unwrap();
+                let compressed = to_vec(XzEncoder::new_stream(bytes, stream));
+
+                let mut crc = flate2::Crc::new();
+                crc.update(bytes);
+
+                let member_size = (6 + compressed.len() + 20) as u64;
+                [
+                    &b"LZIP\x01\x14"[..],
+                    &compressed,
+                    &crc.sum().to_le_bytes(),
+                    &(bytes.len() as u64).to_le_bytes(),
+                    &member_size.to_le_bytes(),
+                ]
+                .concat()
+            }
+
+            pub fn decompress(bytes: &[u8]) -> Vec<u8> {
+                use liblzma::bufread::XzDecoder;
+                use liblzma::stream::Stream;
+
+                to_vec(XzDecoder::new_stream(
+                    bytes,
+                    Stream::new_lzip_decoder(u64::MAX, 0).unwrap(),
+                ))
+            }
+        }
+    }
+
     pub mod lz4("lz4", Lz4Encoder, Lz4Decoder) {
         pub mod sync {
             pub use crate::utils::impls::sync::to_vec;
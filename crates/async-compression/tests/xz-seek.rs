@@ -0,0 +1,172 @@
+use async_compression::tokio::seek::XzDecoder;
+use liblzma::stream::{Action, Check, Status, Stream};
+use std::io::{Cursor, SeekFrom};
+use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _};
+
+/// Compresses `input` into a single stream with a block for every `block_size` bytes.
+fn compress_blocks(input: &[u8], block_size: usize) -> Vec<u8> {
+    let mut stream = Stream::new_easy_encoder(0, Check::Crc64).unwrap();
+    let mut output = Vec::with_capacity(input.len() + 1024);
+
+    for mut chunk in input.chunks(block_size) {
+        while !chunk.is_empty() {
+            output.reserve(64 * 1024);
+            let prior_in = stream.total_in();
+            stream.process_vec(chunk, &mut output, Action::Run).unwrap();
+            chunk = &chunk[(stream.total_in() - prior_in) as usize..];
+        }
+
+        // A full flush ends the current block.
+        output.reserve(64 * 1024);
+        while stream
+            .process_vec(&[], &mut output, Action::FullFlush)
+            .unwrap()
+            != Status::StreamEnd
+        {
+            output.reserve(64 * 1024);
+        }
+    }
+
+    output.reserve(64 * 1024);
+    while stream
+        .process_vec(&[], &mut output, Action::Finish)
+        .unwrap()
+        != Status::StreamEnd
+    {
+        output.reserve(64 * 1024);
+    }
+    output
+}
+
+/// Two streams with padding between and after them, made of five blocks in total.
+fn input() -> (Vec<u8>, Vec<u8>) {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let input = source.repeat(4);
+    let (first, second) = input.split_at(source.len());
+
+    let compressed = [
+        compress_blocks(first, first.len()),
+        vec![0; 8],
+        compress_blocks(second, source.len()),
+        vec![0; 4],
+    ]
+    .concat();
+
+    (input, compressed)
+}
+
+#[tokio::test]
+async fn xz_seek_index() {
+    let (input, compressed) = input();
+
+    let mut decoder = XzDecoder::new(Cursor::new(compressed));
+    let index = decoder.read_index().await.unwrap();
+
+    assert_eq!(index.blocks().len(), 4);
+    assert_eq!(index.uncompressed_size(), input.len() as u64);
+
+    let mut offset = 0;
+    for block in index.blocks() {
+        assert_eq!(block.uncompressed_offset(), offset);
+        offset += block.uncompressed_size();
+    }
+}
+
+#[tokio::test]
+async fn xz_seek_read_all() {
+    let (input, compressed) = input();
+
+    let mut decoder = XzDecoder::new(Cursor::new(compressed));
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+
+    assert_eq!(output, input);
+}
+
+#[tokio::test]
+async fn xz_seek_ranges() {
+    let (input, compressed) = input();
+    let len = input.len() as u64;
+
+    let mut decoder = XzDecoder::new(Cursor::new(compressed));
+    for (pos, start) in [
+        (SeekFrom::Start(len / 2), len / 2),
+        (SeekFrom::Start(10), 10),
+        (SeekFrom::End(-100), len - 100),
+        (SeekFrom::Current(-1000), len - 1000),
+        (SeekFrom::Start(len / 4 - 50), len / 4 - 50),
+    ] {
+        assert_eq!(decoder.seek(pos).await.unwrap(), start);
+
+        let mut output = vec![0; 100];
+        decoder.read_exact(&mut output).await.unwrap();
+
+        let start = start as usize;
+        assert_eq!(output, input[start..start + 100]);
+    }
+}
+
+#[tokio::test]
+async fn xz_seek_past_end() {
+    let (input, compressed) = input();
+
+    let mut decoder = XzDecoder::new(Cursor::new(compressed));
+    decoder
+        .seek(SeekFrom::Start(input.len() as u64 + 10))
+        .await
+        .unwrap();
+
+    let mut output = Vec::new();
+    assert_eq!(decoder.read_to_end(&mut output).await.unwrap(), 0);
+
+    decoder.seek(SeekFrom::Current(-100_000)).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn xz_seek_corrupt_block() {
+    let (input, mut compressed) = input();
+
+    let mut decoder = XzDecoder::new(Cursor::new(compressed.clone()));
+    let block = decoder.read_index().await.unwrap().blocks()[1];
+    compressed[(block.compressed_offset() + block.compressed_size() / 2) as usize] ^= 0xff;
+
+    let mut decoder = XzDecoder::new(Cursor::new(compressed));
+
+    // Blocks other than the corrupt one can still be read.
+    decoder.seek(SeekFrom::End(-100)).await.unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+    assert_eq!(output, input[input.len() - 100..]);
+
+    decoder
+        .seek(SeekFrom::Start(block.uncompressed_offset()))
+        .await
+        .unwrap();
+    decoder.read_to_end(&mut Vec::new()).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn xz_seek_invalid_index() {
+    let (_, compressed) = input();
+
+    let mut decoder = XzDecoder::new(Cursor::new(&compressed[..compressed.len() - 20]));
+    decoder.read_index().await.unwrap_err();
+}
+
+#[cfg(feature = "futures-io")]
+#[test]
+fn xz_seek_futures() {
+    use futures::io::{AsyncReadExt as _, AsyncSeekExt as _};
+
+    let (input, compressed) = input();
+
+    futures::executor::block_on(async {
+        let mut decoder =
+            async_compression::futures::seek::XzDecoder::new(futures::io::Cursor::new(compressed));
+        decoder.seek(SeekFrom::End(-100)).await.unwrap();
+
+        let mut output = Vec::new();
+        decoder.read_to_end(&mut output).await.unwrap();
+        assert_eq!(output, input[input.len() - 100..]);
+    });
+}
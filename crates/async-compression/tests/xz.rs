@@ -56,3 +56,278 @@ fn bufread_multiple_members_with_invalid_padding() {
     let mut output = Vec::new();
     assert!(block_on(decoder.read_to_end(&mut output)).is_err());
 }
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn write_with_params_check() {
+    use async_compression::lzma::LzmaEncoderParams;
+    use futures::io::AsyncWriteExt;
+    use liblzma::stream::Check;
+
+    let params = LzmaEncoderParams::Easy {
+        preset: 1,
+        check: Check::Sha256,
+    };
+    let mut encoder =
+        async_compression::futures::write::XzEncoder::with_params(Vec::new(), params).unwrap();
+    block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    block_on(encoder.close()).unwrap();
+    let compressed = encoder.into_inner();
+
+    // The check type is stored in the stream flags of the header.
+    assert_eq!(compressed[7], 0x0a);
+    assert_eq!(sync::decompress(&compressed), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[cfg(feature = "futures-io")]
+fn write_with_params_not_xz() {
+    use async_compression::lzma::{LzmaEncoderParams, LzmaOptions};
+
+    let params = LzmaEncoderParams::Lzma {
+        options: LzmaOptions::default(),
+    };
+    assert!(async_compression::futures::write::XzEncoder::with_params(Vec::new(), params).is_err());
+}
+
+#[cfg(feature = "futures-io")]
+fn compress_with_check(input: &[u8], check: liblzma::stream::Check) -> Vec<u8> {
+    use std::io::Write;
+
+    let stream = liblzma::stream::Stream::new_easy_encoder(1, check).unwrap();
+    let mut encoder = liblzma::write::XzEncoder::new_stream(Vec::new(), stream);
+    encoder.write_all(input).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_reports_check() {
+    use async_compression::lzma::StreamCheck;
+    use liblzma::stream::Check;
+
+    let compressed = compress_with_check(&[1, 2, 3, 4, 5, 6], Check::Sha256);
+    let input = InputStream::from(vec![compressed]);
+
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    assert!(decoder.check().is_none());
+
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert!(matches!(
+        decoder.check(),
+        Some(StreamCheck::Supported(Check::Sha256))
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_ignore_check() {
+    use async_compression::lzma::LzmaDecoderOptions;
+    use liblzma::stream::Check;
+
+    let mut compressed = compress_with_check(&[1, 2, 3, 4, 5, 6], Check::Crc32);
+    // The CRC32 of the only block sits right before the 8 byte index and 12 byte stream footer.
+    let check_offset = compressed.len() - 12 - 8 - 4;
+    compressed[check_offset] ^= 0xff;
+
+    let input = InputStream::from(vec![compressed.clone()]);
+    let mut output = Vec::new();
+    assert!(
+        block_on(bufread::Decoder::new(bufread::from(&input)).read_to_end(&mut output)).is_err()
+    );
+
+    let input = InputStream::from(vec![compressed]);
+    let mut decoder = async_compression::futures::bufread::XzDecoder::with_options(
+        bufread::from(&input),
+        LzmaDecoderOptions::default().ignore_check(true),
+    );
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_concatenated() {
+    use async_compression::lzma::LzmaDecoderOptions;
+
+    let compressed = [
+        sync::compress(&[1, 2, 3, 4, 5, 6]),
+        vec![0, 0, 0, 0],
+        sync::compress(&[6, 5, 4, 3, 2, 1]),
+    ]
+    .join(&[][..]);
+
+    let input = InputStream::from(vec![compressed]);
+    let mut decoder = async_compression::futures::bufread::XzDecoder::with_options(
+        bufread::from(&input),
+        LzmaDecoderOptions::default().concatenated(true),
+    );
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6, 6, 5, 4, 3, 2, 1][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_tell_no_check() {
+    use async_compression::lzma::{LzmaDecoderOptions, StreamCheck};
+    use liblzma::stream::Check;
+
+    let compressed = compress_with_check(&[1, 2, 3, 4, 5, 6], Check::None);
+
+    let input = InputStream::from(vec![compressed.clone()]);
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert!(matches!(
+        decoder.check(),
+        Some(StreamCheck::Supported(Check::None))
+    ));
+
+    let input = InputStream::from(vec![compressed]);
+    let mut decoder = async_compression::futures::bufread::XzDecoder::with_options(
+        bufread::from(&input),
+        LzmaDecoderOptions::default().tell_no_check(true),
+    );
+    let mut output = Vec::new();
+    assert!(block_on(decoder.read_to_end(&mut output)).is_err());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_reports_unsupported_check() {
+    use async_compression::lzma::StreamCheck;
+    use liblzma::stream::Check;
+
+    // Check ID 2 is reserved for a 4 byte check, so a CRC32 stream only needs its stream flags,
+    // and the CRC32s covering them, changed to use it.
+    let mut compressed = compress_with_check(&[1, 2, 3, 4, 5, 6], Check::Crc32);
+    let crc32 = |data: &[u8]| {
+        let mut crc = flate2::Crc::new();
+        crc.update(data);
+        crc.sum().to_le_bytes()
+    };
+    compressed[7] = 0x02;
+    let crc = crc32(&compressed[6..8]);
+    compressed[8..12].copy_from_slice(&crc);
+    let footer = compressed.len() - 12;
+    compressed[footer + 9] = 0x02;
+    let crc = crc32(&compressed[footer + 4..footer + 10]);
+    compressed[footer..footer + 4].copy_from_slice(&crc);
+
+    let input = InputStream::from(vec![compressed]);
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert!(matches!(decoder.check(), Some(StreamCheck::Unsupported(2))));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn bufread_with_options_not_xz() {
+    use async_compression::lzma::LzmaDecoderOptions;
+    use std::io::Write;
+
+    let stream = liblzma::stream::Stream::new_lzma_encoder(
+        &liblzma::stream::LzmaOptions::new_preset(1).unwrap(),
+    )
+    .unwrap();
+    let mut encoder = liblzma::write::XzEncoder::new_stream(Vec::new(), stream);
+    encoder.write_all(&[1, 2, 3, 4, 5, 6]).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let input = InputStream::from(vec![compressed]);
+    let mut decoder = async_compression::futures::bufread::XzDecoder::with_options(
+        bufread::from(&input),
+        LzmaDecoderOptions::default(),
+    );
+    let mut output = Vec::new();
+    assert!(block_on(decoder.read_to_end(&mut output)).is_err());
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "futures-io")]
+fn write_with_block_size() {
+    use async_compression::codecs::xz::XzIndexParser;
+    use futures::io::AsyncWriteExt;
+    use std::num::NonZeroU64;
+
+    let input: Vec<u8> = (0..10_000u32)
+        .flat_map(|i| (i % 251).to_le_bytes())
+        .collect();
+
+    let mut encoder = async_compression::futures::write::XzEncoder::with_block_size(
+        Vec::new(),
+        async_compression::Level::Default,
+        NonZeroU64::new(16 * 1024).unwrap(),
+    );
+    for chunk in input.chunks(1000) {
+        block_on(encoder.write_all(chunk)).unwrap();
+    }
+    block_on(encoder.close()).unwrap();
+    let compressed = encoder.into_inner();
+
+    // The first block header records both its compressed and uncompressed size.
+    assert_eq!(compressed[13] & 0xc0, 0xc0);
+    assert_eq!(sync::decompress(&compressed), input);
+
+    let mut parser = XzIndexParser::new(compressed.len() as u64);
+    while let Some((offset, len)) = parser.next_read().unwrap() {
+        let offset = offset as usize;
+        parser.feed(&compressed[offset..offset + len]).unwrap();
+    }
+    let index = parser.finish().unwrap();
+
+    let sizes: Vec<u64> = index
+        .blocks()
+        .iter()
+        .map(|block| block.uncompressed_size())
+        .collect();
+    assert_eq!(sizes, [16 * 1024, 16 * 1024, 7232]);
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(all(feature = "futures-io", feature = "xz-parallel"))]
+fn write_with_block_size_parallel_decode() {
+    use futures::io::AsyncWriteExt;
+    use std::num::{NonZeroU32, NonZeroU64};
+
+    let input: Vec<u8> = (0..10_000u32)
+        .flat_map(|i| (i % 251).to_le_bytes())
+        .collect();
+
+    let mut encoder = async_compression::futures::write::XzEncoder::with_block_size(
+        Vec::new(),
+        async_compression::Level::Fastest,
+        NonZeroU64::new(4096).unwrap(),
+    );
+    block_on(encoder.write_all(&input)).unwrap();
+    block_on(encoder.close()).unwrap();
+    let compressed = encoder.into_inner();
+
+    let mut decoder = async_compression::futures::bufread::XzDecoder::parallel(
+        &compressed[..],
+        NonZeroU32::new(4).unwrap(),
+    );
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, input);
+}
@@ -0,0 +1,241 @@
+use async_compression::tokio::zip::{ZipEntry, ZipMethod, ZipWriter};
+use std::io::{Cursor, Read as _};
+use tokio::io::AsyncWriteExt as _;
+use zip::{CompressionMethod, ZipArchive};
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+/// Opens `archive` with the `zip` crate, an independent reader.
+fn read_archive(archive: Vec<u8>) -> ZipArchive<Cursor<Vec<u8>>> {
+    ZipArchive::new(Cursor::new(archive)).unwrap()
+}
+
+/// Reads the data of entry `index`, which the `zip` crate checks against its CRC-32.
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, index: usize) -> Vec<u8> {
+    let mut data = Vec::new();
+    archive
+        .by_index(index)
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    data
+}
+
+async fn write_archive(entries: Vec<(ZipEntry, &[u8])>) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Vec::new());
+    for (entry, data) in entries {
+        writer.start_entry(entry).await.unwrap();
+        for chunk in data.chunks(1000) {
+            writer.write_all(chunk).await.unwrap();
+        }
+    }
+    writer.shutdown().await.unwrap();
+    writer.into_inner()
+}
+
+#[tokio::test]
+async fn stored_and_deflate() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let archive = write_archive(vec![
+        (ZipEntry::new("dir/"), &[]),
+        (
+            ZipEntry::new("dir/stored.rs").method(ZipMethod::Stored),
+            source,
+        ),
+        (
+            ZipEntry::new("dir/deflated.rs")
+                .comment("deflated")
+                .unix_permissions(0o100_644),
+            source,
+        ),
+    ])
+    .await;
+
+    let mut archive = read_archive(archive);
+    assert_eq!(archive.len(), 3);
+
+    {
+        let entry = archive.by_index(0).unwrap();
+        assert_eq!(entry.name(), "dir/");
+        assert!(entry.is_dir());
+        assert_eq!(entry.size(), 0);
+    }
+
+    {
+        let entry = archive.by_index(1).unwrap();
+        assert_eq!(entry.name(), "dir/stored.rs");
+        assert_eq!(entry.compression(), CompressionMethod::Stored);
+        assert_eq!(entry.crc32(), crc32(source));
+    }
+    assert_eq!(read_entry(&mut archive, 1), source);
+
+    {
+        let entry = archive.by_index(2).unwrap();
+        assert_eq!(entry.compression(), CompressionMethod::Deflated);
+        assert_eq!(entry.comment(), "deflated");
+        assert_eq!(entry.unix_mode(), Some(0o100_644));
+        assert_eq!(entry.crc32(), crc32(source));
+        assert_eq!(entry.size(), source.len() as u64);
+        assert!(entry.compressed_size() < source.len() as u64);
+    }
+    assert_eq!(read_entry(&mut archive, 2), source);
+}
+
+#[tokio::test]
+async fn utf8_name() {
+    let archive = write_archive(vec![(ZipEntry::new("größe.txt"), b"data")]).await;
+
+    // Without the UTF-8 flag the name would be read as CP437.
+    let mut archive = read_archive(archive);
+    assert_eq!(archive.by_index(0).unwrap().name(), "größe.txt");
+    assert_eq!(read_entry(&mut archive, 0), b"data");
+}
+
+#[tokio::test]
+async fn empty_archive() {
+    let archive = write_archive(vec![]).await;
+
+    assert_eq!(archive, [&[0x50, 0x4b, 0x05, 0x06][..], &[0; 18]].concat());
+    assert!(read_archive(archive).is_empty());
+}
+
+#[tokio::test]
+#[cfg(feature = "bzip2")]
+async fn bzip2() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let archive = write_archive(vec![(
+        ZipEntry::new("lib.rs").method(ZipMethod::Bzip2),
+        source,
+    )])
+    .await;
+
+    let mut archive = read_archive(archive);
+    assert_eq!(
+        archive.by_index(0).unwrap().compression(),
+        CompressionMethod::Bzip2
+    );
+    assert_eq!(read_entry(&mut archive, 0), source);
+}
+
+#[tokio::test]
+#[cfg(feature = "lzma")]
+async fn lzma() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let archive = write_archive(vec![(
+        ZipEntry::new("lib.rs").method(ZipMethod::Lzma),
+        source,
+    )])
+    .await;
+
+    let mut archive = read_archive(archive);
+    assert_eq!(
+        archive.by_index(0).unwrap().compression(),
+        CompressionMethod::Lzma
+    );
+    assert_eq!(read_entry(&mut archive, 0), source);
+}
+
+#[tokio::test]
+#[cfg(feature = "zstd")]
+async fn zstd() {
+    let source = include_bytes!("./artifacts/lib.rs");
+    let archive = write_archive(vec![(
+        ZipEntry::new("lib.rs").method(ZipMethod::Zstd),
+        source,
+    )])
+    .await;
+
+    let mut archive = read_archive(archive);
+    assert_eq!(
+        archive.by_index(0).unwrap().compression(),
+        CompressionMethod::Zstd
+    );
+    assert_eq!(read_entry(&mut archive, 0), source);
+}
+
+#[tokio::test]
+async fn zip64_entry_count() {
+    let count = usize::from(u16::MAX) + 1;
+    let mut writer = ZipWriter::new(Vec::new());
+    for i in 0..count {
+        let entry = ZipEntry::new(i.to_string()).method(ZipMethod::Stored);
+        writer.start_entry(entry).await.unwrap();
+    }
+    writer.shutdown().await.unwrap();
+    let archive = writer.into_inner();
+
+    let mut archive = read_archive(archive);
+    assert_eq!(archive.len(), count);
+    assert_eq!(
+        archive.by_index(count - 1).unwrap().name(),
+        (count - 1).to_string()
+    );
+}
+
+#[tokio::test]
+#[cfg(feature = "zstd")]
+async fn zip64_entry_size() {
+    // Just over 4 GiB of zeros, which compress to very little, so the data descriptor needs 64 bit
+    // sizes.
+    let len = (1u64 << 32) + 1;
+    let chunk = vec![0; 1 << 20];
+
+    let mut writer = ZipWriter::new(Vec::new());
+    writer
+        .start_entry(ZipEntry::new("large").method(ZipMethod::Zstd))
+        .await
+        .unwrap();
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(chunk.len() as u64) as usize;
+        writer.write_all(&chunk[..n]).await.unwrap();
+        remaining -= n as u64;
+    }
+    writer.start_entry(ZipEntry::new("after")).await.unwrap();
+    writer.write_all(b"data").await.unwrap();
+    writer.shutdown().await.unwrap();
+    let data = writer.into_inner();
+
+    // The local header needs ZIP64 support and has a ZIP64 extra field after the name.
+    assert!(u16::from_le_bytes([data[4], data[5]]) >= 45);
+    assert_eq!(data[28..30], 20u16.to_le_bytes());
+    assert_eq!(data[35..37], 1u16.to_le_bytes());
+
+    let mut archive = read_archive(data.clone());
+    let (descriptor, compressed_size) = {
+        let entry = archive.by_index(0).unwrap();
+        assert_eq!(entry.size(), len);
+        assert!(entry.compressed_size() < len);
+        (
+            (entry.data_start() + entry.compressed_size()) as usize,
+            entry.compressed_size(),
+        )
+    };
+
+    // The data descriptor has the signature, CRC-32 and 64 bit sizes.
+    let descriptor = &data[descriptor..descriptor + 24];
+    assert_eq!(descriptor[..4], 0x0807_4b50u32.to_le_bytes());
+    assert_eq!(descriptor[8..16], compressed_size.to_le_bytes());
+    assert_eq!(descriptor[16..], len.to_le_bytes());
+
+    assert_eq!(read_entry(&mut archive, 1), b"data");
+}
+
+#[tokio::test]
+async fn write_without_entry() {
+    let mut writer = ZipWriter::new(Vec::new());
+
+    assert!(writer.write_all(b"data").await.is_err());
+}
+
+#[tokio::test]
+async fn start_entry_after_shutdown() {
+    let mut writer = ZipWriter::new(Vec::new());
+    writer.shutdown().await.unwrap();
+
+    assert!(writer.start_entry(ZipEntry::new("late")).await.is_err());
+}
@@ -0,0 +1,147 @@
+use std::num::NonZeroU32;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+const THREADS: NonZeroU32 = NonZeroU32::new(4).unwrap();
+
+/// Returns the expected output and its compressed form, made of many independent frames.
+fn input() -> (Vec<u8>, Vec<u8>) {
+    let source = include_bytes!("./artifacts/lib.rs");
+
+    let mut input = Vec::new();
+    let mut compressed = Vec::new();
+    for i in 0..32 {
+        let frame = &source[..source.len() / 32 * (i + 1)];
+        input.extend_from_slice(frame);
+        compressed.extend(libzstd::stream::encode_all(frame, 0).unwrap());
+    }
+    (input, compressed)
+}
+
+#[tokio::test]
+async fn zstd_parallel_write_decode() {
+    let (input, compressed) = input();
+
+    let mut decoder =
+        async_compression::tokio::write::ZstdDecoder::parallel(Vec::new(), THREADS).unwrap();
+    for chunk in compressed.chunks(1000) {
+        decoder.write_all(chunk).await.unwrap();
+    }
+    decoder.shutdown().await.unwrap();
+
+    assert_eq!(decoder.into_inner(), input);
+}
+
+#[tokio::test]
+async fn zstd_parallel_bufread_decode() {
+    let (input, compressed) = input();
+
+    let mut decoder =
+        async_compression::tokio::bufread::ZstdDecoder::parallel(&compressed[..], THREADS).unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+
+    assert_eq!(output, input);
+}
+
+#[tokio::test]
+async fn zstd_parallel_skippable_frame() {
+    let mut compressed = libzstd::stream::encode_all(&b"hello "[..], 0).unwrap();
+    // Skippable frame magic, length and payload.
+    compressed.extend_from_slice(&0x184D2A50u32.to_le_bytes());
+    compressed.extend_from_slice(&3u32.to_le_bytes());
+    compressed.extend_from_slice(b"abc");
+    compressed.extend(libzstd::stream::encode_all(&b"world"[..], 0).unwrap());
+
+    let mut decoder =
+        async_compression::tokio::bufread::ZstdDecoder::parallel(&compressed[..], THREADS).unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+
+    assert_eq!(output, b"hello world");
+}
+
+#[tokio::test]
+async fn zstd_parallel_truncated() {
+    let (_, compressed) = input();
+
+    let mut decoder = async_compression::tokio::bufread::ZstdDecoder::parallel(
+        &compressed[..compressed.len() - 10],
+        THREADS,
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap_err();
+}
+
+/// Returns `len` bytes which don't compress.
+fn random(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn zstd_parallel_oversized_frame() {
+    // A frame too large to be buffered, between frames decoded by the workers.
+    let parts = [
+        b"before ".to_vec(),
+        random(5 * 1024 * 1024),
+        b" after".to_vec(),
+    ];
+
+    let mut compressed = Vec::new();
+    for part in &parts {
+        compressed.extend(libzstd::stream::encode_all(&part[..], 1).unwrap());
+    }
+
+    let mut decoder =
+        async_compression::tokio::write::ZstdDecoder::parallel(Vec::new(), THREADS).unwrap();
+    for chunk in compressed.chunks(64 * 1024) {
+        decoder.write_all(chunk).await.unwrap();
+    }
+    decoder.shutdown().await.unwrap();
+
+    assert_eq!(decoder.into_inner(), parts.concat());
+}
+
+#[tokio::test]
+async fn zstd_parallel_large_output() {
+    // Frames decoding to far more than they are handed back in.
+    let input = vec![7; 32 * 1024 * 1024];
+    let mut compressed = libzstd::stream::encode_all(&input[..], 0).unwrap();
+    compressed.extend(libzstd::stream::encode_all(&input[..], 0).unwrap());
+
+    let mut decoder =
+        async_compression::tokio::bufread::ZstdDecoder::parallel(&compressed[..], THREADS).unwrap();
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).await.unwrap();
+
+    assert_eq!(output.len(), 2 * input.len());
+    assert!(output.iter().all(|&byte| byte == 7));
+}
+
+#[test]
+fn zstd_parallel_wakes_task() {
+    let (input, compressed) = input();
+
+    // `block_on` only polls again once woken, so this hangs if the decoder returns
+    // `Poll::Pending` without the workers waking the task.
+    let output = futures::executor::block_on(async {
+        use futures::io::AsyncReadExt as _;
+
+        let mut decoder =
+            async_compression::futures::bufread::ZstdDecoder::parallel(&compressed[..], THREADS)
+                .unwrap();
+        let mut output = Vec::new();
+        decoder.read_to_end(&mut output).await.unwrap();
+        output
+    });
+
+    assert_eq!(output, input);
+}
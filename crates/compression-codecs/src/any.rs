@@ -0,0 +1,349 @@
+//! Encoders and decoders for an algorithm chosen at runtime.
+
+use crate::{DecodeV2, DecoderLimits, EncodeV2, FlushMode};
+use compression_core::{
+    unshared::Unshared,
+    util::{PartialBuffer, WriteBuffer},
+    Level,
+};
+use std::{fmt, io::Result};
+
+/// A compression algorithm supported by the enabled crate features.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    #[cfg(feature = "brotli")]
+    Brotli,
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+    #[cfg(feature = "deflate")]
+    Deflate,
+    /// Deflate64, which can only be decoded.
+    #[cfg(feature = "deflate64")]
+    Deflate64,
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "lz4")]
+    Lz4,
+    #[cfg(feature = "lzip")]
+    Lzip,
+    #[cfg(feature = "lzma")]
+    Lzma,
+    /// The `compress` format, which can only be decoded.
+    #[cfg(feature = "lzw")]
+    Lzw,
+    #[cfg(feature = "snappy")]
+    Snappy,
+    #[cfg(feature = "xz")]
+    Xz,
+    #[cfg(feature = "zlib")]
+    Zlib,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Algorithm {
+    /// Every supported algorithm.
+    pub const ALL: &'static [Self] = &[
+        #[cfg(feature = "brotli")]
+        Self::Brotli,
+        #[cfg(feature = "bzip2")]
+        Self::Bzip2,
+        #[cfg(feature = "deflate")]
+        Self::Deflate,
+        #[cfg(feature = "deflate64")]
+        Self::Deflate64,
+        #[cfg(feature = "gzip")]
+        Self::Gzip,
+        #[cfg(feature = "lz4")]
+        Self::Lz4,
+        #[cfg(feature = "lzip")]
+        Self::Lzip,
+        #[cfg(feature = "lzma")]
+        Self::Lzma,
+        #[cfg(feature = "lzw")]
+        Self::Lzw,
+        #[cfg(feature = "snappy")]
+        Self::Snappy,
+        #[cfg(feature = "xz")]
+        Self::Xz,
+        #[cfg(feature = "zlib")]
+        Self::Zlib,
+        #[cfg(feature = "zstd")]
+        Self::Zstd,
+    ];
+
+    /// Returns the name of the crate feature enabling this algorithm, such as `"gzip"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "brotli")]
+            Self::Brotli => "brotli",
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => "bzip2",
+            #[cfg(feature = "deflate")]
+            Self::Deflate => "deflate",
+            #[cfg(feature = "deflate64")]
+            Self::Deflate64 => "deflate64",
+            #[cfg(feature = "gzip")]
+            Self::Gzip => "gzip",
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => "lz4",
+            #[cfg(feature = "lzip")]
+            Self::Lzip => "lzip",
+            #[cfg(feature = "lzma")]
+            Self::Lzma => "lzma",
+            #[cfg(feature = "lzw")]
+            Self::Lzw => "lzw",
+            #[cfg(feature = "snappy")]
+            Self::Snappy => "snappy",
+            #[cfg(feature = "xz")]
+            Self::Xz => "xz",
+            #[cfg(feature = "zlib")]
+            Self::Zlib => "zlib",
+            #[cfg(feature = "zstd")]
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Parses the name of an algorithm as returned by [`name`](Self::name), ignoring case.
+    ///
+    /// Returns `None` for algorithms which are unknown or whose crate feature is not enabled.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| name.eq_ignore_ascii_case(algorithm.name()))
+    }
+
+    /// Returns whether data can be compressed with this algorithm, which is not the case for
+    /// [`Deflate64`](Self::Deflate64) and [`Lzw`](Self::Lzw).
+    pub fn can_encode(self) -> bool {
+        match self {
+            #[cfg(feature = "deflate64")]
+            Self::Deflate64 => false,
+            #[cfg(feature = "lzw")]
+            Self::Lzw => false,
+            #[allow(unreachable_patterns)]
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+fn encoder(algorithm: Algorithm, level: Level) -> Result<Box<dyn EncodeV2 + Send>> {
+    let _ = level;
+    match algorithm {
+        #[cfg(feature = "brotli")]
+        Algorithm::Brotli => Ok(Box::new(crate::BrotliEncoder::new(
+            crate::brotli::params::EncoderParams::default().quality(level),
+        ))),
+        #[cfg(feature = "bzip2")]
+        Algorithm::Bzip2 => Ok(Box::new(crate::BzEncoder::new(level.into(), 0))),
+        #[cfg(feature = "deflate")]
+        Algorithm::Deflate => Ok(Box::new(crate::DeflateEncoder::new(level.into()))),
+        #[cfg(feature = "gzip")]
+        Algorithm::Gzip => Ok(Box::new(crate::GzipEncoder::new(level.into()))),
+        #[cfg(feature = "lz4")]
+        Algorithm::Lz4 => Ok(Box::new(crate::Lz4Encoder::new(
+            crate::lz4::params::EncoderParams::default().level(level),
+        ))),
+        #[cfg(feature = "lzip")]
+        Algorithm::Lzip => Ok(Box::new(crate::LzipEncoder::new(level))),
+        #[cfg(feature = "lzma")]
+        Algorithm::Lzma => Ok(Box::new(crate::LzmaEncoder::new(level))),
+        #[cfg(feature = "snappy")]
+        Algorithm::Snappy => Ok(Box::new(crate::SnappyEncoder::new())),
+        #[cfg(feature = "xz")]
+        Algorithm::Xz => Ok(Box::new(crate::XzEncoder::new(level))),
+        #[cfg(feature = "zlib")]
+        Algorithm::Zlib => Ok(Box::new(crate::ZlibEncoder::new(level.into()))),
+        #[cfg(feature = "zstd")]
+        Algorithm::Zstd => Ok(Box::new(crate::ZstdEncoder::new(
+            crate::zstd::params::CParameter::quality(level),
+        ))),
+        #[cfg(feature = "deflate64")]
+        Algorithm::Deflate64 => Err(decode_only(algorithm)),
+        #[cfg(feature = "lzw")]
+        Algorithm::Lzw => Err(decode_only(algorithm)),
+    }
+}
+
+#[cfg(any(feature = "deflate64", feature = "lzw"))]
+fn decode_only(algorithm: Algorithm) -> std::io::Error {
+    use std::io::{Error, ErrorKind};
+
+    Error::new(
+        ErrorKind::Unsupported,
+        format!("{algorithm} can only be decoded"),
+    )
+}
+
+/// An encoder for an [`Algorithm`] chosen at runtime.
+pub struct AnyEncoder {
+    algorithm: Algorithm,
+    encoder: Unshared<Box<dyn EncodeV2 + Send>>,
+    // `Unshared` only gives access to the encoder through `&mut self`.
+    input_size_hint: usize,
+}
+
+impl fmt::Debug for AnyEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyEncoder")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AnyEncoder {
+    /// Creates an encoder for `algorithm` compressing with the given level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Unsupported`](std::io::ErrorKind::Unsupported) error for algorithms which can only be
+    /// decoded, see [`Algorithm::can_encode`].
+    pub fn new(algorithm: Algorithm, level: Level) -> Result<Self> {
+        let encoder = encoder(algorithm, level)?;
+        Ok(Self {
+            algorithm,
+            input_size_hint: encoder.input_size_hint(),
+            encoder: Unshared::new(encoder),
+        })
+    }
+
+    /// Returns the algorithm of this encoder.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+}
+
+impl EncodeV2 for AnyEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<()> {
+        self.encoder.get_mut().encode(input, output)
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.encoder.get_mut().flush(output)
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.encoder.get_mut().finish(output)
+    }
+
+    fn input_size_hint(&self) -> usize {
+        self.input_size_hint
+    }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()> {
+        self.encoder.get_mut().set_flush_mode(mode)
+    }
+
+    fn reinit(&mut self) -> Result<()> {
+        self.encoder.get_mut().reinit()
+    }
+}
+
+fn decoder(algorithm: Algorithm) -> Box<dyn DecodeV2 + Send> {
+    match algorithm {
+        #[cfg(feature = "brotli")]
+        Algorithm::Brotli => Box::new(crate::BrotliDecoder::new()),
+        #[cfg(feature = "bzip2")]
+        Algorithm::Bzip2 => Box::new(crate::BzDecoder::new()),
+        #[cfg(feature = "deflate")]
+        Algorithm::Deflate => Box::new(crate::DeflateDecoder::new()),
+        #[cfg(feature = "deflate64")]
+        Algorithm::Deflate64 => Box::new(crate::Deflate64Decoder::new()),
+        #[cfg(feature = "gzip")]
+        Algorithm::Gzip => Box::new(crate::GzipDecoder::new()),
+        #[cfg(feature = "lz4")]
+        Algorithm::Lz4 => Box::new(crate::Lz4Decoder::new()),
+        #[cfg(feature = "lzip")]
+        Algorithm::Lzip => Box::new(crate::LzipDecoder::new()),
+        #[cfg(feature = "lzma")]
+        Algorithm::Lzma => Box::new(crate::LzmaDecoder::new()),
+        #[cfg(feature = "lzw")]
+        Algorithm::Lzw => Box::new(crate::CompressZDecoder::new()),
+        #[cfg(feature = "snappy")]
+        Algorithm::Snappy => Box::new(crate::SnappyDecoder::new()),
+        #[cfg(feature = "xz")]
+        Algorithm::Xz => Box::new(crate::XzDecoder::new()),
+        #[cfg(feature = "zlib")]
+        Algorithm::Zlib => Box::new(crate::ZlibDecoder::new()),
+        #[cfg(feature = "zstd")]
+        Algorithm::Zstd => Box::new(crate::ZstdDecoder::new()),
+    }
+}
+
+/// A decoder for an [`Algorithm`] chosen at runtime.
+pub struct AnyDecoder {
+    algorithm: Algorithm,
+    decoder: Unshared<Box<dyn DecodeV2 + Send>>,
+    input_size_hint: usize,
+}
+
+impl fmt::Debug for AnyDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyDecoder")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AnyDecoder {
+    /// Creates a decoder for `algorithm`.
+    pub fn new(algorithm: Algorithm) -> Self {
+        let decoder = decoder(algorithm);
+        Self {
+            algorithm,
+            input_size_hint: decoder.input_size_hint(),
+            decoder: Unshared::new(decoder),
+        }
+    }
+
+    /// Returns the algorithm of this decoder.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+}
+
+impl DecodeV2 for AnyDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.decoder.get_mut().reinit()
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        self.decoder.get_mut().decode(input, output)
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.decoder.get_mut().flush(output)
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.decoder.get_mut().finish(output)
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.decoder.get_mut().set_limits(limits)
+    }
+
+    fn input_size_hint(&self) -> usize {
+        self.input_size_hint
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.decoder.get_mut().reset()
+    }
+}
@@ -0,0 +1,593 @@
+//! Detection of the compression format of a stream from its first bytes.
+
+use crate::{DecodeV2, DecoderLimits};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use std::{convert::TryInto, io::Result};
+
+/// A compression format recognised by [`AutoDecoder`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// The gzip format.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// The zlib format.
+    #[cfg(feature = "zlib")]
+    Zlib,
+    /// The zstd format.
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// The xz format.
+    #[cfg(feature = "xz")]
+    Xz,
+    /// The legacy `.lzma` format.
+    #[cfg(feature = "lzma")]
+    Lzma,
+    /// The bzip2 format.
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+    /// The LZ4 frame format.
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// The legacy LZ4 format written by `lz4 -l`.
+    #[cfg(feature = "lz4")]
+    Lz4Legacy,
+    /// None of the enabled formats matched, the data is passed through unchanged.
+    Uncompressed,
+}
+
+/// Length of the `.lzma` header.
+#[cfg(feature = "lzma")]
+const LZMA_HEADER_LEN: usize = 13;
+
+/// Length of the start of a zlib stream inflated to tell it apart from text which happens to start
+/// with a valid zlib header.
+#[cfg(feature = "zlib")]
+const ZLIB_PROBE_LEN: usize = 64;
+
+/// Length of the most input looked at, which is the start of a zlib stream.
+const MAX_HEADER_LEN: usize = 64;
+
+/// Result of comparing the first bytes of a stream against one format.
+#[derive(Debug, PartialEq, Eq)]
+enum Detection {
+    Match,
+    Mismatch,
+    NeedMore,
+}
+
+/// Runs `check` once `header` has at least `len` bytes.
+fn with_len(header: &[u8], len: usize, check: impl FnOnce(&[u8]) -> bool) -> Detection {
+    if header.len() < len {
+        Detection::NeedMore
+    } else if check(&header[..len]) {
+        Detection::Match
+    } else {
+        Detection::Mismatch
+    }
+}
+
+/// Compares `header` against a fixed magic number, ruling it out as soon as a byte differs.
+fn magic(header: &[u8], magic: &[u8]) -> Detection {
+    let len = header.len().min(magic.len());
+    if header[..len] != magic[..len] {
+        Detection::Mismatch
+    } else {
+        with_len(header, magic.len(), |_| true)
+    }
+}
+
+impl Format {
+    /// Formats with a magic number come first, as the zlib and `.lzma` headers are only
+    /// recognised by the values they are allowed to contain.
+    const DETECTED: &'static [Self] = &[
+        #[cfg(feature = "gzip")]
+        Self::Gzip,
+        #[cfg(feature = "zstd")]
+        Self::Zstd,
+        #[cfg(feature = "xz")]
+        Self::Xz,
+        #[cfg(feature = "bzip2")]
+        Self::Bzip2,
+        #[cfg(feature = "lz4")]
+        Self::Lz4,
+        #[cfg(feature = "lz4")]
+        Self::Lz4Legacy,
+        #[cfg(feature = "zlib")]
+        Self::Zlib,
+        #[cfg(feature = "lzma")]
+        Self::Lzma,
+    ];
+
+    /// Formats which may be preceded by skippable frames.
+    const SKIPPABLE_FRAMES: &'static [Self] = &[
+        #[cfg(feature = "zstd")]
+        Self::Zstd,
+        #[cfg(feature = "lz4")]
+        Self::Lz4,
+    ];
+
+    /// Compares `header` against this format, `eof` is only needed for formats which don't have a
+    /// fixed length header.
+    #[cfg_attr(not(feature = "zlib"), allow(unused_variables))]
+    fn detect_one(self, header: &[u8], eof: bool) -> Detection {
+        match self {
+            // The magic number followed by the deflate method.
+            #[cfg(feature = "gzip")]
+            Self::Gzip => magic(header, &[0x1f, 0x8b, 0x08]),
+            #[cfg(feature = "zlib")]
+            Self::Zlib => detect_zlib(header, eof),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => magic(header, &[0x28, 0xb5, 0x2f, 0xfd]),
+            #[cfg(feature = "xz")]
+            Self::Xz => magic(header, &[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]),
+            #[cfg(feature = "lzma")]
+            Self::Lzma => detect_lzma(header),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => match magic(header, b"BZh") {
+                Detection::Match => {
+                    with_len(header, 4, |header| (b'1'..=b'9').contains(&header[3]))
+                }
+                detection => detection,
+            },
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => magic(header, &[0x04, 0x22, 0x4d, 0x18]),
+            #[cfg(feature = "lz4")]
+            Self::Lz4Legacy => magic(header, &[0x02, 0x21, 0x4c, 0x18]),
+            // Anything may be uncompressed, which is why it is only the fallback.
+            Self::Uncompressed => magic(header, &[]),
+        }
+    }
+
+    /// Detects the format of a stream starting with `header`, returns `None` if more bytes are
+    /// needed to decide. At the end of the stream every format which could still match is ruled
+    /// out.
+    fn detect(header: &[u8], eof: bool) -> Option<Self> {
+        if skippable_frame(header) == Detection::NeedMore && !eof {
+            return None;
+        }
+
+        for &format in Self::DETECTED {
+            match format.detect_one(header, eof) {
+                Detection::Match => return Some(format),
+                Detection::NeedMore if !eof => return None,
+                _ => {}
+            }
+        }
+
+        Some(Self::Uncompressed)
+    }
+
+    fn decoder(self) -> Codec {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip => Codec::Gzip(crate::GzipDecoder::new()),
+            #[cfg(feature = "zlib")]
+            Self::Zlib => Codec::Zlib(crate::ZlibDecoder::new()),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Codec::Zstd(crate::ZstdDecoder::new()),
+            #[cfg(feature = "xz")]
+            Self::Xz => Codec::Xz(crate::XzDecoder::new()),
+            #[cfg(feature = "lzma")]
+            Self::Lzma => Codec::Lzma(crate::LzmaDecoder::new()),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => Codec::Bzip2(crate::BzDecoder::new()),
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => Codec::Lz4(crate::Lz4Decoder::new()),
+            #[cfg(feature = "lz4")]
+            Self::Lz4Legacy => Codec::Lz4Legacy(crate::Lz4LegacyDecoder::new()),
+            Self::Uncompressed => Codec::Uncompressed,
+        }
+    }
+}
+
+/// Checks for the 8 byte header of a skippable frame, which zstd and LZ4 frames may be preceded
+/// by, followed by the length of its content.
+fn skippable_frame(header: &[u8]) -> Detection {
+    if cfg!(not(any(feature = "zstd", feature = "lz4"))) {
+        return Detection::Mismatch;
+    }
+
+    match with_len(header, 1, |header| header[0] >> 4 == 0x5) {
+        Detection::Match => match magic(&header[1..], &[0x2a, 0x4d, 0x18]) {
+            Detection::Match => with_len(header, 8, |_| true),
+            detection => detection,
+        },
+        detection => detection,
+    }
+}
+
+/// Checks the 2 byte zlib header, and that the deflate stream following it inflates without error
+/// for the first 64 bytes of the stream, or until it ends if it is shorter.
+///
+/// Six of the printable pairs of characters make valid zlib headers, so on its own the header would
+/// mistake text starting with "x^" or "HK" for zlib, but text soon fails to inflate.
+#[cfg(feature = "zlib")]
+fn detect_zlib(header: &[u8], eof: bool) -> Detection {
+    use flate2::{Decompress, FlushDecompress, Status};
+
+    let valid = with_len(header, 2, |header| {
+        let (cmf, flg) = (header[0], header[1]);
+        // Deflate with a window of at most 32 KiB, a valid check value and no preset dictionary.
+        cmf & 0x0f == 8
+            && cmf >> 4 <= 7
+            && (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0
+            && flg & 0x20 == 0
+    });
+    if valid != Detection::Match {
+        return valid;
+    }
+    if header.len() < ZLIB_PROBE_LEN && !eof {
+        return Detection::NeedMore;
+    }
+
+    let header = &header[..header.len().min(ZLIB_PROBE_LEN)];
+    let mut inflate = Decompress::new(true);
+    let mut output = [0; 4096];
+    loop {
+        let (read, written) = (inflate.total_in(), inflate.total_out());
+        match inflate.decompress(&header[read as usize..], &mut output, FlushDecompress::None) {
+            Ok(Status::StreamEnd) => return Detection::Match,
+            Err(_) => return Detection::Mismatch,
+            Ok(_) if inflate.total_in() == read && inflate.total_out() == written => break,
+            Ok(_) => {}
+        }
+    }
+
+    // A stream cut short at the end of the input is left to pass through as uncompressed data.
+    with_len(header, ZLIB_PROBE_LEN, |_| true)
+}
+
+/// Checks the 13 byte `.lzma` header the way liblzma does, which is strict enough to rule out
+/// most other data.
+#[cfg(feature = "lzma")]
+fn detect_lzma(header: &[u8]) -> Detection {
+    // The literal context, literal position and position bits.
+    let props = with_len(header, 1, |header| {
+        let props = header[0];
+        props <= (4 * 5 + 4) * 9 + 8 && props % 9 + props / 9 % 5 <= 4
+    });
+    // A dictionary size of 2^n or 2^n + 2^(n-1), or unlimited.
+    let dict_size = with_len(header, 5, |header| {
+        let dict_size = u32::from_le_bytes(header[1..5].try_into().unwrap());
+        let mut rounded = dict_size.wrapping_sub(1);
+        rounded |= rounded >> 2;
+        rounded |= rounded >> 3;
+        rounded |= rounded >> 4;
+        rounded |= rounded >> 8;
+        rounded |= rounded >> 16;
+        dict_size == u32::MAX || rounded.wrapping_add(1) == dict_size
+    });
+    // An unknown or plausible uncompressed size.
+    let size = with_len(header, LZMA_HEADER_LEN, |header| {
+        let size = u64::from_le_bytes(header[5..13].try_into().unwrap());
+        size == u64::MAX || size < 1 << 38
+    });
+
+    for detection in [props, dict_size, size] {
+        if detection != Detection::Match {
+            return detection;
+        }
+    }
+    Detection::Match
+}
+
+#[derive(Debug)]
+enum Codec {
+    #[cfg(feature = "gzip")]
+    Gzip(crate::GzipDecoder),
+    #[cfg(feature = "zlib")]
+    Zlib(crate::ZlibDecoder),
+    #[cfg(feature = "zstd")]
+    Zstd(crate::ZstdDecoder),
+    #[cfg(feature = "xz")]
+    Xz(crate::XzDecoder),
+    #[cfg(feature = "lzma")]
+    Lzma(crate::LzmaDecoder),
+    #[cfg(feature = "bzip2")]
+    Bzip2(crate::BzDecoder),
+    #[cfg(feature = "lz4")]
+    Lz4(crate::Lz4Decoder),
+    #[cfg(feature = "lz4")]
+    Lz4Legacy(crate::Lz4LegacyDecoder),
+    Uncompressed,
+}
+
+impl Codec {
+    fn decoder(&mut self) -> Option<&mut dyn DecodeV2> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip(decoder) => Some(decoder),
+            #[cfg(feature = "zlib")]
+            Self::Zlib(decoder) => Some(decoder),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(decoder) => Some(decoder),
+            #[cfg(feature = "xz")]
+            Self::Xz(decoder) => Some(decoder),
+            #[cfg(feature = "lzma")]
+            Self::Lzma(decoder) => Some(decoder),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(decoder) => Some(decoder),
+            #[cfg(feature = "lz4")]
+            Self::Lz4(decoder) => Some(decoder),
+            #[cfg(feature = "lz4")]
+            Self::Lz4Legacy(decoder) => Some(decoder),
+            Self::Uncompressed => None,
+        }
+    }
+}
+
+/// A decoder which detects the compression format from the first bytes of its input, and then
+/// decodes it with the decoder for that format.
+///
+/// Input which doesn't start with the header of any enabled format is passed through unchanged.
+/// Skippable frames at the start, which zstd and LZ4 frames may be preceded by, are held back when
+/// either of those formats is enabled and the format is detected from what follows them. They are
+/// only skipped if a zstd or LZ4 frame follows, otherwise they are passed through along with the
+/// rest of the input. Zlib and `.lzma` streams don't have a magic number, so they are recognised
+/// from the values their header is allowed to contain, and are only tried after every other
+/// format. The first 64 bytes of a zlib stream are inflated as well to rule out text.
+///
+/// Up to 64 bytes of input, and any skippable frames, are held back until the format is known, so
+/// a short uncompressed input is only passed through at the end of the stream.
+#[derive(Debug)]
+pub struct AutoDecoder {
+    header: PartialBuffer<[u8; MAX_HEADER_LEN]>,
+    /// The number of header bytes already passed to the decoder once the format is known.
+    replayed: usize,
+    /// The length of the rest of a skippable frame being skipped.
+    skip: u32,
+    /// The skippable frames read during detection, passed through if no zstd or LZ4 frame follows.
+    skipped: Vec<u8>,
+    state: Option<(Format, Codec)>,
+    /// The codec of the stream before a reset, reused if the next stream is in the same format.
+    previous: Option<(Format, Codec)>,
+    limits: DecoderLimits,
+}
+
+impl Default for AutoDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AutoDecoder {
+    pub fn new() -> Self {
+        Self {
+            header: PartialBuffer::new([0; MAX_HEADER_LEN]),
+            replayed: 0,
+            skip: 0,
+            skipped: Vec::new(),
+            state: None,
+            previous: None,
+            limits: DecoderLimits::new(),
+        }
+    }
+
+    /// Returns the detected format, or `None` if not enough input has been read to detect it.
+    pub fn format(&self) -> Option<Format> {
+        self.state.as_ref().map(|(format, _)| *format)
+    }
+
+    /// Reads input into the header until the format is known, returns whether it is.
+    fn detect(&mut self, input: &mut PartialBuffer<&[u8]>, eof: bool) -> Result<bool> {
+        while self.state.is_none() {
+            if self.skip > 0 {
+                let len = input.unwritten().len().min(self.skip as usize);
+                self.skipped.extend_from_slice(&input.unwritten()[..len]);
+                input.advance(len);
+                self.skip -= len as u32;
+                if self.skip > 0 && !eof {
+                    return Ok(false);
+                }
+                // A truncated skippable frame is left to pass through as uncompressed data.
+                self.skip = 0;
+            }
+
+            let header = self.header.written();
+            if skippable_frame(header) == Detection::Match {
+                self.skip = u32::from_le_bytes(header[4..8].try_into().unwrap());
+                #[cfg(any(feature = "zstd", feature = "lz4"))]
+                self.limits
+                    .check(self.skipped.len() as u64 + u64::from(self.skip) + 8)?;
+                self.skipped.extend_from_slice(header);
+                self.header.reset();
+            } else if let Some(mut format) = Format::detect(header, eof) {
+                if !self.skipped.is_empty() {
+                    if Format::SKIPPABLE_FRAMES.contains(&format) {
+                        self.skipped = Vec::new();
+                    } else {
+                        format = Format::Uncompressed;
+                        self.skipped.extend_from_slice(self.header.written());
+                    }
+                }
+
+                let mut codec = match self.previous.take() {
+                    Some((previous, codec)) if previous == format => codec,
+                    _ => format.decoder(),
+                };
+                if let Some(decoder) = codec.decoder() {
+                    decoder.set_limits(self.limits)?;
+                }
+                self.state = Some((format, codec));
+            } else if let Some(&byte) = input.unwritten().first() {
+                self.header.unwritten_mut()[0] = byte;
+                self.header.advance(1);
+                input.advance(1);
+            } else {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Passes the input held back during detection to the decoder, returns whether all of it has
+    /// been passed and whether the decoder reached the end of the stream.
+    fn replay(&mut self, output: &mut WriteBuffer<'_>) -> Result<(bool, bool)> {
+        // The skippable frames are only kept for uncompressed data, followed by the header.
+        let held = if self.skipped.is_empty() {
+            self.header.written()
+        } else {
+            &self.skipped[..]
+        };
+        if self.replayed == held.len() {
+            return Ok((true, false));
+        }
+
+        let mut header = PartialBuffer::new(held);
+        header.advance(self.replayed);
+
+        let done = match self.state.as_mut().and_then(|(_, codec)| codec.decoder()) {
+            Some(decoder) => decoder.decode(&mut header, output)?,
+            None => {
+                output.copy_unwritten_from(&mut header);
+                false
+            }
+        };
+
+        self.replayed = header.written_len();
+        Ok((header.unwritten().is_empty(), done))
+    }
+}
+
+impl DecodeV2 for AutoDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        match self.state.as_mut().and_then(|(_, codec)| codec.decoder()) {
+            Some(decoder) => decoder.reinit(),
+            None => Ok(()),
+        }
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        if !self.detect(input, false)? {
+            return Ok(false);
+        }
+
+        let (replayed, done) = self.replay(output)?;
+        // Decoders may fail when they are given no input to make progress with.
+        if !replayed || done || input.unwritten().is_empty() {
+            return Ok(done);
+        }
+
+        match self.state.as_mut().and_then(|(_, codec)| codec.decoder()) {
+            Some(decoder) => decoder.decode(input, output),
+            None => {
+                output.copy_unwritten_from(input);
+                Ok(false)
+            }
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        match self.state.as_mut().and_then(|(_, codec)| codec.decoder()) {
+            Some(decoder) => decoder.flush(output),
+            None => Ok(true),
+        }
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.detect(&mut PartialBuffer::new(&[][..]), true)?;
+
+        let (replayed, _) = self.replay(output)?;
+        if !replayed {
+            return Ok(false);
+        }
+
+        match self.state.as_mut().and_then(|(_, codec)| codec.decoder()) {
+            Some(decoder) => decoder.finish(output),
+            None => Ok(true),
+        }
+    }
+
+    /// The limits apply to the decoder of the detected format, uncompressed data needs no memory.
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.limits = limits;
+        match self.state.as_mut().and_then(|(_, codec)| codec.decoder()) {
+            Some(decoder) => decoder.set_limits(limits),
+            None => Ok(()),
+        }
+    }
+
+    /// Detects the format of the new stream again, keeping the decoder of the previous one for
+    /// if it is in the same format.
+    fn reset(&mut self) -> Result<()> {
+        if let Some((format, mut codec)) = self.state.take() {
+            if let Some(decoder) = codec.decoder() {
+                decoder.reset()?;
+            }
+            self.previous = Some((format, codec));
+        }
+
+        self.header.reset();
+        self.replayed = 0;
+        self.skip = 0;
+        self.skipped = Vec::new();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+
+    #[test]
+    fn short_header_waits_until_eof() {
+        assert_eq!(Format::detect(b"", false), None);
+        assert_eq!(Format::detect(b"", true), Some(Format::Uncompressed));
+        assert_eq!(Format::detect(b"text", true), Some(Format::Uncompressed));
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn zstd_and_skippable_frame() {
+        assert_eq!(Format::detect(&[0x28, 0xb5], false), None);
+        assert_eq!(
+            Format::detect(&[0x28, 0xb5, 0x2f, 0xfd], false),
+            Some(Format::Zstd)
+        );
+        assert_eq!(Format::detect(&[0x5e, 0x2a, 0x4d, 0x18, 0], false), None);
+    }
+
+    #[test]
+    #[cfg(feature = "zlib")]
+    fn zlib_header() {
+        // The stream is inflated for the first 64 bytes before it is recognised, or until it ends.
+        assert_eq!(Format::detect(&[0x78, 0x9c], false), None);
+        let empty = [0x78, 0x9c, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01];
+        assert_eq!(Format::detect(&empty, true), Some(Format::Zlib));
+        assert_eq!(
+            Format::detect(&empty[..6], true),
+            Some(Format::Uncompressed)
+        );
+        // A bad check value, and a preset dictionary.
+        assert_eq!(Format::detect(b"x\x9d", true), Some(Format::Uncompressed));
+        assert_eq!(
+            Format::detect(&[0x78, 0xbb], true),
+            Some(Format::Uncompressed)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lzma")]
+    fn lzma_header() {
+        let header = [
+            0x5d, 0, 0, 0x80, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ];
+        assert_eq!(Format::detect(&header[..12], false), None);
+        assert_eq!(Format::detect(&header, false), Some(Format::Lzma));
+
+        // A dictionary size which is not 2^n or 2^n + 2^(n-1).
+        let header = [
+            0x5d, 0, 0, 0x70, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ];
+        assert_eq!(
+            Format::detect(&header[..5], false),
+            Some(Format::Uncompressed)
+        );
+    }
+}
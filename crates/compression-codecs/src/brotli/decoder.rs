@@ -1,19 +1,40 @@
-use crate::DecodeV2;
+use crate::{limits::HeaderCheck, DecodeV2, DecoderLimits};
 use brotli::{enc::StandardAlloc, BrotliDecompressStream, BrotliResult};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use std::{fmt, io};
 
 type BrotliState = brotli::BrotliState<StandardAlloc, StandardAlloc, StandardAlloc>;
 
+/// Returns the size of the window announced by the first bytes of a brotli stream.
+fn window_size(header: &[u8]) -> Option<u64> {
+    let &byte = header.first()?;
+    let bits = if byte & 1 == 0 {
+        16
+    } else if (byte >> 1) & 7 != 0 {
+        17 + ((byte >> 1) & 7)
+    } else {
+        match (byte >> 4) & 7 {
+            0 => 17,
+            // The large window extension has the number of bits in the next byte.
+            1 => header.get(1)? & 0x3f,
+            n => 8 + n,
+        }
+    };
+
+    Some((1 << bits.min(62)) - 16)
+}
+
 pub struct BrotliDecoder {
     // `BrotliState` is very large (over 2kb) which is why we're boxing it.
     state: Box<BrotliState>,
+    header: HeaderCheck<2>,
 }
 
 impl Default for BrotliDecoder {
     fn default() -> Self {
         Self {
             state: Box::new(Self::new_brotli_state()),
+            header: HeaderCheck::new(),
         }
     }
 }
@@ -32,7 +53,7 @@ impl BrotliDecoder {
     }
 
     fn decode(
-        &mut self,
+        state: &mut BrotliState,
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> io::Result<BrotliResult> {
@@ -50,7 +71,7 @@ impl BrotliDecoder {
             &mut output_len,
             out_buf,
             &mut 0,
-            &mut self.state,
+            state,
         ) {
             BrotliResult::ResultFailure => return Err(io::Error::other("brotli error")),
             status => status,
@@ -61,11 +82,25 @@ impl BrotliDecoder {
 
         Ok(status)
     }
+
+    /// Returns whether the end of the stream has been reached.
+    fn decode_stream(
+        state: &mut BrotliState,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> io::Result<bool> {
+        match Self::decode(state, input, output)? {
+            BrotliResult::ResultSuccess => Ok(true),
+            BrotliResult::NeedsMoreOutput | BrotliResult::NeedsMoreInput => Ok(false),
+            BrotliResult::ResultFailure => unreachable!(),
+        }
+    }
 }
 
 impl DecodeV2 for BrotliDecoder {
     fn reinit(&mut self) -> io::Result<()> {
         *self.state = Self::new_brotli_state();
+        self.header.reset();
         Ok(())
     }
 
@@ -74,15 +109,14 @@ impl DecodeV2 for BrotliDecoder {
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> io::Result<bool> {
-        match self.decode(input, output)? {
-            BrotliResult::ResultSuccess => Ok(true),
-            BrotliResult::NeedsMoreOutput | BrotliResult::NeedsMoreInput => Ok(false),
-            BrotliResult::ResultFailure => unreachable!(),
-        }
+        let Self { state, header } = self;
+        header.decode(input, output, window_size, |input, output| {
+            Self::decode_stream(state, input, output)
+        })
     }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
-        match self.decode(&mut PartialBuffer::new(&[][..]), output)? {
+        match Self::decode(&mut self.state, &mut PartialBuffer::new(&[][..]), output)? {
             BrotliResult::ResultSuccess | BrotliResult::NeedsMoreInput => Ok(true),
             BrotliResult::NeedsMoreOutput => Ok(false),
             BrotliResult::ResultFailure => unreachable!(),
@@ -90,7 +124,14 @@ impl DecodeV2 for BrotliDecoder {
     }
 
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
-        match self.decode(&mut PartialBuffer::new(&[][..]), output)? {
+        let Self { state, header } = self;
+        if !header.finish(output, |input, output| {
+            Self::decode_stream(state, input, output)
+        })? {
+            return Ok(false);
+        }
+
+        match Self::decode(&mut self.state, &mut PartialBuffer::new(&[][..]), output)? {
             BrotliResult::ResultSuccess => Ok(true),
             BrotliResult::NeedsMoreOutput => Ok(false),
             BrotliResult::NeedsMoreInput => Err(io::Error::new(
@@ -100,6 +141,11 @@ impl DecodeV2 for BrotliDecoder {
             BrotliResult::ResultFailure => unreachable!(),
         }
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> io::Result<()> {
+        self.header.set_limits(limits);
+        Ok(())
+    }
 }
 
 impl fmt::Debug for BrotliDecoder {
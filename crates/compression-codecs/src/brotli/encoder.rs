@@ -13,10 +13,15 @@ pub struct BrotliEncoder {
 
 impl BrotliEncoder {
     pub fn new(params: EncoderParams) -> Self {
-        let params = BrotliEncoderParams::from(params);
+        Self {
+            state: Self::new_state(BrotliEncoderParams::from(params)),
+        }
+    }
+
+    fn new_state(params: BrotliEncoderParams) -> BrotliEncoderStateStruct<StandardAlloc> {
         let mut state = BrotliEncoderStateStruct::new(StandardAlloc::default());
         state.params = params;
-        Self { state }
+        state
     }
 
     fn encode(
@@ -84,6 +89,13 @@ impl EncodeV2 for BrotliEncoder {
 
         Ok(self.state.is_finished())
     }
+
+    fn reinit(&mut self) -> io::Result<()> {
+        // The brotli crate has no way to reset an encoder state, so a new one is set up with the
+        // same parameters. It only allocates its buffers once it is first used.
+        self.state = Self::new_state(self.state.params.clone());
+        Ok(())
+    }
 }
 
 impl fmt::Debug for BrotliEncoder {
@@ -1,11 +1,21 @@
-use crate::DecodeV2;
+use crate::{limits::HeaderCheck, DecodeV2, DecoderLimits};
 use bzip2::{Decompress, Status};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use std::{fmt, io};
 
+/// Returns the memory needed to decode a stream starting with `header`.
+fn block_memory(header: &[u8]) -> Option<u64> {
+    match header {
+        // The decoder uses 4 bytes for each byte of the blocks, which are up to `level` * 100 kB.
+        [b'B', b'Z', b'h', level @ b'1'..=b'9'] => Some(u64::from(level - b'0') * 400_000),
+        _ => None,
+    }
+}
+
 pub struct BzDecoder {
     decompress: Decompress,
     stream_ended: bool,
+    header: HeaderCheck<4>,
 }
 
 impl fmt::Debug for BzDecoder {
@@ -24,6 +34,7 @@ impl Default for BzDecoder {
         Self {
             decompress: Decompress::new(false),
             stream_ended: false,
+            header: HeaderCheck::new(),
         }
     }
 }
@@ -34,47 +45,39 @@ impl BzDecoder {
     }
 
     fn decode(
-        &mut self,
+        decompress: &mut Decompress,
+        stream_ended: &mut bool,
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> io::Result<Status> {
-        let prior_in = self.decompress.total_in();
-        let prior_out = self.decompress.total_out();
+        let prior_in = decompress.total_in();
+        let prior_out = decompress.total_out();
 
-        let status = self
-            .decompress
+        let status = decompress
             // Safety: We **trust** bzip2 to only write initialized data to it
             .decompress_uninit(input.unwritten(), unsafe { output.unwritten_mut() })
             .map_err(io::Error::other)?;
 
-        input.advance((self.decompress.total_in() - prior_in) as usize);
+        input.advance((decompress.total_in() - prior_in) as usize);
         // Safety: We **trust** bzip2 to write bytes properly
-        unsafe {
-            output.assume_init_and_advance((self.decompress.total_out() - prior_out) as usize)
-        };
+        unsafe { output.assume_init_and_advance((decompress.total_out() - prior_out) as usize) };
 
         // Track when stream has properly ended
         if status == Status::StreamEnd {
-            self.stream_ended = true;
+            *stream_ended = true;
         }
 
         Ok(status)
     }
-}
 
-impl DecodeV2 for BzDecoder {
-    fn reinit(&mut self) -> io::Result<()> {
-        self.decompress = Decompress::new(false);
-        self.stream_ended = false;
-        Ok(())
-    }
-
-    fn decode(
-        &mut self,
+    /// Returns whether the end of the stream has been reached.
+    fn decode_stream(
+        decompress: &mut Decompress,
+        stream_ended: &mut bool,
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> io::Result<bool> {
-        match self.decode(input, output)? {
+        match Self::decode(decompress, stream_ended, input, output)? {
             // Decompression went fine, nothing much to report.
             Status::Ok => Ok(false),
 
@@ -95,13 +98,52 @@ impl DecodeV2 for BzDecoder {
             Status::MemNeeded => Err(io::ErrorKind::OutOfMemory.into()),
         }
     }
+}
+
+impl DecodeV2 for BzDecoder {
+    fn reinit(&mut self) -> io::Result<()> {
+        self.decompress = Decompress::new(false);
+        self.stream_ended = false;
+        self.header.reset();
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> io::Result<bool> {
+        let Self {
+            decompress,
+            stream_ended,
+            header,
+        } = self;
+        header.decode(input, output, block_memory, |input, output| {
+            Self::decode_stream(decompress, stream_ended, input, output)
+        })
+    }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
-        self.decode(&mut PartialBuffer::new(&[][..]), output)?;
+        let Self {
+            decompress,
+            stream_ended,
+            ..
+        } = self;
+        Self::decode(
+            decompress,
+            stream_ended,
+            &mut PartialBuffer::new(&[][..]),
+            output,
+        )?;
 
         loop {
             let old_len = output.written_len();
-            self.decode(&mut PartialBuffer::new(&[][..]), output)?;
+            Self::decode(
+                decompress,
+                stream_ended,
+                &mut PartialBuffer::new(&[][..]),
+                output,
+            )?;
             if output.written_len() == old_len {
                 break;
             }
@@ -110,7 +152,18 @@ impl DecodeV2 for BzDecoder {
         Ok(!output.has_no_spare_space())
     }
 
-    fn finish(&mut self, _output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        let Self {
+            decompress,
+            stream_ended,
+            header,
+        } = self;
+        if !header.finish(output, |input, output| {
+            Self::decode_stream(decompress, stream_ended, input, output)
+        })? {
+            return Ok(false);
+        }
+
         if self.stream_ended {
             Ok(true)
         } else {
@@ -120,4 +173,9 @@ impl DecodeV2 for BzDecoder {
             ))
         }
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> io::Result<()> {
+        self.header.set_limits(limits);
+        Ok(())
+    }
 }
@@ -1,10 +1,12 @@
-use crate::{bzip2::params::Bzip2EncoderParams, EncodeV2};
+use crate::{bzip2::params::Bzip2EncoderParams, EncodeV2, FlushMode};
 use bzip2::{Action, Compress, Compression, Status};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use std::{fmt, io};
 
 pub struct BzEncoder {
     compress: Compress,
+    level: Compression,
+    work_factor: u32,
 }
 
 impl fmt::Debug for BzEncoder {
@@ -40,9 +42,11 @@ impl BzEncoder {
     /// Allowable values range from 0 to 250 inclusive. 0 is a special case,
     /// equivalent to using the default value of 30.
     pub fn new(params: Bzip2EncoderParams, work_factor: u32) -> Self {
-        let params = Compression::from(params);
+        let level = Compression::from(params);
         Self {
-            compress: Compress::new(params, work_factor),
+            compress: Compress::new(level, work_factor),
+            level,
+            work_factor,
         }
     }
 
@@ -142,4 +146,15 @@ impl EncodeV2 for BzEncoder {
             Status::MemNeeded => Err(io::ErrorKind::OutOfMemory.into()),
         }
     }
+
+    fn set_flush_mode(&mut self, _mode: FlushMode) -> io::Result<()> {
+        // Flushing ends the block, and blocks are compressed independently.
+        Ok(())
+    }
+
+    fn reinit(&mut self) -> io::Result<()> {
+        // libbz2 has no way to reset a stream, so a new one is allocated.
+        self.compress = Compress::new(self.level, self.work_factor);
+        Ok(())
+    }
 }
@@ -1,8 +1,11 @@
-use crate::DecodeV2;
+use crate::{DecodeV2, DecoderLimits};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use deflate64::InflaterManaged;
 use std::io::{Error, ErrorKind, Result};
 
+/// Size of the window of the deflate64 format, which every stream needs.
+const WINDOW_SIZE: u64 = 64 * 1024;
+
 #[derive(Debug)]
 pub struct Deflate64Decoder {
     inflater: Box<InflaterManaged>,
@@ -74,4 +77,8 @@ impl DecodeV2 for Deflate64Decoder {
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
         self.decode(&mut PartialBuffer::new(&[]), output)
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        limits.check(WINDOW_SIZE)
+    }
 }
@@ -1,8 +1,11 @@
-use crate::DecodeV2;
+use crate::{DecodeV2, DecoderLimits};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use flate2::{Decompress, FlushDecompress, Status};
 use std::io;
 
+/// Size of the window of the deflate format, which every stream needs.
+const WINDOW_SIZE: u64 = 32 * 1024;
+
 #[derive(Debug)]
 pub struct FlateDecoder {
     zlib_header: bool,
@@ -92,4 +95,8 @@ impl DecodeV2 for FlateDecoder {
             Status::BufError => Err(io::Error::other("unexpected BufError")),
         }
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> io::Result<()> {
+        limits.check(WINDOW_SIZE)
+    }
 }
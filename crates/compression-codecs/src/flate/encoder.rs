@@ -1,4 +1,4 @@
-use crate::{flate::params::FlateEncoderParams, EncodeV2};
+use crate::{flate::params::FlateEncoderParams, EncodeV2, FlushMode};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use flate2::{Compress, FlushCompress, Status};
 use std::io;
@@ -7,6 +7,7 @@ use std::io;
 pub struct FlateEncoder {
     compress: Compress,
     flushed: bool,
+    full_flush: bool,
 }
 
 impl FlateEncoder {
@@ -15,6 +16,7 @@ impl FlateEncoder {
         Self {
             compress: Compress::new(level, zlib_header),
             flushed: true,
+            full_flush: false,
         }
     }
 
@@ -65,11 +67,12 @@ impl EncodeV2 for FlateEncoder {
             return Ok(true);
         }
 
-        self.encode(
-            &mut PartialBuffer::new(&[][..]),
-            output,
-            FlushCompress::Sync,
-        )?;
+        let flush = if self.full_flush {
+            FlushCompress::Full
+        } else {
+            FlushCompress::Sync
+        };
+        self.encode(&mut PartialBuffer::new(&[][..]), output, flush)?;
 
         loop {
             let old_len = output.written_len();
@@ -100,4 +103,19 @@ impl EncodeV2 for FlateEncoder {
             Status::BufError => Err(io::Error::other("unexpected BufError")),
         }
     }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> io::Result<()> {
+        self.full_flush = match mode {
+            FlushMode::Sync => false,
+            FlushMode::Full => true,
+            mode => return Err(mode.unsupported()),
+        };
+        Ok(())
+    }
+
+    fn reinit(&mut self) -> io::Result<()> {
+        self.compress.reset();
+        self.flushed = true;
+        Ok(())
+    }
 }
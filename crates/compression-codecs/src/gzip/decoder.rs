@@ -1,5 +1,5 @@
 use super::header;
-use crate::{DecodeV2, FlateDecoder};
+use crate::{DecodeV2, DecoderLimits, FlateDecoder};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use flate2::Crc;
 use std::io::{Error, ErrorKind, Result};
@@ -157,4 +157,8 @@ impl DecodeV2 for GzipDecoder {
             Err(Error::from(ErrorKind::UnexpectedEof))
         }
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.inner.set_limits(limits)
+    }
 }
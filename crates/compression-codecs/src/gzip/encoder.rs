@@ -1,4 +1,4 @@
-use crate::{flate::params::FlateEncoderParams, EncodeV2, FlateEncoder};
+use crate::{flate::params::FlateEncoderParams, EncodeV2, FlateEncoder, FlushMode};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use flate2::{Compression, Crc};
 use std::io;
@@ -11,10 +11,18 @@ enum State {
     Done,
 }
 
+#[derive(Debug)]
+enum Deflater {
+    Single(FlateEncoder),
+    #[cfg(feature = "gzip-parallel")]
+    Parallel(super::parallel::ParallelDeflateEncoder),
+}
+
 #[derive(Debug)]
 pub struct GzipEncoder {
-    inner: FlateEncoder,
+    inner: Deflater,
     crc: Crc,
+    header: [u8; 10],
     state: State,
 }
 
@@ -32,18 +40,60 @@ fn header(level: Compression) -> [u8; 10] {
 
 impl GzipEncoder {
     pub fn new(level: FlateEncoderParams) -> Self {
+        let header = header(Compression::from(level.clone()));
         Self {
-            inner: FlateEncoder::new(level.clone(), false),
+            inner: Deflater::Single(FlateEncoder::new(level, false)),
             crc: Crc::new(),
-            state: State::Header(header(Compression::from(level)).into()),
+            header,
+            state: State::Header(header.into()),
         }
     }
 
+    /// Creates an encoder which deflates chunks of the input on `threads` worker threads, pigz
+    /// style, and joins them into a single gzip member.
+    ///
+    /// The encoder doesn't wait for its workers: while it can make no progress until one of them
+    /// is done, [`encode`](EncodeV2::encode), [`flush`](EncodeV2::flush) and
+    /// [`finish`](EncodeV2::finish) return a [`WouldBlock`](io::ErrorKind::WouldBlock) error, and
+    /// the waker given to [`register_waker`](EncodeV2::register_waker) is woken once it can
+    /// continue.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the worker threads could not be spawned.
+    #[cfg(feature = "gzip-parallel")]
+    pub fn parallel(level: FlateEncoderParams, threads: std::num::NonZeroU32) -> io::Result<Self> {
+        let level = Compression::from(level);
+        let header = header(level);
+        Ok(Self {
+            inner: Deflater::Parallel(super::parallel::ParallelDeflateEncoder::new(
+                level, threads,
+            )?),
+            crc: Crc::new(),
+            header,
+            state: State::Header(header.into()),
+        })
+    }
+
+    /// Whether `err` is the [`WouldBlock`](io::ErrorKind::WouldBlock) error of the parallel
+    /// deflater in a call which already made progress, which must then return what it has done
+    /// instead, as that error may only be returned by calls which made no progress.
+    #[cfg(feature = "gzip-parallel")]
+    fn blocked_after_progress(err: &io::Error, progressed: bool) -> bool {
+        err.kind() == io::ErrorKind::WouldBlock && progressed
+    }
+
     fn footer(&mut self) -> [u8; 8] {
+        let crc = match &self.inner {
+            Deflater::Single(_) => &self.crc,
+            #[cfg(feature = "gzip-parallel")]
+            Deflater::Parallel(inner) => inner.crc(),
+        };
+
         let mut output = [0; 8];
 
-        output[..4].copy_from_slice(&self.crc.sum().to_le_bytes());
-        output[4..].copy_from_slice(&self.crc.amount().to_le_bytes());
+        output[..4].copy_from_slice(&crc.sum().to_le_bytes());
+        output[4..].copy_from_slice(&crc.amount().to_le_bytes());
 
         output
     }
@@ -55,6 +105,9 @@ impl EncodeV2 for GzipEncoder {
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> io::Result<()> {
+        #[cfg(feature = "gzip-parallel")]
+        let (read, written) = (input.written_len(), output.written_len());
+
         loop {
             match &mut self.state {
                 State::Header(header) => {
@@ -65,11 +118,25 @@ impl EncodeV2 for GzipEncoder {
                     }
                 }
 
-                State::Encoding => {
-                    let prior_written = input.written().len();
-                    self.inner.encode(input, output)?;
-                    self.crc.update(&input.written()[prior_written..]);
-                }
+                State::Encoding => match &mut self.inner {
+                    Deflater::Single(inner) => {
+                        let prior_written = input.written().len();
+                        inner.encode(input, output)?;
+                        self.crc.update(&input.written()[prior_written..]);
+                    }
+                    #[cfg(feature = "gzip-parallel")]
+                    Deflater::Parallel(inner) => match inner.encode(input, output) {
+                        Err(err)
+                            if Self::blocked_after_progress(
+                                &err,
+                                input.written_len() > read || output.written_len() > written,
+                            ) =>
+                        {
+                            return Ok(())
+                        }
+                        res => res?,
+                    },
+                },
 
                 State::Footer(_) | State::Done => {
                     return Err(io::Error::other("encode after complete"));
@@ -83,6 +150,9 @@ impl EncodeV2 for GzipEncoder {
     }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        #[cfg(feature = "gzip-parallel")]
+        let written = output.written_len();
+
         loop {
             let done = match &mut self.state {
                 State::Header(header) => {
@@ -94,7 +164,21 @@ impl EncodeV2 for GzipEncoder {
                     false
                 }
 
-                State::Encoding => self.inner.flush(output)?,
+                State::Encoding => match &mut self.inner {
+                    Deflater::Single(inner) => inner.flush(output)?,
+                    #[cfg(feature = "gzip-parallel")]
+                    Deflater::Parallel(inner) => match inner.flush(output) {
+                        Err(err)
+                            if Self::blocked_after_progress(
+                                &err,
+                                output.written_len() > written,
+                            ) =>
+                        {
+                            return Ok(false)
+                        }
+                        res => res?,
+                    },
+                },
 
                 State::Footer(footer) => {
                     output.copy_unwritten_from(&mut *footer);
@@ -121,6 +205,9 @@ impl EncodeV2 for GzipEncoder {
     }
 
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        #[cfg(feature = "gzip-parallel")]
+        let written = output.written_len();
+
         loop {
             match &mut self.state {
                 State::Header(header) => {
@@ -132,7 +219,23 @@ impl EncodeV2 for GzipEncoder {
                 }
 
                 State::Encoding => {
-                    if self.inner.finish(output)? {
+                    let done = match &mut self.inner {
+                        Deflater::Single(inner) => inner.finish(output)?,
+                        #[cfg(feature = "gzip-parallel")]
+                        Deflater::Parallel(inner) => match inner.finish(output) {
+                            Err(err)
+                                if Self::blocked_after_progress(
+                                    &err,
+                                    output.written_len() > written,
+                                ) =>
+                            {
+                                return Ok(false)
+                            }
+                            res => res?,
+                        },
+                    };
+
+                    if done {
                         self.state = State::Footer(self.footer().into());
                     }
                 }
@@ -157,4 +260,30 @@ impl EncodeV2 for GzipEncoder {
             }
         }
     }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> io::Result<()> {
+        match &mut self.inner {
+            Deflater::Single(inner) => inner.set_flush_mode(mode),
+            #[cfg(feature = "gzip-parallel")]
+            Deflater::Parallel(inner) => inner.set_flush_mode(mode),
+        }
+    }
+
+    #[cfg(feature = "gzip-parallel")]
+    fn register_waker(&mut self, waker: &std::task::Waker) {
+        if let Deflater::Parallel(inner) = &self.inner {
+            inner.register_waker(waker);
+        }
+    }
+
+    fn reinit(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            Deflater::Single(inner) => inner.reinit()?,
+            #[cfg(feature = "gzip-parallel")]
+            Deflater::Parallel(inner) => inner.reinit(),
+        }
+        self.crc.reset();
+        self.state = State::Header(self.header.into());
+        Ok(())
+    }
 }
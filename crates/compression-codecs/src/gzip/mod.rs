@@ -1,5 +1,7 @@
 mod decoder;
 mod encoder;
 mod header;
+#[cfg(feature = "gzip-parallel")]
+mod parallel;
 
 pub use self::{decoder::GzipDecoder, encoder::GzipEncoder};
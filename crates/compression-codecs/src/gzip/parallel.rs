@@ -0,0 +1,298 @@
+//! pigz-style parallel deflate: input is split into chunks which are compressed independently on a
+//! pool of worker threads, each primed with the last 32 KiB of the preceding input so that
+//! back-references can cross chunk boundaries. Every chunk but the last ends with a sync flush so
+//! the compressed chunks can simply be concatenated into one raw deflate stream.
+//!
+//! The encoder never waits for the workers, once it has as many chunks in flight as it allows it
+//! returns a [`would_block`] error until one of them is done.
+
+use crate::{
+    pool::{would_block, Ordered, WorkerPool},
+    FlushMode,
+};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use flate2::{Compress, Compression, Crc, FlushCompress, Status};
+use std::{fmt, io, num::NonZeroU32, task::Waker};
+
+/// Size of the deflate window, the maximum distance a back-reference can reach.
+const WINDOW_SIZE: usize = 32 * 1024;
+
+/// Amount of input compressed by a single job, matches the pigz default block size.
+const CHUNK_SIZE: usize = 128 * 1024;
+
+struct Job {
+    level: Compression,
+    dictionary: Vec<u8>,
+    data: Vec<u8>,
+    last: bool,
+}
+
+struct Chunk {
+    compressed: Vec<u8>,
+    crc: Crc,
+}
+
+fn compress_chunk(job: &Job) -> io::Result<Chunk> {
+    let mut compress = Compress::new(job.level, false);
+    let mut compressed = Vec::with_capacity(job.data.len() / 2 + 64);
+
+    if !job.dictionary.is_empty() {
+        // Not every flate2 backend supports `set_dictionary`, so instead run the dictionary
+        // through the compressor and throw away the output. The sync flush leaves the compressor
+        // byte aligned at a block boundary with the dictionary still in its window, so everything
+        // produced after it can be appended to the output of the previous chunk.
+        run(
+            &mut compress,
+            &job.dictionary,
+            &mut compressed,
+            FlushCompress::Sync,
+        )?;
+        compressed.clear();
+    }
+
+    let flush = if job.last {
+        FlushCompress::Finish
+    } else {
+        FlushCompress::Sync
+    };
+    run(&mut compress, &job.data, &mut compressed, flush)?;
+
+    let mut crc = Crc::new();
+    crc.update(&job.data);
+
+    Ok(Chunk { compressed, crc })
+}
+
+/// Compresses all of `input` into `output` and completes the given `flush`.
+fn run(
+    compress: &mut Compress,
+    mut input: &[u8],
+    output: &mut Vec<u8>,
+    flush: FlushCompress,
+) -> io::Result<()> {
+    loop {
+        if output.capacity() - output.len() < 1024 {
+            output.reserve(input.len().max(4096));
+        }
+
+        let prior_in = compress.total_in();
+        let status = compress.compress_vec(input, output, flush)?;
+        input = &input[(compress.total_in() - prior_in) as usize..];
+
+        match status {
+            Status::StreamEnd => return Ok(()),
+            // A sync flush is complete once all input is consumed and the compressor stopped
+            // short of filling the space it was given.
+            Status::Ok | Status::BufError => {
+                if !matches!(flush, FlushCompress::Finish)
+                    && input.is_empty()
+                    && output.len() < output.capacity()
+                {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Raw deflate encoder compressing chunks of its input on multiple threads.
+pub(super) struct ParallelDeflateEncoder {
+    level: Compression,
+    pool: WorkerPool,
+    max_pending: usize,
+    pending: Ordered<Chunk>,
+    chunk: Vec<u8>,
+    window: Vec<u8>,
+    current: PartialBuffer<Vec<u8>>,
+    crc: Crc,
+    flushed: bool,
+    /// Whether flushing drops the window, so the chunks after it don't refer back to it.
+    full_flush: bool,
+    finished: bool,
+}
+
+impl fmt::Debug for ParallelDeflateEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParallelDeflateEncoder")
+            .field("level", &self.level)
+            .field("pool", &self.pool)
+            .field("chunk", &self.chunk.len())
+            .field("flushed", &self.flushed)
+            .field("finished", &self.finished)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ParallelDeflateEncoder {
+    pub(super) fn new(level: Compression, threads: NonZeroU32) -> io::Result<Self> {
+        Ok(Self {
+            level,
+            pool: WorkerPool::new("gzip-parallel", threads)?,
+            // Allow every worker to have one job queued behind the one it is compressing, which
+            // bounds memory use to roughly `4 * threads * CHUNK_SIZE`.
+            max_pending: 2 * threads.get() as usize,
+            pending: Ordered::default(),
+            chunk: Vec::with_capacity(CHUNK_SIZE),
+            window: Vec::with_capacity(WINDOW_SIZE),
+            current: PartialBuffer::new(Vec::new()),
+            crc: Crc::new(),
+            flushed: true,
+            full_flush: false,
+            finished: false,
+        })
+    }
+
+    /// The CRC-32 of all the input whose compressed form has been written out so far.
+    pub(super) fn crc(&self) -> &Crc {
+        &self.crc
+    }
+
+    fn submit(&mut self, last: bool) -> io::Result<()> {
+        let data = std::mem::replace(&mut self.chunk, Vec::with_capacity(CHUNK_SIZE));
+        let dictionary = self.window.clone();
+
+        let keep = WINDOW_SIZE
+            .saturating_sub(data.len())
+            .min(self.window.len());
+        self.window.drain(..self.window.len() - keep);
+        let tail = data.len().min(WINDOW_SIZE);
+        self.window.extend_from_slice(&data[data.len() - tail..]);
+
+        let job = Job {
+            level: self.level,
+            dictionary,
+            data,
+            last,
+        };
+        let receiver = self.pool.spawn(1, move |results| {
+            results.send(compress_chunk(&job));
+        })?;
+        self.pending.push(receiver);
+        Ok(())
+    }
+
+    /// Moves the oldest completed chunk into `current`, returns whether there was one.
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        let Some(chunk) = self.pending.try_next()? else {
+            return Ok(false);
+        };
+
+        self.crc.combine(&chunk.crc);
+        self.current = PartialBuffer::new(chunk.compressed);
+        Ok(true)
+    }
+
+    /// Writes out completed chunks, returns whether everything submitted has been written.
+    fn drain(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        loop {
+            output.copy_unwritten_from(&mut self.current);
+
+            if !self.current.unwritten().is_empty() {
+                return Ok(false);
+            }
+
+            if !self.next_chunk()? {
+                return Ok(self.pending.is_empty());
+            }
+        }
+    }
+
+    /// Like [`drain`](Self::drain), but returns a [`would_block`] error if it had nothing to
+    /// write out yet.
+    fn drain_or_block(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        let written = output.written_len();
+        let done = self.drain(output)?;
+        if done || output.has_no_spare_space() || output.written_len() > written {
+            Ok(done)
+        } else {
+            Err(would_block())
+        }
+    }
+
+    pub(super) fn register_waker(&self, waker: &Waker) {
+        self.pool.register_waker(waker);
+    }
+
+    pub(super) fn encode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> io::Result<()> {
+        if self.finished {
+            return Err(io::Error::other("encode after complete"));
+        }
+
+        let (read, written) = (input.written_len(), output.written_len());
+
+        loop {
+            self.drain(output)?;
+
+            if self.chunk.len() == CHUNK_SIZE {
+                if self.pending.len() >= self.max_pending {
+                    // Wait for the oldest job rather than queueing unbounded amounts of input.
+                    if output.has_no_spare_space()
+                        || input.written_len() > read
+                        || output.written_len() > written
+                    {
+                        return Ok(());
+                    }
+                    return Err(would_block());
+                }
+                self.submit(false)?;
+            }
+
+            if input.unwritten().is_empty() {
+                return Ok(());
+            }
+
+            let len = input.unwritten().len().min(CHUNK_SIZE - self.chunk.len());
+            self.chunk.extend_from_slice(&input.unwritten()[..len]);
+            input.advance(len);
+            self.flushed = false;
+        }
+    }
+
+    pub(super) fn flush(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        if !self.flushed {
+            if !self.chunk.is_empty() {
+                self.submit(false)?;
+            }
+            if self.full_flush {
+                self.window.clear();
+            }
+            self.flushed = true;
+        }
+
+        self.drain_or_block(output)
+    }
+
+    pub(super) fn set_flush_mode(&mut self, mode: FlushMode) -> io::Result<()> {
+        self.full_flush = match mode {
+            FlushMode::Sync => false,
+            FlushMode::Full => true,
+            mode => return Err(mode.unsupported()),
+        };
+        Ok(())
+    }
+
+    /// Drops any chunks still being compressed and gets ready for a new stream, keeping the
+    /// worker threads.
+    pub(super) fn reinit(&mut self) {
+        self.pending.clear();
+        self.chunk.clear();
+        self.window.clear();
+        self.current = PartialBuffer::new(Vec::new());
+        self.crc = Crc::new();
+        self.flushed = true;
+        self.finished = false;
+    }
+
+    pub(super) fn finish(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        if !self.finished {
+            self.submit(true)?;
+            self.finished = true;
+        }
+
+        self.drain_or_block(output)
+    }
+}
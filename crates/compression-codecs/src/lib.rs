@@ -2,10 +2,12 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-use std::io::Result;
+use std::{io::Result, task::Waker};
 
 pub use compression_core as core;
 
+pub mod any;
+pub mod auto;
 #[cfg(feature = "brotli")]
 pub mod brotli;
 #[cfg(feature = "bzip2")]
@@ -18,21 +20,37 @@ pub mod deflate64;
 pub mod flate;
 #[cfg(feature = "gzip")]
 pub mod gzip;
+pub mod limits;
 #[cfg(feature = "lz4")]
 pub mod lz4;
+#[cfg(feature = "lzip")]
+pub mod lzip;
 #[cfg(feature = "lzma")]
 pub mod lzma;
+#[cfg(feature = "lzw")]
+pub mod lzw;
+pub mod oneshot;
+#[cfg(feature = "snappy")]
+pub mod snappy;
 #[cfg(feature = "xz")]
 pub mod xz;
 #[cfg(feature = "lzma")]
 pub mod xz2;
+#[cfg(feature = "zip")]
+pub mod zip;
 #[cfg(feature = "zlib")]
 pub mod zlib;
 #[cfg(feature = "zstd")]
 pub mod zstd;
 
+#[cfg(any(feature = "gzip-parallel", feature = "zstd-parallel"))]
+mod pool;
+
 use compression_core::util::{PartialBuffer, WriteBuffer};
 
+/// The input size hint of codecs without a preferred input size.
+const DEFAULT_INPUT_SIZE: usize = 8 * 1024;
+
 #[cfg(feature = "brotli")]
 pub use self::brotli::{BrotliDecoder, BrotliEncoder};
 #[cfg(feature = "bzip2")]
@@ -46,17 +64,30 @@ pub use self::flate::{FlateDecoder, FlateEncoder};
 #[cfg(feature = "gzip")]
 pub use self::gzip::{GzipDecoder, GzipEncoder};
 #[cfg(feature = "lz4")]
-pub use self::lz4::{Lz4Decoder, Lz4Encoder};
+pub use self::lz4::{Lz4Decoder, Lz4Encoder, Lz4LegacyDecoder};
+#[cfg(feature = "lzip")]
+pub use self::lzip::{LzipDecoder, LzipEncoder};
 #[cfg(feature = "lzma")]
 pub use self::lzma::{LzmaDecoder, LzmaEncoder};
+#[cfg(feature = "lzw")]
+pub use self::lzw::CompressZDecoder;
+#[cfg(feature = "snappy")]
+pub use self::snappy::{SnappyDecoder, SnappyEncoder};
 #[cfg(feature = "xz")]
 pub use self::xz::{XzDecoder, XzEncoder};
 #[cfg(feature = "lzma")]
 pub use self::xz2::{Xz2Decoder, Xz2Encoder, Xz2FileFormat};
+#[cfg(feature = "zip")]
+pub use self::zip::ZipEncoder;
 #[cfg(feature = "zlib")]
 pub use self::zlib::{ZlibDecoder, ZlibEncoder};
 #[cfg(feature = "zstd")]
 pub use self::zstd::{ZstdDecoder, ZstdEncoder};
+pub use self::{
+    any::{Algorithm, AnyDecoder, AnyEncoder},
+    auto::AutoDecoder,
+    limits::DecoderLimits,
+};
 
 fn forward_output<R>(
     output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
@@ -134,6 +165,53 @@ impl<T: EncodeV2 + ?Sized> Encode for T {
     }
 }
 
+/// How [`EncodeV2::flush`] ends the data encoded so far, set with
+/// [`EncodeV2::set_flush_mode`].
+///
+/// Every mode makes all the data encoded before the flush decodable from the output written so
+/// far, except with lzma and lzip whose format can't be flushed. The modes each algorithm
+/// supports are:
+///
+/// | Algorithm            | `Sync`                      | `Full`              | `BlockEnd`          |
+/// |----------------------|-----------------------------|---------------------|---------------------|
+/// | deflate, zlib, gzip  | sync flush                  | full flush          | -                   |
+/// | xz                   | `LZMA_SYNC_FLUSH`           | `LZMA_FULL_FLUSH`   | `LZMA_FULL_BARRIER` |
+/// | xz with a block size | ends the block              | ends the block      | ends the block      |
+/// | zstd                 | `ZSTD_e_flush`              | ends the frame      | ends the frame      |
+/// | bzip2                | ends the block              | ends the block      | ends the block      |
+/// | lz4                  | ends the block              | -                   | ends the block      |
+/// | snappy               | ends the chunk              | ends the chunk      | ends the chunk      |
+/// | brotli               | `BROTLI_OPERATION_FLUSH`    | -                   | -                   |
+/// | lzma, lzip           | nothing                     | -                   | -                   |
+///
+/// The multi-threaded xz encoder does a full flush for `Sync`, and may write out the data before
+/// a `BlockEnd` later on to keep its threads busy. Ending a zstd frame makes the output
+/// a sequence of frames, which decoders only read past the first of with multiple members
+/// enabled.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FlushMode {
+    /// Later data may still refer back to the data before the flush.
+    #[default]
+    Sync,
+    /// Later data doesn't refer back to the data before the flush, so decoding can start over
+    /// from the flush point.
+    Full,
+    /// The flush point is the end of a block or frame of the format, which readers can find
+    /// without decoding the data before it.
+    BlockEnd,
+}
+
+impl FlushMode {
+    /// Returns the error of encoders which don't support this mode.
+    fn unsupported(self) -> std::io::Error {
+        std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("this encoder does not support the {self:?} flush mode"),
+        )
+    }
+}
+
 /// version 2 of [`Encode`] that is trait object safe.
 ///
 /// The different from [`Encode`] is that:
@@ -151,6 +229,47 @@ pub trait EncodeV2 {
 
     /// Returns whether the internal buffers are flushed and the end of the stream is written
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool>;
+
+    /// Returns the preferred size of the input passed to each call of [`encode`](Self::encode),
+    /// for callers which read the input into a buffer of their own.
+    fn input_size_hint(&self) -> usize {
+        DEFAULT_INPUT_SIZE
+    }
+
+    /// Sets how the following calls to [`flush`](Self::flush) end the data encoded so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this encoder doesn't support `mode`, every encoder supports
+    /// [`FlushMode::Sync`].
+    fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()> {
+        match mode {
+            FlushMode::Sync => Ok(()),
+            mode => Err(mode.unsupported()),
+        }
+    }
+
+    /// Sets the waker to wake once this encoder can continue after returning a
+    /// [`WouldBlock`](std::io::ErrorKind::WouldBlock) error.
+    ///
+    /// Only encoders which hand their work to other threads return such errors, when they can
+    /// make no progress until that work is done, so the default does nothing.
+    fn register_waker(&mut self, waker: &Waker) {
+        let _ = waker;
+    }
+
+    /// Reinitializes this encoder ready to encode a new stream, keeping its parameters and, where
+    /// the codec allows it, its allocations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this encoder can't start a new stream.
+    fn reinit(&mut self) -> Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "this encoder does not support reinitialization",
+        ))
+    }
 }
 
 pub trait Decode {
@@ -226,6 +345,41 @@ pub trait DecodeV2 {
 
     /// Returns whether the internal buffers are flushed
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool>;
+
+    /// Returns the preferred size of the input passed to each call of [`decode`](Self::decode),
+    /// for callers which read the input into a buffer of their own.
+    fn input_size_hint(&self) -> usize {
+        DEFAULT_INPUT_SIZE
+    }
+
+    /// Limits the memory used to decode the streams started from now on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this decoder can't enforce `limits`, or if every stream of its format
+    /// needs more memory than they allow.
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        let _ = limits;
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "this decoder does not support memory limits",
+        ))
+    }
+
+    /// Sets the waker to wake once this decoder can continue after returning a
+    /// [`WouldBlock`](std::io::ErrorKind::WouldBlock) error.
+    ///
+    /// Only decoders which hand their work to other threads return such errors, when they can
+    /// make no progress until that work is done, so the default does nothing.
+    fn register_waker(&mut self, waker: &Waker) {
+        let _ = waker;
+    }
+
+    /// Resets this decoder ready to decode a new stream, which unlike the next member after a
+    /// [`reinit`](Self::reinit) may be in a different format for decoders which detect it.
+    fn reset(&mut self) -> Result<()> {
+        self.reinit()
+    }
 }
 
 pub trait DecodedSize {
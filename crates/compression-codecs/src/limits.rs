@@ -0,0 +1,193 @@
+//! Limits on the memory used by decoders.
+
+#[cfg(any(feature = "brotli", feature = "bzip2", feature = "lz4"))]
+use compression_core::util::{PartialBuffer, WriteBuffer};
+#[cfg(any(
+    feature = "brotli",
+    feature = "bzip2",
+    feature = "deflate64",
+    feature = "flate2",
+    feature = "lz4",
+    feature = "lzw",
+    feature = "snappy",
+    feature = "zstd"
+))]
+use std::io::Result;
+#[cfg(any(
+    feature = "brotli",
+    feature = "bzip2",
+    feature = "deflate64",
+    feature = "flate2",
+    feature = "lz4",
+    feature = "lzma",
+    feature = "lzw",
+    feature = "snappy",
+    feature = "zstd"
+))]
+use std::io::{Error, ErrorKind};
+
+/// Limits on the memory a decoder uses, see [`DecodeV2::set_limits`](crate::DecodeV2::set_limits).
+///
+/// The memory limit applies to the window, dictionary or block buffer a stream needs to be
+/// decoded, which makes up most of the memory used by its decoder. Decoders use the limit of their
+/// codec where it has one, and otherwise check the size announced by the header of each stream,
+/// or the fixed size of their format.
+///
+/// Streams needing more memory fail with an error of kind
+/// [`OutOfMemory`](std::io::ErrorKind::OutOfMemory).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DecoderLimits {
+    pub(crate) max_memory: Option<u64>,
+}
+
+impl DecoderLimits {
+    /// Creates limits which don't restrict anything.
+    pub const fn new() -> Self {
+        Self { max_memory: None }
+    }
+
+    /// Limits the memory needed by a stream to `bytes`.
+    pub const fn max_memory(mut self, bytes: u64) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+
+    /// Returns an error if a stream needing `needed` bytes of memory goes over the limit.
+    #[cfg(any(
+        feature = "brotli",
+        feature = "bzip2",
+        feature = "deflate64",
+        feature = "flate2",
+        feature = "lz4",
+        feature = "lzw",
+        feature = "snappy",
+        feature = "zstd"
+    ))]
+    pub(crate) fn check(&self, needed: u64) -> Result<()> {
+        match self.max_memory {
+            Some(max_memory) if needed > max_memory => Err(self.error(Some(needed))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the error for a stream needing `needed` bytes of memory, if known, over the limit.
+    #[cfg(any(
+        feature = "brotli",
+        feature = "bzip2",
+        feature = "deflate64",
+        feature = "flate2",
+        feature = "lz4",
+        feature = "lzma",
+        feature = "lzw",
+        feature = "snappy",
+        feature = "zstd"
+    ))]
+    pub(crate) fn error(&self, needed: Option<u64>) -> Error {
+        let limit = self.max_memory.unwrap_or(u64::MAX);
+        let message = match needed {
+            Some(needed) => format!(
+                "the stream needs {needed} bytes of memory, more than the limit of {limit} bytes"
+            ),
+            None => format!("the stream needs more than the limit of {limit} bytes of memory"),
+        };
+
+        Error::new(ErrorKind::OutOfMemory, message)
+    }
+}
+
+/// Holds back the first `N` bytes of each stream until the memory its header announces is known
+/// and within the limits, for codecs which can't enforce a limit themselves.
+#[cfg(any(feature = "brotli", feature = "bzip2", feature = "lz4"))]
+#[derive(Debug)]
+pub(crate) struct HeaderCheck<const N: usize> {
+    limits: DecoderLimits,
+    header: PartialBuffer<[u8; N]>,
+    /// The number of header bytes passed to the decoder, once the header has been checked.
+    replayed: Option<usize>,
+}
+
+#[cfg(any(feature = "brotli", feature = "bzip2", feature = "lz4"))]
+impl<const N: usize> HeaderCheck<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            limits: DecoderLimits::new(),
+            header: PartialBuffer::new([0; N]),
+            replayed: None,
+        }
+    }
+
+    pub(crate) fn set_limits(&mut self, limits: DecoderLimits) {
+        self.limits = limits;
+    }
+
+    /// Gets ready to check the header of the next stream.
+    pub(crate) fn reset(&mut self) {
+        self.header.reset();
+        self.replayed = None;
+    }
+
+    /// Passes the held back header bytes to `decode`, returns whether all of them have been passed
+    /// and whether the decoder reached the end of the stream.
+    fn replay(
+        &mut self,
+        output: &mut WriteBuffer<'_>,
+        decode: &mut impl FnMut(&mut PartialBuffer<&[u8]>, &mut WriteBuffer<'_>) -> Result<bool>,
+    ) -> Result<(bool, bool)> {
+        let replayed = self.replayed.get_or_insert(0);
+        if *replayed == self.header.written_len() {
+            return Ok((true, false));
+        }
+
+        let mut header = PartialBuffer::new(self.header.written());
+        header.advance(*replayed);
+        let done = decode(&mut header, output)?;
+        *replayed = header.written_len();
+
+        Ok((header.unwritten().is_empty(), done))
+    }
+
+    /// Decodes `input` with `decode`, once `memory` has returned the memory announced by the
+    /// header within the limits, or `None` if given all `N` bytes of a header it doesn't know.
+    pub(crate) fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+        memory: impl FnOnce(&[u8]) -> Option<u64>,
+        mut decode: impl FnMut(&mut PartialBuffer<&[u8]>, &mut WriteBuffer<'_>) -> Result<bool>,
+    ) -> Result<bool> {
+        if self.replayed.is_none() && self.limits.max_memory.is_none() {
+            self.replayed = Some(0);
+        }
+        if self.replayed == Some(self.header.written_len()) {
+            return decode(input, output);
+        }
+
+        if self.replayed.is_none() {
+            self.header.copy_unwritten_from(input);
+            match memory(self.header.written()) {
+                Some(needed) => self.limits.check(needed)?,
+                // The decoder rejects the header itself if it is invalid.
+                None if self.header.unwritten().is_empty() => {}
+                None => return Ok(false),
+            }
+        }
+
+        let (replayed, done) = self.replay(output, &mut decode)?;
+        // Decoders may fail when they are given no input to make progress with.
+        if !replayed || done || input.unwritten().is_empty() {
+            return Ok(done);
+        }
+
+        decode(input, output)
+    }
+
+    /// Passes any held back header bytes of a stream cut short to `decode`, returns whether all
+    /// of them have been passed.
+    pub(crate) fn finish(
+        &mut self,
+        output: &mut WriteBuffer<'_>,
+        mut decode: impl FnMut(&mut PartialBuffer<&[u8]>, &mut WriteBuffer<'_>) -> Result<bool>,
+    ) -> Result<bool> {
+        Ok(self.replay(output, &mut decode)?.0)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::DecodeV2;
+use crate::{limits::HeaderCheck, DecodeV2, DecoderLimits};
 use compression_core::{
     unshared::Unshared,
     util::{PartialBuffer, WriteBuffer},
@@ -14,10 +14,27 @@ struct DecoderContext {
     ctx: LZ4FDecompressionContext,
 }
 
+/// Returns the size of the blocks of a frame starting with `header`.
+fn block_size(header: &[u8]) -> Option<u64> {
+    match header {
+        [0x04, 0x22, 0x4d, 0x18, _, descriptor] => match (descriptor >> 4) & 7 {
+            4 => Some(64 * 1024),
+            5 => Some(256 * 1024),
+            6 => Some(1024 * 1024),
+            7 => Some(4 * 1024 * 1024),
+            _ => None,
+        },
+        // Skippable frames need no memory.
+        [0x50..=0x5f, 0x2a, 0x4d, 0x18, ..] => Some(0),
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub struct Lz4Decoder {
     ctx: Unshared<DecoderContext>,
     stream_ended: bool,
+    header: HeaderCheck<6>,
 }
 
 impl DecoderContext {
@@ -39,6 +56,7 @@ impl Default for Lz4Decoder {
         Self {
             ctx: Unshared::new(DecoderContext::new().unwrap()),
             stream_ended: false,
+            header: HeaderCheck::new(),
         }
     }
 }
@@ -47,17 +65,10 @@ impl Lz4Decoder {
     pub fn new() -> Self {
         Self::default()
     }
-}
-
-impl DecodeV2 for Lz4Decoder {
-    fn reinit(&mut self) -> Result<()> {
-        unsafe { LZ4F_resetDecompressionContext(self.ctx.get_mut().ctx) };
-        self.stream_ended = false;
-        Ok(())
-    }
 
     fn decode(
-        &mut self,
+        ctx: &mut Unshared<DecoderContext>,
+        stream_ended: &mut bool,
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> Result<bool> {
@@ -71,7 +82,7 @@ impl DecodeV2 for Lz4Decoder {
             let mut output_size = out_buf.len();
 
             let result = check_error(LZ4F_decompress(
-                self.ctx.get_mut().ctx,
+                ctx.get_mut().ctx,
                 out_buf.as_mut_ptr() as *mut _,
                 &mut output_size,
                 input.unwritten().as_ptr(),
@@ -86,17 +97,44 @@ impl DecodeV2 for Lz4Decoder {
 
         let finished = result == 0;
         if finished {
-            self.stream_ended = true;
+            *stream_ended = true;
         }
         Ok(finished)
     }
+}
+
+impl DecodeV2 for Lz4Decoder {
+    fn reinit(&mut self) -> Result<()> {
+        unsafe { LZ4F_resetDecompressionContext(self.ctx.get_mut().ctx) };
+        self.stream_ended = false;
+        self.header.reset();
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        let Self {
+            ctx,
+            stream_ended,
+            header,
+        } = self;
+        header.decode(input, output, block_size, |input, output| {
+            Self::decode(ctx, stream_ended, input, output)
+        })
+    }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
-        self.decode(&mut PartialBuffer::new(&[][..]), output)?;
+        let Self {
+            ctx, stream_ended, ..
+        } = self;
+        Self::decode(ctx, stream_ended, &mut PartialBuffer::new(&[][..]), output)?;
 
         loop {
             let old_len = output.written_len();
-            self.decode(&mut PartialBuffer::new(&[][..]), output)?;
+            Self::decode(ctx, stream_ended, &mut PartialBuffer::new(&[][..]), output)?;
             if output.written_len() == old_len {
                 break;
             }
@@ -106,6 +144,17 @@ impl DecodeV2 for Lz4Decoder {
     }
 
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        let Self {
+            ctx,
+            stream_ended,
+            header,
+        } = self;
+        if !header.finish(output, |input, output| {
+            Self::decode(ctx, stream_ended, input, output)
+        })? {
+            return Ok(false);
+        }
+
         self.flush(output)?;
 
         if self.stream_ended {
@@ -117,4 +166,9 @@ impl DecodeV2 for Lz4Decoder {
             ))
         }
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.header.set_limits(limits);
+        Ok(())
+    }
 }
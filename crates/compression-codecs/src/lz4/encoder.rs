@@ -1,4 +1,4 @@
-use crate::{lz4::params::EncoderParams, EncodeV2};
+use crate::{lz4::params::EncoderParams, EncodeV2, FlushMode};
 use compression_core::{
     unshared::Unshared,
     util::{PartialBuffer, WriteBuffer},
@@ -297,4 +297,22 @@ impl EncodeV2 for Lz4Encoder {
             }
         }
     }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()> {
+        // Flushing ends the block, but blocks are linked to the ones before them.
+        match mode {
+            FlushMode::Sync | FlushMode::BlockEnd => Ok(()),
+            mode => Err(mode.unsupported()),
+        }
+    }
+
+    fn reinit(&mut self) -> Result<()> {
+        // Beginning a frame resets the context, even in the middle of another one.
+        self.state = State::Header;
+        if let Some(buffer) = &mut self.maybe_buffer {
+            buffer.reset();
+            buffer.get_mut().clear();
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,162 @@
+use crate::{DecodeV2, DecoderLimits};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use std::io::{Error, ErrorKind, Result};
+
+/// Magic number starting a stream in the legacy frame format.
+const LEGACY_MAGIC: [u8; 4] = [0x02, 0x21, 0x4c, 0x18];
+
+/// Uncompressed size of every block but the last one.
+const BLOCK_SIZE: usize = 8 << 20;
+
+/// The most a block may expand by, as the length of a match grows by at most 255 for each byte
+/// encoding it.
+const MAX_RATIO: usize = 255;
+
+/// The largest size a block of `len` compressed bytes may decode to.
+fn decoded_bound(len: usize) -> usize {
+    len.saturating_mul(MAX_RATIO).min(BLOCK_SIZE)
+}
+
+fn invalid(message: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Decoder for the legacy LZ4 frame format, as written by `lz4 -l`: a magic number followed by
+/// blocks of up to 8 MiB, each preceded by its compressed size.
+///
+/// The format has no end of stream marker or checksum, so the whole input is decoded as a single
+/// stream, which may contain several concatenated legacy streams.
+#[derive(Debug)]
+pub struct Lz4LegacyDecoder {
+    header: PartialBuffer<[u8; 4]>,
+    seen_magic: bool,
+    /// Compressed size of the block being read, if its header has been read.
+    block_len: Option<usize>,
+    block: Vec<u8>,
+    output: PartialBuffer<Vec<u8>>,
+    limits: DecoderLimits,
+}
+
+impl Default for Lz4LegacyDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lz4LegacyDecoder {
+    pub fn new() -> Self {
+        Self {
+            header: PartialBuffer::new([0; 4]),
+            seen_magic: false,
+            block_len: None,
+            block: Vec::new(),
+            output: PartialBuffer::new(Vec::new()),
+            limits: DecoderLimits::new(),
+        }
+    }
+
+    /// Writes out as much of the decoded block as fits, returns whether all of it was written.
+    fn drain(&mut self, output: &mut WriteBuffer<'_>) -> bool {
+        output.copy_unwritten_from(&mut self.output);
+        self.output.unwritten().is_empty()
+    }
+
+    fn read_header(&mut self) -> Result<()> {
+        let header = *self.header.get_mut();
+        self.header.reset();
+
+        if !self.seen_magic {
+            if header != LEGACY_MAGIC {
+                return Err(invalid("invalid lz4 legacy frame magic"));
+            }
+            self.seen_magic = true;
+        } else if header != LEGACY_MAGIC {
+            let len = u32::from_le_bytes(header) as usize;
+            if len > lz4::block::compress_bound(BLOCK_SIZE)? {
+                return Err(invalid("invalid lz4 legacy block size"));
+            }
+            // The block is buffered along with its decoded data.
+            self.limits.check((len + decoded_bound(len)) as u64)?;
+            self.block.clear();
+            self.block_len = Some(len);
+        }
+
+        Ok(())
+    }
+
+    fn decode_block(&mut self) -> Result<()> {
+        let max_len = decoded_bound(self.block.len());
+        let output = self.output.get_mut();
+        output.resize(max_len, 0);
+        // The size is a maximum for the decoded block, which always fits an `i32`.
+        let len = lz4::block::decompress_to_buffer(&self.block, Some(max_len as i32), output)?;
+        output.truncate(len);
+        self.output.reset();
+        self.block_len = None;
+        Ok(())
+    }
+}
+
+impl DecodeV2 for Lz4LegacyDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        *self = Self {
+            limits: self.limits,
+            ..Self::new()
+        };
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        loop {
+            // There is no end of stream marker, so decoding only stops at the end of input.
+            if !self.drain(output) || input.unwritten().is_empty() {
+                return Ok(false);
+            }
+
+            match self.block_len {
+                None => {
+                    self.header.copy_unwritten_from(input);
+                    if self.header.unwritten().is_empty() {
+                        self.read_header()?;
+                    }
+                }
+
+                Some(len) => {
+                    let read = input.unwritten().len().min(len - self.block.len());
+                    self.block.extend_from_slice(&input.unwritten()[..read]);
+                    input.advance(read);
+
+                    if self.block.len() == len {
+                        self.decode_block()?;
+                    }
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        Ok(self.drain(output))
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if self.seen_magic && self.block_len.is_none() && self.header.written_len() == 0 {
+            Ok(self.drain(output))
+        } else {
+            Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "lz4 legacy stream is truncated",
+            ))
+        }
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        // The memory needed depends on the compressed size of each block, so the limit is checked
+        // as their headers are read.
+        self.limits = limits;
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 mod decoder;
 mod encoder;
+mod legacy;
 pub mod params;
 
-pub use self::{decoder::Lz4Decoder, encoder::Lz4Encoder};
+pub use self::{decoder::Lz4Decoder, encoder::Lz4Encoder, legacy::Lz4LegacyDecoder};
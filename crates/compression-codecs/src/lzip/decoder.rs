@@ -0,0 +1,122 @@
+use super::{HEADER_LEN, MAGIC, TRAILER_LEN};
+use crate::{lzma::params::LzmaDecoderParams, DecodeV2, DecodedSize, DecoderLimits, Xz2Decoder};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use std::{
+    convert::{TryFrom, TryInto},
+    io::{Error, ErrorKind, Result},
+};
+
+/// Lzip decoding stream
+///
+/// The CRC-32, data size and member size in each member trailer are validated by liblzma.
+#[derive(Debug)]
+pub struct LzipDecoder {
+    inner: Xz2Decoder,
+}
+
+impl Default for LzipDecoder {
+    fn default() -> Self {
+        Self::with_memlimit(usize::MAX.try_into().unwrap())
+    }
+}
+
+impl LzipDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_memlimit(mem_limit: u64) -> Self {
+        let params = LzmaDecoderParams::Lzip {
+            mem_limit,
+            flags: 0,
+        };
+        Self {
+            inner: Xz2Decoder::try_from(params).unwrap(),
+        }
+    }
+}
+
+impl DecodeV2 for LzipDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.inner.reinit()
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        self.inner.decode(input, output)
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.inner.flush(output)
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.inner.finish(output)
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.inner.set_limits(limits)
+    }
+}
+
+impl DecodedSize for LzipDecoder {
+    /// Sums the data sizes of all members, walking backwards from the end of `input` using the
+    /// member size recorded in each trailer.
+    fn decoded_size(mut input: &[u8]) -> Result<u64> {
+        let invalid = || Error::new(ErrorKind::InvalidData, "invalid lzip member trailer");
+
+        let mut size = 0u64;
+        while !input.is_empty() {
+            let trailer = input
+                .len()
+                .checked_sub(TRAILER_LEN)
+                .map(|start| &input[start..])
+                .ok_or_else(invalid)?;
+            let data_size = u64::from_le_bytes(trailer[4..12].try_into().unwrap());
+            let member_size = u64::from_le_bytes(trailer[12..].try_into().unwrap());
+
+            let start = usize::try_from(member_size)
+                .ok()
+                .filter(|&len| len >= HEADER_LEN + TRAILER_LEN)
+                .and_then(|len| input.len().checked_sub(len))
+                .ok_or_else(invalid)?;
+            if input[start..start + MAGIC.len()] != MAGIC {
+                return Err(invalid());
+            }
+
+            size = size.checked_add(data_size).ok_or_else(invalid)?;
+            input = &input[..start];
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DecodedSize, Encode, LzipDecoder, LzipEncoder};
+    use compression_core::{util::PartialBuffer, Level};
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = LzipEncoder::new(Level::Fastest);
+        let mut input = PartialBuffer::new(data);
+        let mut output = PartialBuffer::new(vec![0; 1024]);
+        encoder.encode(&mut input, &mut output).unwrap();
+        assert!(encoder.finish(&mut output).unwrap());
+
+        let len = output.written_len();
+        let mut output = output.into_inner();
+        output.truncate(len);
+        output
+    }
+
+    #[test]
+    fn test_lzip_decoded_size_multiple_members() {
+        let input = [compress(b"hello "), compress(b"world")].concat();
+        assert_eq!(LzipDecoder::decoded_size(&input).unwrap(), 11);
+        LzipDecoder::decoded_size(&input[1..]).unwrap_err();
+    }
+}
@@ -0,0 +1,178 @@
+use super::{HEADER_LEN, MAGIC, TRAILER_LEN};
+use crate::{
+    lzma::params::{LzmaEncoderParams, LzmaFilter, LzmaFilters, LzmaOptions},
+    xz2::{xz2_dictionary_size, xz2_level},
+    EncodeV2, Xz2Encoder,
+};
+use compression_core::{
+    util::{PartialBuffer, WriteBuffer},
+    Level,
+};
+use std::{convert::TryFrom, io};
+
+#[derive(Debug)]
+enum State {
+    Header(PartialBuffer<[u8; HEADER_LEN]>),
+    Encoding,
+    Trailer(PartialBuffer<[u8; TRAILER_LEN]>),
+    Done,
+}
+
+/// Lzip encoding stream, producing a single member.
+#[derive(Debug)]
+pub struct LzipEncoder {
+    inner: Xz2Encoder,
+    crc: crc32fast::Hasher,
+    data_size: u64,
+    member_size: u64,
+    header: [u8; HEADER_LEN],
+    state: State,
+}
+
+fn header(dictionary_size: u32) -> [u8; HEADER_LEN] {
+    let [a, b, c, d] = MAGIC;
+    // The coded dictionary size is the base 2 logarithm in the low bits, with no fraction
+    // subtracted from it.
+    [a, b, c, d, 1, dictionary_size.trailing_zeros() as u8]
+}
+
+impl LzipEncoder {
+    pub fn new(level: Level) -> Self {
+        let preset = xz2_level(level);
+        let dictionary_size = xz2_dictionary_size(preset);
+
+        // The lzip format fixes the literal and position parameters, and members are terminated
+        // by the end of stream marker which the raw LZMA1 encoder always writes.
+        let options = LzmaOptions::default()
+            .preset(preset)
+            .dict_size(dictionary_size)
+            .literal_context_bits(3)
+            .literal_position_bits(0)
+            .position_bits(2);
+        let filters = LzmaFilters::default().add_filter(LzmaFilter::Lzma1(options));
+
+        let header = header(dictionary_size);
+        Self {
+            inner: Xz2Encoder::try_from(LzmaEncoderParams::Raw { filters }).unwrap(),
+            crc: crc32fast::Hasher::new(),
+            data_size: 0,
+            member_size: (HEADER_LEN + TRAILER_LEN) as u64,
+            header,
+            state: State::Header(header.into()),
+        }
+    }
+
+    fn trailer(&self) -> [u8; TRAILER_LEN] {
+        let mut output = [0; TRAILER_LEN];
+
+        output[..4].copy_from_slice(&self.crc.clone().finalize().to_le_bytes());
+        output[4..12].copy_from_slice(&self.data_size.to_le_bytes());
+        output[12..].copy_from_slice(&self.member_size.to_le_bytes());
+
+        output
+    }
+}
+
+impl EncodeV2 for LzipEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> io::Result<()> {
+        loop {
+            match &mut self.state {
+                State::Header(header) => {
+                    output.copy_unwritten_from(&mut *header);
+
+                    if header.unwritten().is_empty() {
+                        self.state = State::Encoding;
+                    }
+                }
+
+                State::Encoding => {
+                    let prior_read = input.written_len();
+                    let prior_written = output.written_len();
+                    self.inner.encode(input, output)?;
+
+                    let read = &input.written()[prior_read..];
+                    self.crc.update(read);
+                    self.data_size += read.len() as u64;
+                    self.member_size += (output.written_len() - prior_written) as u64;
+                }
+
+                State::Trailer(_) | State::Done => {
+                    return Err(io::Error::other("encode after complete"));
+                }
+            };
+
+            if input.unwritten().is_empty() || output.has_no_spare_space() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        // Flush on LZMA 1 is not supported, but the header can at least be written out
+        if let State::Header(header) = &mut self.state {
+            output.copy_unwritten_from(&mut *header);
+
+            if !header.unwritten().is_empty() {
+                return Ok(false);
+            }
+            self.state = State::Encoding;
+        }
+
+        Ok(true)
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        loop {
+            match &mut self.state {
+                State::Header(header) => {
+                    output.copy_unwritten_from(&mut *header);
+
+                    if header.unwritten().is_empty() {
+                        self.state = State::Encoding;
+                    }
+                }
+
+                State::Encoding => {
+                    let prior_written = output.written_len();
+                    let done = self.inner.finish(output)?;
+                    self.member_size += (output.written_len() - prior_written) as u64;
+
+                    if done {
+                        self.state = State::Trailer(self.trailer().into());
+                    }
+                }
+
+                State::Trailer(trailer) => {
+                    output.copy_unwritten_from(&mut *trailer);
+
+                    if trailer.unwritten().is_empty() {
+                        self.state = State::Done;
+                    }
+                }
+
+                State::Done => {}
+            };
+
+            if let State::Done = self.state {
+                return Ok(true);
+            }
+
+            if output.has_no_spare_space() {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn reinit(&mut self) -> io::Result<()> {
+        self.inner.reinit()?;
+        self.crc.reset();
+        self.data_size = 0;
+        self.member_size = (HEADER_LEN + TRAILER_LEN) as u64;
+        self.state = State::Header(self.header.into());
+        Ok(())
+    }
+}
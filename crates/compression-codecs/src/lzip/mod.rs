@@ -0,0 +1,13 @@
+mod decoder;
+mod encoder;
+
+pub use self::{decoder::LzipDecoder, encoder::LzipEncoder};
+
+/// Magic bytes at the start of every lzip member.
+const MAGIC: [u8; 4] = *b"LZIP";
+
+/// Length of the member header: magic, version and coded dictionary size.
+const HEADER_LEN: usize = 6;
+
+/// Length of the member trailer: CRC-32, data size and member size.
+const TRAILER_LEN: usize = 20;
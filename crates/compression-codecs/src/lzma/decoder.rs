@@ -1,6 +1,12 @@
-use crate::{DecodeV2, DecodedSize, Xz2Decoder};
+use crate::{
+    lzma::params::{LzmaDecoderOptions, LzmaDecoderParams, StreamCheck},
+    DecodeV2, DecodedSize, DecoderLimits, Xz2Decoder,
+};
 use compression_core::util::{PartialBuffer, WriteBuffer};
-use std::{convert::TryInto, io::Result};
+use std::{
+    convert::{TryFrom, TryInto},
+    io::{Error, ErrorKind, Result},
+};
 
 /// Lzma decoding stream
 #[derive(Debug)]
@@ -32,6 +38,41 @@ impl LzmaDecoder {
             inner: Xz2Decoder::new(memlimit),
         }
     }
+
+    pub fn with_options(options: LzmaDecoderOptions) -> Self {
+        Self {
+            inner: Xz2Decoder::try_from(options.auto_params()).unwrap(),
+        }
+    }
+
+    /// Creates a decoder from the given parameters, which must be either
+    /// [`Lzma`](LzmaDecoderParams::Lzma) for the `.lzma` format or
+    /// [`Raw`](LzmaDecoderParams::Raw) for a raw filter chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the parameters are for an xz or lzip stream or are not supported by
+    /// liblzma.
+    pub fn with_params(params: LzmaDecoderParams) -> Result<Self> {
+        match params {
+            LzmaDecoderParams::Lzma { .. } | LzmaDecoderParams::Raw { .. } => {}
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "parameters describe an xz or lzip stream, use the matching decoder instead",
+                ))
+            }
+        }
+
+        Ok(Self {
+            inner: Xz2Decoder::try_from(params)?,
+        })
+    }
+
+    /// Returns the integrity check of the stream being decoded, see [`Xz2Decoder::check`].
+    pub fn check(&self) -> Option<StreamCheck> {
+        self.inner.check()
+    }
 }
 
 impl DecodeV2 for LzmaDecoder {
@@ -54,6 +95,10 @@ impl DecodeV2 for LzmaDecoder {
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.inner.set_limits(limits)
+    }
 }
 
 impl DecodedSize for LzmaDecoder {
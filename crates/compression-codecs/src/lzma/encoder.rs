@@ -1,9 +1,12 @@
-use crate::{EncodeV2, Xz2Encoder, Xz2FileFormat};
+use crate::{lzma::params::LzmaEncoderParams, EncodeV2, Xz2Encoder, Xz2FileFormat};
 use compression_core::{
     util::{PartialBuffer, WriteBuffer},
     Level,
 };
-use std::io::Result;
+use std::{
+    convert::TryFrom,
+    io::{Error, ErrorKind, Result},
+};
 
 /// Lzma encoding stream
 #[derive(Debug)]
@@ -17,6 +20,29 @@ impl LzmaEncoder {
             inner: Xz2Encoder::new(Xz2FileFormat::Lzma, level),
         }
     }
+
+    /// Creates an encoder from the given parameters, which must be either
+    /// [`Lzma`](LzmaEncoderParams::Lzma) for the `.lzma` format or
+    /// [`Raw`](LzmaEncoderParams::Raw) for a raw filter chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the parameters are for an xz stream or are not supported by liblzma.
+    pub fn with_params(params: LzmaEncoderParams) -> Result<Self> {
+        match params {
+            LzmaEncoderParams::Lzma { .. } | LzmaEncoderParams::Raw { .. } => {}
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "parameters describe an xz stream, use an xz encoder instead",
+                ))
+            }
+        }
+
+        Ok(Self {
+            inner: Xz2Encoder::try_from(params)?,
+        })
+    }
 }
 
 impl From<Xz2Encoder> for LzmaEncoder {
@@ -42,4 +68,8 @@ impl EncodeV2 for LzmaEncoder {
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn reinit(&mut self) -> Result<()> {
+        self.inner.reinit()
+    }
 }
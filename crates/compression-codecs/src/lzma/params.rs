@@ -1,8 +1,13 @@
 //! Clone-able structs to build the non-clone-ables in liblzma
 
-use std::convert::TryFrom;
 #[cfg(feature = "xz-parallel")]
 use std::num::NonZeroU32;
+use std::{
+    convert::TryFrom,
+    io::{Error, ErrorKind, Result as IoResult},
+};
+
+pub use liblzma::stream::Check;
 
 /// Used to control how the LZMA stream is created.
 #[derive(Debug, Clone)]
@@ -58,6 +63,110 @@ impl TryFrom<&LzmaEncoderParams> for liblzma::stream::Stream {
     }
 }
 
+/// Options for decoding xz, lzma and lzip streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LzmaDecoderOptions {
+    mem_limit: u64,
+    ignore_check: bool,
+    concatenated: bool,
+    tell_no_check: bool,
+    tell_unsupported_check: bool,
+}
+
+impl Default for LzmaDecoderOptions {
+    fn default() -> Self {
+        Self {
+            mem_limit: u64::MAX,
+            ignore_check: false,
+            concatenated: false,
+            tell_no_check: false,
+            tell_unsupported_check: false,
+        }
+    }
+}
+
+impl LzmaDecoderOptions {
+    /// Limits the memory the decoder may use, decoding fails if it would need more.
+    pub fn mem_limit(mut self, value: u64) -> Self {
+        self.mem_limit = value;
+        self
+    }
+
+    /// Skips verifying the integrity check of the decoded data, which is faster but only safe for
+    /// trusted input.
+    pub fn ignore_check(mut self, value: bool) -> Self {
+        self.ignore_check = value;
+        self
+    }
+
+    /// Decodes concatenated streams, and the padding between them, as a single stream.
+    pub fn concatenated(mut self, value: bool) -> Self {
+        self.concatenated = value;
+        self
+    }
+
+    /// Fails decoding if the stream has no integrity check.
+    pub fn tell_no_check(mut self, value: bool) -> Self {
+        self.tell_no_check = value;
+        self
+    }
+
+    /// Fails decoding if the stream uses an integrity check liblzma doesn't support, rather than
+    /// decoding it without verification.
+    pub fn tell_unsupported_check(mut self, value: bool) -> Self {
+        self.tell_unsupported_check = value;
+        self
+    }
+
+    /// The liblzma decoder flags for these options.
+    fn flags(&self) -> u32 {
+        use liblzma::stream::{CONCATENATED, IGNORE_CHECK, TELL_NO_CHECK, TELL_UNSUPPORTED_CHECK};
+
+        let mut flags = 0;
+        if self.ignore_check {
+            flags |= IGNORE_CHECK;
+        }
+        if self.concatenated {
+            flags |= CONCATENATED;
+        }
+        if self.tell_no_check {
+            flags |= TELL_NO_CHECK;
+        }
+        if self.tell_unsupported_check {
+            flags |= TELL_UNSUPPORTED_CHECK;
+        }
+        flags
+    }
+
+    /// Parameters for a decoder which detects whether the input is in the xz, lzma or lzip
+    /// format.
+    pub(crate) fn auto_params(&self) -> LzmaDecoderParams {
+        LzmaDecoderParams::Auto {
+            mem_limit: self.mem_limit,
+            flags: self.flags(),
+        }
+    }
+
+    /// Parameters for a decoder which only accepts the xz format.
+    #[cfg(feature = "xz")]
+    pub(crate) fn stream_params(&self) -> LzmaDecoderParams {
+        LzmaDecoderParams::Stream {
+            mem_limit: self.mem_limit,
+            flags: self.flags(),
+        }
+    }
+}
+
+/// The integrity check of an xz stream, as found in its header.
+#[derive(Debug, Clone, Copy)]
+pub enum StreamCheck {
+    /// A check liblzma verifies, unless told to ignore it.
+    Supported(Check),
+    /// The ID of a check liblzma does not support, the data is decoded without verifying it
+    /// unless [`tell_unsupported_check`](LzmaDecoderOptions::tell_unsupported_check) is set.
+    Unsupported(u8),
+}
+
 /// Directly translate to how the stream is constructed
 #[derive(Clone, Debug)]
 pub enum LzmaDecoderParams {
@@ -86,6 +195,30 @@ pub enum LzmaDecoderParams {
     },
 }
 
+impl LzmaDecoderParams {
+    /// Sets the memory limit of the decoder, which raw filter chains don't have.
+    pub(crate) fn set_mem_limit(&mut self, value: u64) -> std::io::Result<()> {
+        match self {
+            Self::Auto { mem_limit, .. }
+            | Self::Lzip { mem_limit, .. }
+            | Self::Lzma { mem_limit }
+            | Self::Stream { mem_limit, .. } => *mem_limit = value,
+            Self::Raw { .. } => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "raw lzma streams do not support memory limits",
+                ))
+            }
+            #[cfg(feature = "xz-parallel")]
+            Self::MultiThread { builder } => {
+                builder.mem_limit_stop(value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl TryFrom<&LzmaDecoderParams> for liblzma::stream::Stream {
     type Error = liblzma::stream::Error;
 
@@ -187,6 +320,136 @@ impl TryFrom<&LzmaFilters> for liblzma::stream::Filters {
     }
 }
 
+/// The raw filter chain of `LzmaFilters`, for setting up an encoder through liblzma-sys, which
+/// owns the options its filters point to.
+pub(crate) struct RawFilters {
+    /// The filters, ending with a terminator.
+    filters: Vec<liblzma_sys::lzma_filter>,
+    /// A list rather than a `Vec`, so that the options don't move as more are added.
+    lzma_options: std::collections::LinkedList<liblzma_sys::lzma_options_lzma>,
+    /// The filters whose options liblzma allocated when decoding their properties.
+    decoded: Vec<liblzma_sys::lzma_filter>,
+}
+
+impl RawFilters {
+    pub(crate) fn as_ptr(&self) -> *const liblzma_sys::lzma_filter {
+        self.filters.as_ptr()
+    }
+
+    fn push(
+        &mut self,
+        id: liblzma_sys::lzma_vli,
+        properties: Option<&[u8]>,
+    ) -> Result<(), liblzma::stream::Error> {
+        let mut filter = liblzma_sys::lzma_filter {
+            id,
+            options: std::ptr::null_mut(),
+        };
+        if let Some(properties) = properties {
+            cvt(unsafe {
+                liblzma_sys::lzma_properties_decode(
+                    &mut filter,
+                    std::ptr::null(),
+                    properties.as_ptr(),
+                    properties.len(),
+                )
+            })?;
+            if !filter.options.is_null() {
+                self.decoded.push(filter);
+            }
+        }
+        self.filters.push(filter);
+        Ok(())
+    }
+
+    fn push_lzma(
+        &mut self,
+        id: liblzma_sys::lzma_vli,
+        options: &LzmaOptions,
+    ) -> Result<(), liblzma::stream::Error> {
+        self.lzma_options.push_back(options.to_raw()?);
+        let options = self.lzma_options.back_mut().unwrap();
+        self.filters.push(liblzma_sys::lzma_filter {
+            id,
+            options: (options as *mut liblzma_sys::lzma_options_lzma).cast(),
+        });
+        Ok(())
+    }
+}
+
+impl TryFrom<&LzmaFilters> for RawFilters {
+    type Error = liblzma::stream::Error;
+
+    fn try_from(value: &LzmaFilters) -> Result<Self, Self::Error> {
+        use liblzma_sys::*;
+
+        let mut filters = Self {
+            filters: Vec::with_capacity(value.filters.len() + 1),
+            lzma_options: std::collections::LinkedList::new(),
+            decoded: Vec::new(),
+        };
+        for f in value.filters.iter() {
+            match f {
+                LzmaFilter::Arm(p) => filters.push(LZMA_FILTER_ARM, p.as_deref())?,
+                LzmaFilter::Arm64(p) => filters.push(LZMA_FILTER_ARM64, p.as_deref())?,
+                LzmaFilter::ArmThumb(p) => filters.push(LZMA_FILTER_ARMTHUMB, p.as_deref())?,
+                LzmaFilter::Delta(p) => filters.push(LZMA_FILTER_DELTA, p.as_deref())?,
+                LzmaFilter::Ia64(p) => filters.push(LZMA_FILTER_IA64, p.as_deref())?,
+                LzmaFilter::Lzma1(opts) => filters.push_lzma(LZMA_FILTER_LZMA1, opts)?,
+                LzmaFilter::Lzma1Properties(p) => filters.push(LZMA_FILTER_LZMA1, Some(p))?,
+                LzmaFilter::Lzma2(opts) => filters.push_lzma(LZMA_FILTER_LZMA2, opts)?,
+                LzmaFilter::Lzma2Properties(p) => filters.push(LZMA_FILTER_LZMA2, Some(p))?,
+                LzmaFilter::PowerPc(p) => filters.push(LZMA_FILTER_POWERPC, p.as_deref())?,
+                LzmaFilter::Sparc(p) => filters.push(LZMA_FILTER_SPARC, p.as_deref())?,
+                LzmaFilter::X86(p) => filters.push(LZMA_FILTER_X86, p.as_deref())?,
+            }
+        }
+        filters.filters.push(lzma_filter {
+            id: LZMA_VLI_UNKNOWN,
+            options: std::ptr::null_mut(),
+        });
+
+        Ok(filters)
+    }
+}
+
+impl Drop for RawFilters {
+    fn drop(&mut self) {
+        for filter in &self.decoded {
+            let mut filters = [
+                *filter,
+                liblzma_sys::lzma_filter {
+                    id: liblzma_sys::LZMA_VLI_UNKNOWN,
+                    options: std::ptr::null_mut(),
+                },
+            ];
+            unsafe { liblzma_sys::lzma_filters_free(filters.as_mut_ptr(), std::ptr::null()) };
+        }
+    }
+}
+
+/// Converts the return code of a liblzma-sys function into a result, as `liblzma` does.
+pub(crate) fn cvt(
+    ret: liblzma_sys::lzma_ret,
+) -> Result<liblzma::stream::Status, liblzma::stream::Error> {
+    use liblzma::stream::{Error, Status};
+
+    match ret {
+        liblzma_sys::LZMA_OK => Ok(Status::Ok),
+        liblzma_sys::LZMA_STREAM_END => Ok(Status::StreamEnd),
+        liblzma_sys::LZMA_GET_CHECK => Ok(Status::GetCheck),
+        liblzma_sys::LZMA_BUF_ERROR => Ok(Status::MemNeeded),
+        liblzma_sys::LZMA_NO_CHECK => Err(Error::NoCheck),
+        liblzma_sys::LZMA_UNSUPPORTED_CHECK => Err(Error::UnsupportedCheck),
+        liblzma_sys::LZMA_MEM_ERROR => Err(Error::Mem),
+        liblzma_sys::LZMA_MEMLIMIT_ERROR => Err(Error::MemLimit),
+        liblzma_sys::LZMA_FORMAT_ERROR => Err(Error::Format),
+        liblzma_sys::LZMA_OPTIONS_ERROR => Err(Error::Options),
+        liblzma_sys::LZMA_DATA_ERROR => Err(Error::Data),
+        _ => Err(Error::Program),
+    }
+}
+
 /// A builder for liblzma::LzmaOptions, so that it can be cloned
 #[derive(Default, Clone)]
 pub struct LzmaOptions {
@@ -269,6 +532,45 @@ impl LzmaOptions {
     }
 }
 
+impl LzmaOptions {
+    /// The raw options, for setting up an encoder through liblzma-sys.
+    pub(crate) fn to_raw(&self) -> Result<liblzma_sys::lzma_options_lzma, liblzma::stream::Error> {
+        // All zeroes is what `liblzma` starts its options from too.
+        let mut raw: liblzma_sys::lzma_options_lzma = unsafe { std::mem::zeroed() };
+        if let Some(preset) = self.preset {
+            if unsafe { liblzma_sys::lzma_lzma_preset(&mut raw, preset) } != 0 {
+                return Err(liblzma::stream::Error::Program);
+            }
+        }
+        if let Some(depth) = self.depth {
+            raw.depth = depth;
+        }
+        if let Some(dict_size) = self.dict_size {
+            raw.dict_size = dict_size;
+        }
+        if let Some(bits) = self.literal_context_bits {
+            raw.lc = bits;
+        }
+        if let Some(bits) = self.literal_position_bits {
+            raw.lp = bits;
+        }
+        if let Some(mf) = self.match_finder {
+            raw.mf = mf as _;
+        }
+        if let Some(mode) = self.mode {
+            raw.mode = mode as _;
+        }
+        if let Some(len) = self.nice_len {
+            raw.nice_len = len;
+        }
+        if let Some(bits) = self.position_bits {
+            raw.pb = bits;
+        }
+
+        Ok(raw)
+    }
+}
+
 impl TryFrom<&LzmaOptions> for liblzma::stream::LzmaOptions {
     type Error = liblzma::stream::Error;
 
@@ -307,6 +609,113 @@ impl TryFrom<&LzmaOptions> for liblzma::stream::LzmaOptions {
     }
 }
 
+/// The 5 byte LZMA1 properties which raw LZMA1 streams, such as ZIP method 14 or 7z entries,
+/// store out of band: the `lc`/`lp`/`pb` literal and position bits and the dictionary size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lzma1Props {
+    pub literal_context_bits: u32,
+    pub literal_position_bits: u32,
+    pub position_bits: u32,
+    pub dict_size: u32,
+}
+
+impl Lzma1Props {
+    /// Length of the encoded properties.
+    pub const LEN: usize = 5;
+
+    /// Length of the header preceding the compressed data of a ZIP method 14 entry.
+    pub const ZIP_HEADER_LEN: usize = 4 + Self::LEN;
+
+    /// Parses the encoded properties.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when `bytes` is shorter than [`Self::LEN`] or the properties byte is invalid.
+    pub fn parse(bytes: &[u8]) -> IoResult<Self> {
+        let bytes = bytes
+            .get(..Self::LEN)
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "truncated lzma1 properties"))?;
+
+        let mut props = u32::from(bytes[0]);
+        if props >= 9 * 5 * 5 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "invalid lzma1 properties byte",
+            ));
+        }
+        let literal_context_bits = props % 9;
+        props /= 9;
+
+        Ok(Self {
+            literal_context_bits,
+            literal_position_bits: props % 5,
+            position_bits: props / 5,
+            dict_size: u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]),
+        })
+    }
+
+    /// Parses the header of a ZIP method 14 entry: a two byte LZMA SDK version, the little endian
+    /// length of the properties and the properties themselves. The compressed data follows
+    /// [`Self::ZIP_HEADER_LEN`] bytes into the entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the header is truncated or does not contain valid properties.
+    pub fn parse_zip_header(bytes: &[u8]) -> IoResult<Self> {
+        let header = bytes
+            .get(..Self::ZIP_HEADER_LEN)
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "truncated zip lzma header"))?;
+
+        if usize::from(u16::from_le_bytes([header[2], header[3]])) != Self::LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "unexpected zip lzma properties length",
+            ));
+        }
+
+        Self::parse(&header[4..])
+    }
+
+    /// Encodes the properties.
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let props =
+            (self.position_bits * 5 + self.literal_position_bits) * 9 + self.literal_context_bits;
+        let dict_size = self.dict_size.to_le_bytes();
+
+        // `as` is fine here as the bits are at most 4, keeping `props` below 225.
+        [
+            props as u8,
+            dict_size[0],
+            dict_size[1],
+            dict_size[2],
+            dict_size[3],
+        ]
+    }
+
+    /// Encodes the header of a ZIP method 14 entry, see [`Self::parse_zip_header`].
+    pub fn to_zip_header(&self) -> [u8; Self::ZIP_HEADER_LEN] {
+        let mut header = [0; Self::ZIP_HEADER_LEN];
+        // LZMA SDK 9.20, which is what most zip writers report.
+        header[..2].copy_from_slice(&[9, 20]);
+        header[2..4].copy_from_slice(&(Self::LEN as u16).to_le_bytes());
+        header[4..].copy_from_slice(&self.to_bytes());
+        header
+    }
+
+    /// Returns the raw LZMA1 filter chain for these properties, usable for both encoding and
+    /// decoding.
+    pub fn filters(&self) -> LzmaFilters {
+        let options = LzmaOptions::default()
+            .preset(6)
+            .literal_context_bits(self.literal_context_bits)
+            .literal_position_bits(self.literal_position_bits)
+            .position_bits(self.position_bits)
+            .dict_size(self.dict_size);
+
+        LzmaFilters::default().add_filter(LzmaFilter::Lzma1(options))
+    }
+}
+
 #[cfg(feature = "xz-parallel")]
 #[derive(Default, Clone)]
 /// Used to build a clonable mt stream builder
@@ -380,6 +789,48 @@ impl MtStreamBuilder {
     }
 }
 
+#[cfg(feature = "xz-parallel")]
+impl MtStreamBuilder {
+    /// The raw options, for setting up an encoder through liblzma-sys, along with the filters
+    /// they point to.
+    pub(crate) fn to_raw(
+        &self,
+    ) -> Result<(liblzma_sys::lzma_mt, Option<RawFilters>), liblzma::stream::Error> {
+        // All zeroes with a single thread is what `liblzma` starts its options from too.
+        let mut raw: liblzma_sys::lzma_mt = unsafe { std::mem::zeroed() };
+        raw.threads = self.threads.map_or(1, NonZeroU32::get);
+        if let Some(block_size) = self.block_size {
+            raw.block_size = block_size;
+        }
+        if let Some(preset) = self.preset {
+            raw.preset = preset;
+        }
+        if let Some(check) = self.check {
+            raw.check = check as _;
+        }
+        if let Some(memlimit) = self.mem_limit_stop {
+            raw.memlimit_stop = memlimit;
+        }
+        if let Some(memlimit) = self.mem_limit_threading {
+            raw.memlimit_threading = memlimit;
+        }
+        if let Some(timeout) = self.timeout_ms {
+            raw.timeout = timeout;
+        }
+        // The filters are on the heap, so the pointer stays valid as they are moved.
+        let filters = self
+            .filters
+            .as_ref()
+            .map(RawFilters::try_from)
+            .transpose()?;
+        if let Some(filters) = &filters {
+            raw.filters = filters.as_ptr();
+        }
+
+        Ok((raw, filters))
+    }
+}
+
 #[cfg(feature = "xz-parallel")]
 impl TryFrom<&MtStreamBuilder> for liblzma::stream::MtStreamBuilder {
     type Error = liblzma::stream::Error;
@@ -414,3 +865,37 @@ impl TryFrom<&MtStreamBuilder> for liblzma::stream::MtStreamBuilder {
         Ok(mt)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Lzma1Props;
+
+    #[test]
+    fn lzma1_props_round_trip() {
+        // lc=3, lp=0, pb=2 with an 8 MiB dictionary, the LZMA SDK defaults.
+        let bytes = [0x5d, 0x00, 0x00, 0x80, 0x00];
+        let props = Lzma1Props::parse(&bytes).unwrap();
+
+        assert_eq!(
+            props,
+            Lzma1Props {
+                literal_context_bits: 3,
+                literal_position_bits: 0,
+                position_bits: 2,
+                dict_size: 8 << 20,
+            }
+        );
+        assert_eq!(props.to_bytes(), bytes);
+
+        let header = props.to_zip_header();
+        assert_eq!(&header[2..4], &[5, 0]);
+        assert_eq!(Lzma1Props::parse_zip_header(&header).unwrap(), props);
+    }
+
+    #[test]
+    fn lzma1_props_invalid() {
+        assert!(Lzma1Props::parse(&[0x5d, 0, 0, 0x80]).is_err());
+        assert!(Lzma1Props::parse(&[225, 0, 0, 0x80, 0]).is_err());
+        assert!(Lzma1Props::parse_zip_header(&[9, 20, 4, 0, 0x5d, 0, 0, 0x80, 0]).is_err());
+    }
+}
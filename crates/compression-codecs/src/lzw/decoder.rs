@@ -0,0 +1,287 @@
+use crate::{DecodeV2, DecoderLimits};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use std::io::{Error, ErrorKind, Result};
+
+const MAGIC: [u8; 2] = [0x1f, 0x9d];
+const HEADER_LEN: usize = 3;
+
+const BIT_MASK: u8 = 0x1f;
+const RESERVED_MASK: u8 = 0x60;
+const BLOCK_MODE: u8 = 0x80;
+
+const INIT_BITS: u32 = 9;
+const MAX_BITS: u32 = 16;
+
+/// Code which resets the table in block mode.
+const CLEAR: u32 = 256;
+/// First free code in block mode, after [`CLEAR`].
+const FIRST: u32 = 257;
+
+fn invalid(message: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// The table and code reader, set up once the header has been read.
+#[derive(Debug)]
+struct Lzw {
+    block_mode: bool,
+    max_bits: u32,
+    /// Number of codes the table can hold.
+    max_max_code: u32,
+    n_bits: u32,
+    /// Largest code representable with `n_bits`, the code size grows once the table outgrows it.
+    max_code: u32,
+    free_ent: u32,
+    old_code: Option<u32>,
+    fin_char: u8,
+    prefix: Vec<u16>,
+    suffix: Vec<u8>,
+
+    bits: u32,
+    bit_count: u32,
+    /// Codes read since the code size last changed, codes are written in groups of eight.
+    codes_in_group: u32,
+    /// Padding left to skip after the code size changed.
+    skip_bits: u32,
+}
+
+impl Lzw {
+    fn new(flags: u8, limits: &DecoderLimits) -> Result<Self> {
+        let max_bits = u32::from(flags & BIT_MASK);
+        if !(INIT_BITS..=MAX_BITS).contains(&max_bits) {
+            return Err(invalid("unsupported .Z maximum code size"));
+        }
+        if flags & RESERVED_MASK != 0 {
+            return Err(invalid("unsupported .Z flags"));
+        }
+        // Each code has a 2 byte prefix and a 1 byte suffix in the table.
+        limits.check(3 << max_bits)?;
+
+        let block_mode = flags & BLOCK_MODE != 0;
+        let max_max_code = 1 << max_bits;
+
+        Ok(Self {
+            block_mode,
+            max_bits,
+            max_max_code,
+            n_bits: INIT_BITS,
+            max_code: (1 << INIT_BITS) - 1,
+            free_ent: if block_mode { FIRST } else { 256 },
+            old_code: None,
+            fin_char: 0,
+            prefix: vec![0; max_max_code as usize],
+            suffix: (0..max_max_code).map(|code| code as u8).collect(),
+            bits: 0,
+            bit_count: 0,
+            codes_in_group: 0,
+            skip_bits: 0,
+        })
+    }
+
+    /// Buffers input until at least `n` bits are available, returns whether there are.
+    fn fill(&mut self, input: &mut PartialBuffer<&[u8]>, n: u32) -> bool {
+        while self.bit_count < n {
+            let Some(&byte) = input.unwritten().first() else {
+                return false;
+            };
+            input.advance(1);
+            self.bits |= u32::from(byte) << self.bit_count;
+            self.bit_count += 8;
+        }
+        true
+    }
+
+    /// Changes the code size, the encoder pads to the end of the current group of eight codes.
+    fn set_code_size(&mut self, n_bits: u32) {
+        self.skip_bits = (8 - self.codes_in_group % 8) % 8 * self.n_bits;
+        self.codes_in_group = 0;
+        self.n_bits = n_bits;
+        self.max_code = if n_bits == self.max_bits {
+            self.max_max_code
+        } else {
+            (1 << n_bits) - 1
+        };
+    }
+
+    /// Reads the next code, or returns `None` if more input is needed.
+    fn next_code(&mut self, input: &mut PartialBuffer<&[u8]>) -> Option<u32> {
+        while self.skip_bits > 0 {
+            if !self.fill(input, 1) {
+                return None;
+            }
+            let skip = self.skip_bits.min(self.bit_count);
+            self.bits >>= skip;
+            self.bit_count -= skip;
+            self.skip_bits -= skip;
+        }
+
+        if self.free_ent > self.max_code {
+            self.set_code_size(self.n_bits + 1);
+            return self.next_code(input);
+        }
+
+        if !self.fill(input, self.n_bits) {
+            return None;
+        }
+        let code = self.bits & ((1 << self.n_bits) - 1);
+        self.bits >>= self.n_bits;
+        self.bit_count -= self.n_bits;
+        self.codes_in_group += 1;
+        Some(code)
+    }
+
+    /// Decodes `code`, pushing its string onto `output` in reverse.
+    fn decode(&mut self, code: u32, output: &mut Vec<u8>) -> Result<()> {
+        let Some(old_code) = self.old_code else {
+            if code >= 256 {
+                return Err(invalid("corrupt .Z data"));
+            }
+            self.old_code = Some(code);
+            self.fin_char = code as u8;
+            output.push(self.fin_char);
+            return Ok(());
+        };
+
+        if code == CLEAR && self.block_mode {
+            // The next free code is taken by a placeholder entry when the next code is decoded.
+            self.free_ent = FIRST - 1;
+            self.set_code_size(INIT_BITS);
+            return Ok(());
+        }
+
+        let mut current = code;
+        if current >= self.free_ent {
+            // The code being defined by this very step, its string is the previous one followed
+            // by its own first character.
+            if current > self.free_ent {
+                return Err(invalid("corrupt .Z data"));
+            }
+            output.push(self.fin_char);
+            current = old_code;
+        }
+
+        while current >= 256 {
+            if output.len() >= self.max_max_code as usize {
+                return Err(invalid("corrupt .Z data"));
+            }
+            output.push(self.suffix[current as usize]);
+            current = u32::from(self.prefix[current as usize]);
+        }
+        self.fin_char = current as u8;
+        output.push(self.fin_char);
+
+        if self.free_ent < self.max_max_code {
+            self.prefix[self.free_ent as usize] = old_code as u16;
+            self.suffix[self.free_ent as usize] = self.fin_char;
+            self.free_ent += 1;
+        }
+        self.old_code = Some(code);
+
+        Ok(())
+    }
+}
+
+/// Decoder for the `.Z` format of the Unix `compress` utility: LZW with 9 to 16 bit codes,
+/// optionally in block mode where the encoder may reset the table with a `CLEAR` code.
+///
+/// The format has no end of stream marker or checksum, so the whole input is decoded as a single
+/// stream and truncation is only noticed when it splits a code.
+#[derive(Debug)]
+pub struct CompressZDecoder {
+    header: PartialBuffer<[u8; HEADER_LEN]>,
+    lzw: Option<Lzw>,
+    output: PartialBuffer<Vec<u8>>,
+    limits: DecoderLimits,
+}
+
+impl Default for CompressZDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompressZDecoder {
+    pub fn new() -> Self {
+        Self {
+            header: PartialBuffer::new([0; HEADER_LEN]),
+            lzw: None,
+            output: PartialBuffer::new(Vec::new()),
+            limits: DecoderLimits::new(),
+        }
+    }
+
+    /// Writes out as much of the decoded string as fits, returns whether all of it was written.
+    fn drain(&mut self, output: &mut WriteBuffer<'_>) -> bool {
+        output.copy_unwritten_from(&mut self.output);
+        self.output.unwritten().is_empty()
+    }
+}
+
+impl DecodeV2 for CompressZDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        *self = Self {
+            limits: self.limits,
+            ..Self::new()
+        };
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        loop {
+            if !self.drain(output) {
+                return Ok(false);
+            }
+
+            let lzw = match &mut self.lzw {
+                Some(lzw) => lzw,
+                None => {
+                    self.header.copy_unwritten_from(input);
+                    if !self.header.unwritten().is_empty() {
+                        return Ok(false);
+                    }
+
+                    let [a, b, flags] = *self.header.get_mut();
+                    if [a, b] != MAGIC {
+                        return Err(invalid("invalid .Z header"));
+                    }
+                    self.lzw.insert(Lzw::new(flags, &self.limits)?)
+                }
+            };
+
+            // There is no end of stream marker, so decoding only stops at the end of input.
+            let Some(code) = lzw.next_code(input) else {
+                return Ok(false);
+            };
+
+            let string = self.output.get_mut();
+            string.clear();
+            lzw.decode(code, string)?;
+            string.reverse();
+            self.output.reset();
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        Ok(self.drain(output))
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        // A whole byte left over can only be part of a code which was cut off.
+        match &self.lzw {
+            Some(lzw) if lzw.skip_bits > 0 || lzw.bit_count < 8 => Ok(self.drain(output)),
+            _ => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
+            )),
+        }
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.limits = limits;
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//! Functions compressing or decompressing a whole buffer at once.
+
+use crate::{Algorithm, AnyDecoder, AnyEncoder, DecodeV2, EncodeV2};
+use compression_core::{util::PartialBuffer, util::WriteBuffer, Level};
+use std::{
+    io::{Error, ErrorKind, Result},
+    sync::Arc,
+    task::{Wake, Waker},
+    thread::{self, Thread},
+};
+
+const MIN_RESERVE: usize = 4096;
+
+/// Runs `f` with the spare capacity of `output`, up to `limit` bytes in total, growing it first if
+/// it is full.
+fn write_with<T>(
+    output: &mut Vec<u8>,
+    limit: usize,
+    f: impl FnOnce(&mut WriteBuffer<'_>) -> Result<T>,
+) -> Result<T> {
+    if output.len() == output.capacity() {
+        output.reserve(output.len().max(MIN_RESERVE));
+    }
+
+    let len = output.len();
+    let spare = output.capacity().min(limit) - len;
+    let mut buffer = WriteBuffer::new_uninitialized(&mut output.spare_capacity_mut()[..spare]);
+    let res = f(&mut buffer);
+    let written = buffer.written_len();
+
+    // Safety: `WriteBuffer` only counts the bytes written to it, which it has initialized.
+    unsafe { output.set_len(len + written) };
+    res
+}
+
+/// Unparks the thread blocked on a codec waiting for its worker threads.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs the codec call `f` until it stops returning the [`WouldBlock`](ErrorKind::WouldBlock)
+/// error of codecs waiting for their worker threads, parking the thread in between. Once it has
+/// returned that error, `register` is given a waker to register with the codec before trying
+/// again.
+fn blocking<C: ?Sized, T>(
+    codec: &mut C,
+    register: fn(&mut C, &Waker),
+    mut f: impl FnMut(&mut C) -> Result<T>,
+) -> Result<T> {
+    let mut registered = false;
+    loop {
+        match f(codec) {
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                if registered {
+                    thread::park();
+                } else {
+                    register(
+                        codec,
+                        &Waker::from(Arc::new(ThreadWaker(thread::current()))),
+                    );
+                    registered = true;
+                }
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Compresses all of `input` with `encoder`, returning the compressed data.
+pub fn encode_all(encoder: &mut dyn EncodeV2, input: &[u8]) -> Result<Vec<u8>> {
+    let mut input = PartialBuffer::new(input);
+    let mut output = Vec::new();
+
+    while !input.unwritten().is_empty() {
+        write_with(&mut output, usize::MAX, |output| {
+            blocking(encoder, EncodeV2::register_waker, |encoder| {
+                encoder.encode(&mut input, output)
+            })
+        })?;
+    }
+    while !write_with(&mut output, usize::MAX, |output| {
+        blocking(encoder, EncodeV2::register_waker, |encoder| {
+            encoder.finish(output)
+        })
+    })? {}
+
+    Ok(output)
+}
+
+/// Decompresses the first member/frame of `input` with `decoder`, returning the decompressed
+/// data.
+///
+/// # Errors
+///
+/// Returns a [`FileTooLarge`](ErrorKind::FileTooLarge) error if the decompressed data is longer
+/// than `max_len` bytes, without decompressing more than that.
+pub fn decode_all(decoder: &mut dyn DecodeV2, input: &[u8], max_len: usize) -> Result<Vec<u8>> {
+    let mut input = PartialBuffer::new(input);
+    let mut output = Vec::new();
+    // One more byte than allowed is decompressed to tell whether the limit is exceeded.
+    let limit = max_len.saturating_add(1);
+
+    let mut done = false;
+    while !done && !input.unwritten().is_empty() && output.len() < limit {
+        done = write_with(&mut output, limit, |output| {
+            blocking(decoder, DecodeV2::register_waker, |decoder| {
+                decoder.decode(&mut input, output)
+            })
+        })?;
+    }
+    while output.len() < limit
+        && !write_with(&mut output, limit, |output| {
+            blocking(decoder, DecodeV2::register_waker, |decoder| {
+                decoder.finish(output)
+            })
+        })?
+    {}
+
+    if output.len() > max_len {
+        return Err(Error::new(
+            ErrorKind::FileTooLarge,
+            format!("decompressed data is longer than the limit of {max_len} bytes"),
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Compresses all of `input` with `algorithm` at the given level, returning the compressed
+/// data.
+///
+/// This uses the single-shot API of the codec when it has one, such as for zstd.
+///
+/// # Errors
+///
+/// Returns an [`Unsupported`](ErrorKind::Unsupported) error for algorithms which can only be
+/// decoded, see [`Algorithm::can_encode`].
+pub fn compress(algorithm: Algorithm, level: Level, input: &[u8]) -> Result<Vec<u8>> {
+    match algorithm {
+        #[cfg(feature = "zstd")]
+        Algorithm::Zstd => {
+            libzstd::bulk::compress(input, crate::zstd::params::CParameter::quality(level))
+        }
+        #[allow(unreachable_patterns)]
+        _ => encode_all(&mut AnyEncoder::new(algorithm, level)?, input),
+    }
+}
+
+/// Decompresses the first member/frame of `input` with `algorithm`, returning the decompressed
+/// data.
+///
+/// # Errors
+///
+/// Returns a [`FileTooLarge`](ErrorKind::FileTooLarge) error if the decompressed data is longer
+/// than `max_len` bytes, without decompressing more than that.
+pub fn decompress(algorithm: Algorithm, input: &[u8], max_len: usize) -> Result<Vec<u8>> {
+    decode_all(&mut AnyDecoder::new(algorithm), input, max_len)
+}
+
+#[cfg(test)]
+#[cfg(feature = "gzip")]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = b"hello world, hello world, hello world";
+
+    #[test]
+    fn round_trip() {
+        let compressed = compress(Algorithm::Gzip, Level::Default, DATA).unwrap();
+        assert_eq!(
+            decompress(Algorithm::Gzip, &compressed, DATA.len()).unwrap(),
+            DATA
+        );
+    }
+
+    #[test]
+    fn limit() {
+        let compressed = compress(Algorithm::Gzip, Level::Default, DATA).unwrap();
+        let err = decompress(Algorithm::Gzip, &compressed, DATA.len() - 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+    }
+
+    #[test]
+    fn truncated() {
+        let compressed = compress(Algorithm::Gzip, Level::Default, DATA).unwrap();
+        let err = decompress(Algorithm::Gzip, &compressed[..10], DATA.len()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
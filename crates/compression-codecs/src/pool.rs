@@ -0,0 +1,210 @@
+//! A small pool of worker threads shared by the codecs that compress or decompress independent
+//! pieces of their input in parallel.
+//!
+//! The codecs never wait for the workers: when they can make no progress until a worker is done
+//! they return a [`would_block`] error, and the workers wake the waker registered with
+//! [`WorkerPool::register_waker`] whenever they hand back a result.
+
+use compression_core::unshared::Unshared;
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    num::NonZeroU32,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, Receiver, Sender, SyncSender, TryRecvError},
+        Arc, Mutex,
+    },
+    task::Waker,
+    thread,
+};
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// The error codecs return while they can make no progress until one of their workers is done.
+pub(crate) fn would_block() -> io::Error {
+    io::Error::new(io::ErrorKind::WouldBlock, "waiting for the worker threads")
+}
+
+/// The waker of the task using a pool, woken by the workers whenever they hand back a result.
+#[derive(Clone, Default)]
+struct Notify(Arc<Mutex<Option<Waker>>>);
+
+impl Notify {
+    fn register(&self, waker: &Waker) {
+        if let Ok(mut current) = self.0.lock() {
+            if !current
+                .as_ref()
+                .is_some_and(|current| current.will_wake(waker))
+            {
+                *current = Some(waker.clone());
+            }
+        }
+    }
+
+    fn wake(&self) {
+        if let Ok(current) = self.0.lock() {
+            if let Some(waker) = &*current {
+                waker.wake_by_ref();
+            }
+        }
+    }
+}
+
+pub(crate) struct WorkerPool {
+    tasks: Option<Sender<Task>>,
+    workers: Vec<thread::JoinHandle<()>>,
+    notify: Notify,
+}
+
+impl fmt::Debug for WorkerPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkerPool")
+            .field("threads", &self.workers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl WorkerPool {
+    pub(crate) fn new(name: &str, threads: NonZeroU32) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads.get())
+            .map(|i| {
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("{name}-{i}"))
+                    .spawn(move || loop {
+                        let task = match receiver.lock() {
+                            Ok(receiver) => receiver.recv(),
+                            Err(_) => return,
+                        };
+                        let Ok(task) = task else { return };
+                        task();
+                    })
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self {
+            tasks: Some(sender),
+            workers,
+            notify: Notify::default(),
+        })
+    }
+
+    /// Sets the waker to wake when a task hands back a result or completes.
+    pub(crate) fn register_waker(&self, waker: &Waker) {
+        self.notify.register(waker);
+    }
+
+    /// Runs `f` on one of the workers, the results it sends are delivered to the returned
+    /// receiver, which disconnects once `f` returns. At most `capacity` results are queued, `f`
+    /// waits for them to be received before sending more.
+    pub(crate) fn spawn<T: Send + 'static>(
+        &self,
+        capacity: usize,
+        f: impl FnOnce(&Results<T>) + Send + 'static,
+    ) -> io::Result<Receiver<io::Result<T>>> {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let results = Results {
+            sender,
+            notify: self.notify.clone(),
+        };
+        let task: Task = Box::new(move || {
+            if panic::catch_unwind(AssertUnwindSafe(|| f(&results))).is_err() {
+                results.send(Err(io::Error::other("worker thread panicked")));
+            }
+            let Results { sender, notify } = results;
+            drop(sender);
+            notify.wake();
+        });
+
+        self.tasks
+            .as_ref()
+            .and_then(|tasks| tasks.send(task).ok())
+            .ok_or_else(|| io::Error::other("worker pool has shut down"))?;
+
+        Ok(receiver)
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // Closing the channel makes every worker return once its current task is done.
+        self.tasks = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Hands the results of a task spawned on a [`WorkerPool`] back to the codec.
+pub(crate) struct Results<T> {
+    sender: SyncSender<io::Result<T>>,
+    notify: Notify,
+}
+
+impl<T> Results<T> {
+    /// Sends `result`, waiting while the queue is full, returns false if nobody is interested in
+    /// the results anymore.
+    pub(crate) fn send(&self, result: io::Result<T>) -> bool {
+        let sent = self.sender.send(result).is_ok();
+        self.notify.wake();
+        sent
+    }
+}
+
+/// Results of tasks spawned on a [`WorkerPool`], handed back in the order they were spawned.
+pub(crate) struct Ordered<T> {
+    pending: Unshared<VecDeque<Receiver<io::Result<T>>>>,
+}
+
+impl<T> fmt::Debug for Ordered<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ordered").finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for Ordered<T> {
+    fn default() -> Self {
+        Self {
+            pending: Unshared::new(VecDeque::new()),
+        }
+    }
+}
+
+impl<T> Ordered<T> {
+    pub(crate) fn push(&mut self, receiver: Receiver<io::Result<T>>) {
+        self.pending.get_mut().push_back(receiver);
+    }
+
+    /// The number of tasks which have not been seen to complete yet.
+    pub(crate) fn len(&mut self) -> usize {
+        self.pending.get_mut().len()
+    }
+
+    pub(crate) fn is_empty(&mut self) -> bool {
+        self.pending.get_mut().is_empty()
+    }
+
+    /// Returns the next result of the oldest task which has not completed yet, if it has already
+    /// sent one.
+    pub(crate) fn try_next(&mut self) -> io::Result<Option<T>> {
+        let pending = self.pending.get_mut();
+        while let Some(receiver) = pending.front() {
+            match receiver.try_recv() {
+                Ok(result) => return result.map(Some),
+                Err(TryRecvError::Empty) => return Ok(None),
+                Err(TryRecvError::Disconnected) => {
+                    pending.pop_front();
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pending.get_mut().clear();
+    }
+}
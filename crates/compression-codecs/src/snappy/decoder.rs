@@ -0,0 +1,209 @@
+use super::{
+    masked_crc32c, CHECKSUM_LEN, CHUNK_COMPRESSED, CHUNK_HEADER_LEN, CHUNK_STREAM_IDENTIFIER,
+    CHUNK_UNCOMPRESSED, MAX_BLOCK_SIZE, STREAM_IDENTIFIER,
+};
+use crate::{DecodeV2, DecoderLimits};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use std::{
+    convert::TryInto,
+    io::{Error, ErrorKind, Result},
+};
+
+#[derive(Debug)]
+enum State {
+    Header,
+    Chunk { chunk_type: u8, len: usize },
+    Skip { remaining: usize },
+    Output,
+}
+
+fn invalid(message: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Snappy decoding stream, for the framing format.
+///
+/// The masked CRC-32C of every data chunk is verified. Skippable chunks are ignored, and streams
+/// may be concatenated since each one starts with a stream identifier.
+#[derive(Debug)]
+pub struct SnappyDecoder {
+    decoder: snap::raw::Decoder,
+    state: State,
+    header: PartialBuffer<[u8; CHUNK_HEADER_LEN]>,
+    chunk: Vec<u8>,
+    output: PartialBuffer<Vec<u8>>,
+    seen_identifier: bool,
+}
+
+impl Default for SnappyDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnappyDecoder {
+    pub fn new() -> Self {
+        Self {
+            decoder: snap::raw::Decoder::new(),
+            state: State::Header,
+            header: PartialBuffer::new([0; CHUNK_HEADER_LEN]),
+            chunk: Vec::new(),
+            output: PartialBuffer::new(Vec::with_capacity(MAX_BLOCK_SIZE)),
+            seen_identifier: false,
+        }
+    }
+
+    fn parse_header(&mut self) -> Result<State> {
+        let [chunk_type, a, b, c] = *self.header.get_mut();
+        let len = u32::from_le_bytes([a, b, c, 0]) as usize;
+        self.header.reset();
+
+        let max_len = match chunk_type {
+            CHUNK_STREAM_IDENTIFIER => STREAM_IDENTIFIER.len() - CHUNK_HEADER_LEN,
+            CHUNK_COMPRESSED | CHUNK_UNCOMPRESSED if !self.seen_identifier => {
+                return Err(invalid(
+                    "snappy stream does not start with a stream identifier",
+                ))
+            }
+            CHUNK_COMPRESSED => CHECKSUM_LEN + snap::raw::max_compress_len(MAX_BLOCK_SIZE),
+            CHUNK_UNCOMPRESSED => CHECKSUM_LEN + MAX_BLOCK_SIZE,
+            0x02..=0x7f => return Err(invalid("unsupported unskippable snappy chunk")),
+            _ if len == 0 => return Ok(State::Header),
+            _ => return Ok(State::Skip { remaining: len }),
+        };
+
+        if len > max_len || (chunk_type != CHUNK_STREAM_IDENTIFIER && len < CHECKSUM_LEN) {
+            return Err(invalid("invalid snappy chunk length"));
+        }
+
+        self.chunk.clear();
+        Ok(State::Chunk { chunk_type, len })
+    }
+
+    /// Handles a completely buffered chunk.
+    fn process_chunk(&mut self, chunk_type: u8) -> Result<State> {
+        if chunk_type == CHUNK_STREAM_IDENTIFIER {
+            if self.chunk[..] != STREAM_IDENTIFIER[CHUNK_HEADER_LEN..] {
+                return Err(invalid("invalid snappy stream identifier"));
+            }
+            self.seen_identifier = true;
+            return Ok(State::Header);
+        }
+
+        let (checksum, data) = self.chunk.split_at(CHECKSUM_LEN);
+        let checksum = u32::from_le_bytes(checksum.try_into().unwrap());
+
+        let output = self.output.get_mut();
+        output.clear();
+        if chunk_type == CHUNK_COMPRESSED {
+            let len = snap::raw::decompress_len(data)?;
+            if len > MAX_BLOCK_SIZE {
+                return Err(invalid("snappy chunk is too large"));
+            }
+            output.resize(len, 0);
+            self.decoder.decompress(data, output)?;
+        } else {
+            output.extend_from_slice(data);
+        }
+        self.output.reset();
+
+        if masked_crc32c(self.output.unwritten()) != checksum {
+            return Err(invalid("snappy checksum mismatch"));
+        }
+
+        Ok(State::Output)
+    }
+}
+
+impl DecodeV2 for SnappyDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.state = State::Header;
+        self.header.reset();
+        self.chunk.clear();
+        self.output.get_mut().clear();
+        self.output.reset();
+        self.seen_identifier = false;
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        loop {
+            self.state = match self.state {
+                State::Output => {
+                    output.copy_unwritten_from(&mut self.output);
+                    if !self.output.unwritten().is_empty() {
+                        return Ok(false);
+                    }
+                    State::Header
+                }
+
+                // There is no end of stream marker, so decoding only stops at the end of input.
+                _ if input.unwritten().is_empty() => return Ok(false),
+
+                State::Header => {
+                    self.header.copy_unwritten_from(input);
+                    if !self.header.unwritten().is_empty() {
+                        return Ok(false);
+                    }
+                    self.parse_header()?
+                }
+
+                State::Chunk { chunk_type, len } => {
+                    let read = input.unwritten().len().min(len - self.chunk.len());
+                    self.chunk.extend_from_slice(&input.unwritten()[..read]);
+                    input.advance(read);
+
+                    if self.chunk.len() == len {
+                        self.process_chunk(chunk_type)?
+                    } else {
+                        State::Chunk { chunk_type, len }
+                    }
+                }
+
+                State::Skip { remaining } => {
+                    let read = input.unwritten().len().min(remaining);
+                    input.advance(read);
+
+                    match remaining - read {
+                        0 => State::Header,
+                        remaining => State::Skip { remaining },
+                    }
+                }
+            };
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if let State::Output = self.state {
+            output.copy_unwritten_from(&mut self.output);
+            if !self.output.unwritten().is_empty() {
+                return Ok(false);
+            }
+            self.state = State::Header;
+        }
+
+        Ok(true)
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if !self.flush(output)? {
+            return Ok(false);
+        }
+
+        match self.state {
+            State::Header if self.header.written_len() == 0 => Ok(true),
+            _ => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "snappy stream is truncated",
+            )),
+        }
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        limits.check(MAX_BLOCK_SIZE as u64)
+    }
+}
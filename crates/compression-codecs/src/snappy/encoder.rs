@@ -0,0 +1,126 @@
+use super::{
+    masked_crc32c, CHECKSUM_LEN, CHUNK_COMPRESSED, CHUNK_HEADER_LEN, CHUNK_UNCOMPRESSED,
+    MAX_BLOCK_SIZE, STREAM_IDENTIFIER,
+};
+use crate::{EncodeV2, FlushMode};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use std::io::Result;
+
+/// Snappy encoding stream, producing the framing format.
+#[derive(Debug)]
+pub struct SnappyEncoder {
+    encoder: snap::raw::Encoder,
+    /// Uncompressed data for the next chunk.
+    input: Vec<u8>,
+    /// Encoded chunks waiting to be written out.
+    output: PartialBuffer<Vec<u8>>,
+}
+
+impl Default for SnappyEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnappyEncoder {
+    pub fn new() -> Self {
+        Self {
+            encoder: snap::raw::Encoder::new(),
+            input: Vec::with_capacity(MAX_BLOCK_SIZE),
+            output: PartialBuffer::new(STREAM_IDENTIFIER.to_vec()),
+        }
+    }
+
+    /// Writes out as much of the encoded chunks as fits, returns whether all of it was written.
+    fn drain(&mut self, output: &mut WriteBuffer<'_>) -> bool {
+        output.copy_unwritten_from(&mut self.output);
+        self.output.unwritten().is_empty()
+    }
+
+    /// Encodes the buffered input as a single chunk.
+    fn encode_chunk(&mut self) -> Result<()> {
+        let checksum = masked_crc32c(&self.input);
+
+        let chunk = self.output.get_mut();
+        chunk.clear();
+        chunk.resize(
+            CHUNK_HEADER_LEN + CHECKSUM_LEN + snap::raw::max_compress_len(self.input.len()),
+            0,
+        );
+        let compressed_len = self
+            .encoder
+            .compress(&self.input, &mut chunk[CHUNK_HEADER_LEN + CHECKSUM_LEN..])?;
+
+        // Like the reference implementation, only keep the compressed data if it saves at least
+        // an eighth of the input.
+        let (chunk_type, data_len) = if compressed_len >= self.input.len() - self.input.len() / 8 {
+            chunk.truncate(CHUNK_HEADER_LEN + CHECKSUM_LEN);
+            chunk.extend_from_slice(&self.input);
+            (CHUNK_UNCOMPRESSED, self.input.len())
+        } else {
+            chunk.truncate(CHUNK_HEADER_LEN + CHECKSUM_LEN + compressed_len);
+            (CHUNK_COMPRESSED, compressed_len)
+        };
+
+        let len = ((CHECKSUM_LEN + data_len) as u32).to_le_bytes();
+        chunk[..CHUNK_HEADER_LEN].copy_from_slice(&[chunk_type, len[0], len[1], len[2]]);
+        chunk[CHUNK_HEADER_LEN..CHUNK_HEADER_LEN + CHECKSUM_LEN]
+            .copy_from_slice(&checksum.to_le_bytes());
+        self.output.reset();
+
+        self.input.clear();
+        Ok(())
+    }
+}
+
+impl EncodeV2 for SnappyEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<()> {
+        loop {
+            if !self.drain(output) || input.unwritten().is_empty() {
+                return Ok(());
+            }
+
+            let len = input
+                .unwritten()
+                .len()
+                .min(MAX_BLOCK_SIZE - self.input.len());
+            self.input.extend_from_slice(&input.unwritten()[..len]);
+            input.advance(len);
+
+            if self.input.len() == MAX_BLOCK_SIZE {
+                self.encode_chunk()?;
+            }
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if self.drain(output) && !self.input.is_empty() {
+            self.encode_chunk()?;
+        }
+
+        Ok(self.drain(output))
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        // The framing format has no end of stream marker, so finishing is just flushing.
+        self.flush(output)
+    }
+
+    fn set_flush_mode(&mut self, _mode: FlushMode) -> Result<()> {
+        // Flushing ends the chunk, and chunks are compressed independently.
+        Ok(())
+    }
+
+    fn reinit(&mut self) -> Result<()> {
+        self.input.clear();
+        let output = self.output.get_mut();
+        output.clear();
+        output.extend_from_slice(&STREAM_IDENTIFIER);
+        self.output.reset();
+        Ok(())
+    }
+}
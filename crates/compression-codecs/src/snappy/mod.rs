@@ -0,0 +1,113 @@
+mod decoder;
+mod encoder;
+
+pub use self::{decoder::SnappyDecoder, encoder::SnappyEncoder};
+
+/// The stream identifier chunk which starts every framed snappy stream.
+const STREAM_IDENTIFIER: [u8; 10] = [0xff, 0x06, 0x00, 0x00, b's', b'N', b'a', b'P', b'p', b'Y'];
+
+/// Length of a chunk header: the chunk type and 24-bit little endian length.
+const CHUNK_HEADER_LEN: usize = 4;
+
+/// Length of the masked CRC-32C at the start of data chunks.
+const CHECKSUM_LEN: usize = 4;
+
+/// Maximum amount of uncompressed data in a single chunk.
+const MAX_BLOCK_SIZE: usize = 1 << 16;
+
+const CHUNK_COMPRESSED: u8 = 0x00;
+const CHUNK_UNCOMPRESSED: u8 = 0x01;
+const CHUNK_STREAM_IDENTIFIER: u8 = 0xff;
+
+/// Slicing-by-8 tables for CRC-32C: `CRC32C_TABLES[0]` is the usual byte-at-a-time table and
+/// `CRC32C_TABLES[k][i]` is the CRC of byte `i` followed by `k` zero bytes.
+const CRC32C_TABLES: [[u32; 256]; 8] = {
+    let mut tables = [[0; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+
+    let mut k = 1;
+    while k < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let prev = tables[k - 1][i];
+            tables[k][i] = tables[0][(prev & 0xff) as usize] ^ (prev >> 8);
+            i += 1;
+        }
+        k += 1;
+    }
+    tables
+};
+
+fn crc32c(data: &[u8]) -> u32 {
+    let t = &CRC32C_TABLES;
+    let mut crc = !0u32;
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let lo = crc ^ u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let hi = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        crc = t[7][(lo & 0xff) as usize]
+            ^ t[6][((lo >> 8) & 0xff) as usize]
+            ^ t[5][((lo >> 16) & 0xff) as usize]
+            ^ t[4][(lo >> 24) as usize]
+            ^ t[3][(hi & 0xff) as usize]
+            ^ t[2][((hi >> 8) & 0xff) as usize]
+            ^ t[1][((hi >> 16) & 0xff) as usize]
+            ^ t[0][(hi >> 24) as usize];
+    }
+
+    !chunks.remainder().iter().fold(crc, |crc, &byte| {
+        t[0][((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// The CRC-32C of `data`, masked as the framing format requires.
+fn masked_crc32c(data: &[u8]) -> u32 {
+    crc32c(data).rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crc32c;
+
+    #[test]
+    fn crc32c_check() {
+        // The check value of the CRC-32C parameters.
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+    }
+
+    #[test]
+    fn crc32c_unaligned_lengths() {
+        // Every length exercises a different split between the 8-byte and bytewise loops, each
+        // checked against the plain bitwise definition.
+        let data: Vec<u8> = (0..100u32).map(|i| (i * 37 % 251) as u8).collect();
+        for len in 0..data.len() {
+            let expected = !data[..len].iter().fold(!0u32, |mut crc, &byte| {
+                crc ^= u32::from(byte);
+                for _ in 0..8 {
+                    crc = if crc & 1 == 1 {
+                        (crc >> 1) ^ 0x82f6_3b78
+                    } else {
+                        crc >> 1
+                    };
+                }
+                crc
+            });
+            assert_eq!(crc32c(&data[..len]), expected, "length {len}");
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use super::index::{write_varint, XzBlock, FOOTER_MAGIC, HEADER_MAGIC, STREAM_HEADER_LEN};
+use crate::{lzma::params::LzmaDecoderParams, DecodeV2, DecoderLimits, Xz2Decoder};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use std::{
+    convert::{TryFrom, TryInto},
+    io::{Error, ErrorKind, Result},
+};
+
+/// Decodes a single block of an xz file, as located through its [`XzIndex`](super::XzIndex).
+///
+/// The block is wrapped in a stream header and an index and footer describing just that block, so
+/// that liblzma still validates its size and check.
+#[derive(Debug)]
+pub struct XzBlockDecoder {
+    inner: Xz2Decoder,
+    header: PartialBuffer<[u8; STREAM_HEADER_LEN]>,
+    footer: PartialBuffer<Vec<u8>>,
+    size: u64,
+    remaining: u64,
+}
+
+impl XzBlockDecoder {
+    pub fn new(block: &XzBlock) -> Self {
+        Self::with_memlimit(block, usize::MAX.try_into().unwrap())
+    }
+
+    pub fn with_memlimit(block: &XzBlock, mem_limit: u64) -> Self {
+        let flags = [0, block.check()];
+        let flags_crc = crc32fast::hash(&flags).to_le_bytes();
+
+        let mut header = [0; STREAM_HEADER_LEN];
+        header[..6].copy_from_slice(&HEADER_MAGIC);
+        header[6..8].copy_from_slice(&flags);
+        header[8..].copy_from_slice(&flags_crc);
+
+        let mut footer = vec![0];
+        write_varint(&mut footer, 1);
+        write_varint(&mut footer, block.unpadded_size());
+        write_varint(&mut footer, block.uncompressed_size());
+        footer.resize((footer.len() + 3) & !3, 0);
+        footer.extend_from_slice(&crc32fast::hash(&footer).to_le_bytes());
+
+        let backward_size = (footer.len() / 4 - 1) as u32;
+        let mut stream_footer = [0; STREAM_HEADER_LEN];
+        stream_footer[4..8].copy_from_slice(&backward_size.to_le_bytes());
+        stream_footer[8..10].copy_from_slice(&flags);
+        stream_footer[10..].copy_from_slice(&FOOTER_MAGIC);
+        let footer_crc = crc32fast::hash(&stream_footer[4..10]).to_le_bytes();
+        stream_footer[..4].copy_from_slice(&footer_crc);
+        footer.extend_from_slice(&stream_footer);
+
+        let params = LzmaDecoderParams::Stream {
+            mem_limit,
+            flags: 0,
+        };
+        Self {
+            inner: Xz2Decoder::try_from(params).unwrap(),
+            header: header.into(),
+            footer: footer.into(),
+            size: block.compressed_size(),
+            remaining: block.compressed_size(),
+        }
+    }
+
+    /// Everything consumed and produced so far, to detect when decoding stops making progress.
+    fn progress(&self, input: &PartialBuffer<&[u8]>, output: &WriteBuffer<'_>) -> [usize; 4] {
+        [
+            self.header.written_len(),
+            input.written_len(),
+            self.footer.written_len(),
+            output.written_len(),
+        ]
+    }
+}
+
+/// Passes the unwritten part of `buffer` to `inner`, returns whether the stream ended.
+fn feed(
+    inner: &mut Xz2Decoder,
+    buffer: &mut PartialBuffer<impl AsRef<[u8]>>,
+    output: &mut WriteBuffer<'_>,
+) -> Result<bool> {
+    let mut input = PartialBuffer::new(buffer.unwritten());
+    let done = inner.decode(&mut input, output)?;
+    let read = input.written_len();
+    buffer.advance(read);
+    Ok(done)
+}
+
+impl DecodeV2 for XzBlockDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.header.reset();
+        self.footer.reset();
+        self.remaining = self.size;
+        self.inner.reinit()
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<bool> {
+        loop {
+            let prior = self.progress(input, output);
+
+            let done = if !self.header.unwritten().is_empty() {
+                feed(&mut self.inner, &mut self.header, output)?
+            } else if self.remaining > 0 {
+                let len = input
+                    .unwritten()
+                    .len()
+                    .min(self.remaining.try_into().unwrap_or(usize::MAX));
+                let mut block = PartialBuffer::new(&input.unwritten()[..len]);
+                let done = self.inner.decode(&mut block, output)?;
+                let read = block.written_len();
+                input.advance(read);
+                self.remaining -= read as u64;
+                done
+            } else {
+                feed(&mut self.inner, &mut self.footer, output)?
+            };
+
+            if done {
+                return Ok(true);
+            }
+
+            if self.progress(input, output) == prior || output.has_no_spare_space() {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        self.inner.flush(output)
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if self.remaining > 0 {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "xz block is truncated",
+            ));
+        }
+
+        if self.decode(&mut PartialBuffer::new(&[]), output)? {
+            return Ok(true);
+        }
+        self.inner.finish(output)
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.inner.set_limits(limits)
+    }
+}
@@ -1,7 +1,10 @@
-use crate::{DecodeV2, DecodedSize, Xz2Decoder};
+use crate::{
+    lzma::params::{LzmaDecoderOptions, StreamCheck},
+    DecodeV2, DecodedSize, DecoderLimits, Xz2Decoder,
+};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use std::{
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     io::{Error, ErrorKind, Result},
 };
 
@@ -33,6 +36,18 @@ impl XzDecoder {
         }
     }
 
+    pub fn with_options(options: LzmaDecoderOptions) -> Self {
+        Self {
+            inner: Xz2Decoder::try_from(options.stream_params()).unwrap(),
+            skip_padding: None,
+        }
+    }
+
+    /// Returns the integrity check of the stream being decoded, see [`Xz2Decoder::check`].
+    pub fn check(&self) -> Option<StreamCheck> {
+        self.inner.check()
+    }
+
     #[cfg(feature = "xz-parallel")]
     pub fn parallel(threads: std::num::NonZeroU32, memlimit: u64) -> Self {
         Self {
@@ -90,6 +105,10 @@ impl DecodeV2 for XzDecoder {
         }
         self.inner.finish(output)
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.inner.set_limits(limits)
+    }
 }
 
 impl DecodedSize for XzDecoder {
@@ -1,27 +1,76 @@
-use crate::{EncodeV2, Xz2Encoder, Xz2FileFormat};
+use super::split::SplitEncoder;
+use crate::{
+    lzma::params::LzmaEncoderParams, xz2::xz2_level, EncodeV2, FlushMode, Xz2Encoder, Xz2FileFormat,
+};
 use compression_core::{
     util::{PartialBuffer, WriteBuffer},
     Level,
 };
-use std::io::Result;
+use std::{
+    convert::TryFrom,
+    io::{Error, ErrorKind, Result},
+};
+
+#[derive(Debug)]
+enum Inner {
+    Stream(Xz2Encoder),
+    Split(SplitEncoder),
+}
 
 /// Xz encoding stream
 #[derive(Debug)]
 pub struct XzEncoder {
-    inner: Xz2Encoder,
+    inner: Inner,
 }
 
 impl XzEncoder {
     pub fn new(level: Level) -> Self {
         Self {
-            inner: Xz2Encoder::new(Xz2FileFormat::Xz, level),
+            inner: Inner::Stream(Xz2Encoder::new(Xz2FileFormat::Xz, level)),
+        }
+    }
+
+    /// Creates a single-threaded encoder which starts a new block after every `block_size` bytes
+    /// of input, like `xz --block-size`.
+    ///
+    /// The sizes of every block are stored in its header, so the output can be decoded in
+    /// parallel, at the cost of buffering up to one compressed block in memory.
+    pub fn with_block_size(level: Level, block_size: std::num::NonZeroU64) -> Self {
+        Self {
+            inner: Inner::Split(SplitEncoder::new(xz2_level(level), block_size.get())),
+        }
+    }
+
+    /// Creates an encoder from the given parameters, which must describe an xz stream: one of
+    /// [`Easy`](LzmaEncoderParams::Easy), [`Stream`](LzmaEncoderParams::Stream) or, with the
+    /// `xz-parallel` feature, `MultiThread`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the parameters are not for an xz stream or are not supported by
+    /// liblzma.
+    pub fn with_params(params: LzmaEncoderParams) -> Result<Self> {
+        match params {
+            LzmaEncoderParams::Easy { .. } | LzmaEncoderParams::Stream { .. } => {}
+            #[cfg(feature = "xz-parallel")]
+            LzmaEncoderParams::MultiThread { .. } => {}
+            LzmaEncoderParams::Lzma { .. } | LzmaEncoderParams::Raw { .. } => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "parameters do not describe an xz stream",
+                ))
+            }
         }
+
+        Ok(Self {
+            inner: Inner::Stream(Xz2Encoder::try_from(params)?),
+        })
     }
 
     #[cfg(feature = "xz-parallel")]
     pub fn parallel(threads: std::num::NonZeroU32, level: Level) -> Self {
         Self {
-            inner: Xz2Encoder::xz_parallel(level, threads),
+            inner: Inner::Stream(Xz2Encoder::xz_parallel(level, threads)),
         }
     }
 }
@@ -32,14 +81,38 @@ impl EncodeV2 for XzEncoder {
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> Result<()> {
-        self.inner.encode(input, output)
+        match &mut self.inner {
+            Inner::Stream(inner) => inner.encode(input, output),
+            Inner::Split(inner) => inner.encode(input, output),
+        }
     }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
-        self.inner.flush(output)
+        match &mut self.inner {
+            Inner::Stream(inner) => inner.flush(output),
+            Inner::Split(inner) => inner.flush(output),
+        }
     }
 
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
-        self.inner.finish(output)
+        match &mut self.inner {
+            Inner::Stream(inner) => inner.finish(output),
+            Inner::Split(inner) => inner.finish(output),
+        }
+    }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()> {
+        match &mut self.inner {
+            Inner::Stream(inner) => inner.set_flush_mode(mode),
+            // Flushing always ends the block.
+            Inner::Split(_) => Ok(()),
+        }
+    }
+
+    fn reinit(&mut self) -> Result<()> {
+        match &mut self.inner {
+            Inner::Stream(inner) => inner.reinit(),
+            Inner::Split(inner) => inner.reinit(),
+        }
     }
 }
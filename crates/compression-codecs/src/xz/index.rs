@@ -0,0 +1,348 @@
+//! Parsing of the index at the end of every xz stream, which lists the blocks the stream is made
+//! of so they can be located and decoded independently.
+
+use std::{
+    convert::TryInto,
+    io::{Error, ErrorKind, Result},
+};
+
+pub(super) const HEADER_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+pub(super) const FOOTER_MAGIC: [u8; 2] = *b"YZ";
+/// Length of both the stream header and the stream footer.
+pub(super) const STREAM_HEADER_LEN: usize = 12;
+
+fn invalid(message: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn round_up_4(value: u64) -> u64 {
+    (value + 3) & !3
+}
+
+/// Reads a variable-length integer as used by the xz index.
+fn read_varint(input: &mut &[u8]) -> Result<u64> {
+    let mut value = 0;
+    for i in 0..9 {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| invalid("xz index is truncated"))?;
+        *input = rest;
+
+        value |= u64::from(byte & 0x7f) << (i * 7);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i != 0 {
+                return Err(invalid("xz index contains a non-minimal integer"));
+            }
+            return Ok(value);
+        }
+    }
+    Err(invalid("xz index contains an integer that is too large"))
+}
+
+pub(super) fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Validates the stream flags shared by the header and footer, returns the check type.
+fn check_flags(flags: [u8; 2]) -> Result<u8> {
+    if flags[0] != 0 || flags[1] & 0xf0 != 0 {
+        return Err(invalid("unsupported xz stream flags"));
+    }
+    Ok(flags[1])
+}
+
+fn check_crc(data: &[u8], crc: &[u8]) -> Result<()> {
+    if crc32fast::hash(data).to_le_bytes() != crc {
+        return Err(invalid("xz stream CRC32 mismatch"));
+    }
+    Ok(())
+}
+
+/// A block of an xz file, as listed in the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XzBlock {
+    compressed_offset: u64,
+    unpadded_size: u64,
+    uncompressed_offset: u64,
+    uncompressed_size: u64,
+    check: u8,
+}
+
+impl XzBlock {
+    /// Offset of the block header in the file.
+    pub fn compressed_offset(&self) -> u64 {
+        self.compressed_offset
+    }
+
+    /// Size of the block in the file, including its header, padding and check.
+    pub fn compressed_size(&self) -> u64 {
+        round_up_4(self.unpadded_size)
+    }
+
+    /// Offset of the first byte the block decodes to in the decompressed output.
+    pub fn uncompressed_offset(&self) -> u64 {
+        self.uncompressed_offset
+    }
+
+    /// Number of bytes the block decodes to.
+    pub fn uncompressed_size(&self) -> u64 {
+        self.uncompressed_size
+    }
+
+    pub(super) fn unpadded_size(&self) -> u64 {
+        self.unpadded_size
+    }
+
+    pub(super) fn check(&self) -> u8 {
+        self.check
+    }
+}
+
+/// The blocks of all streams in an xz file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XzIndex {
+    blocks: Vec<XzBlock>,
+}
+
+impl XzIndex {
+    /// All blocks of the file, in order.
+    pub fn blocks(&self) -> &[XzBlock] {
+        &self.blocks
+    }
+
+    /// Size of the whole file when decompressed.
+    pub fn uncompressed_size(&self) -> u64 {
+        self.blocks.last().map_or(0, |block| {
+            block.uncompressed_offset + block.uncompressed_size
+        })
+    }
+
+    /// Returns the block containing the given offset in the decompressed output.
+    pub fn block_at(&self, uncompressed_offset: u64) -> Option<&XzBlock> {
+        let i = self.blocks.partition_point(|block| {
+            block.uncompressed_offset + block.uncompressed_size <= uncompressed_offset
+        });
+        self.blocks.get(i)
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Footer {
+        end: u64,
+    },
+    Index {
+        end: u64,
+        flags: [u8; 2],
+        size: u64,
+    },
+    Header {
+        start: u64,
+        flags: [u8; 2],
+        blocks: Vec<XzBlock>,
+    },
+    Done,
+}
+
+/// Reads the index of an xz file, starting from its end and working backwards one stream at a
+/// time.
+///
+/// The parser does no IO itself: [`next_read`](Self::next_read) returns the part of the file
+/// which is needed next, and those bytes are then passed to [`feed`](Self::feed), until the whole
+/// index has been read.
+#[derive(Debug)]
+pub struct XzIndexParser {
+    state: State,
+    /// Blocks of the streams read so far, last stream first.
+    streams: Vec<Vec<XzBlock>>,
+}
+
+impl XzIndexParser {
+    /// Creates a parser for a file of the given size.
+    pub fn new(file_size: u64) -> Self {
+        Self {
+            state: State::Footer { end: file_size },
+            streams: Vec::new(),
+        }
+    }
+
+    /// Returns the offset and length of the bytes to pass to [`feed`](Self::feed) next, or `None`
+    /// once the index is complete.
+    pub fn next_read(&self) -> Result<Option<(u64, usize)>> {
+        let read = match self.state {
+            State::Footer { end } => {
+                let start = end
+                    .checked_sub(STREAM_HEADER_LEN as u64)
+                    .ok_or_else(|| invalid("xz file is truncated"))?;
+                (start, STREAM_HEADER_LEN)
+            }
+            State::Index { end, size, .. } => {
+                let start = end
+                    .checked_sub(STREAM_HEADER_LEN as u64 + size)
+                    .ok_or_else(|| invalid("xz file is truncated"))?;
+                let size = size
+                    .try_into()
+                    .map_err(|_| invalid("xz index is too large"))?;
+                (start, size)
+            }
+            State::Header { start, .. } => (start, STREAM_HEADER_LEN),
+            State::Done => return Ok(None),
+        };
+        Ok(Some(read))
+    }
+
+    /// Parses the bytes requested by [`next_read`](Self::next_read).
+    pub fn feed(&mut self, data: &[u8]) -> Result<()> {
+        self.state = match std::mem::replace(&mut self.state, State::Done) {
+            State::Footer { end } => {
+                let footer: &[u8; STREAM_HEADER_LEN] = data
+                    .try_into()
+                    .map_err(|_| invalid("xz stream footer is truncated"))?;
+
+                if footer[8..] == [0; 4] {
+                    // Stream padding, which comes in multiples of four null bytes.
+                    State::Footer { end: end - 4 }
+                } else {
+                    if footer[10..] != FOOTER_MAGIC {
+                        return Err(invalid("invalid xz stream footer"));
+                    }
+                    check_crc(&footer[4..10], &footer[..4])?;
+
+                    let flags = [footer[8], footer[9]];
+                    check_flags(flags)?;
+                    let backward_size = u32::from_le_bytes(footer[4..8].try_into().unwrap());
+
+                    State::Index {
+                        end,
+                        flags,
+                        size: (u64::from(backward_size) + 1) * 4,
+                    }
+                }
+            }
+
+            State::Index { end, flags, size } => {
+                let (blocks, blocks_size) = parse_index(data, flags)?;
+
+                let start = blocks_size
+                    .checked_add(size + 2 * STREAM_HEADER_LEN as u64)
+                    .and_then(|len| end.checked_sub(len))
+                    .ok_or_else(|| invalid("xz index does not match the file size"))?;
+
+                let mut compressed_offset = start + STREAM_HEADER_LEN as u64;
+                let blocks = blocks
+                    .into_iter()
+                    .map(|mut block| {
+                        block.compressed_offset = compressed_offset;
+                        compressed_offset += block.compressed_size();
+                        block
+                    })
+                    .collect();
+
+                State::Header {
+                    start,
+                    flags,
+                    blocks,
+                }
+            }
+
+            State::Header {
+                start,
+                flags,
+                blocks,
+            } => {
+                if data.len() != STREAM_HEADER_LEN || data[..6] != HEADER_MAGIC {
+                    return Err(invalid("invalid xz stream header"));
+                }
+                check_crc(&data[6..8], &data[8..])?;
+                if data[6..8] != flags {
+                    return Err(invalid("xz stream header and footer flags differ"));
+                }
+
+                self.streams.push(blocks);
+                if start == 0 {
+                    State::Done
+                } else {
+                    State::Footer { end: start }
+                }
+            }
+
+            State::Done => return Err(invalid("xz index has already been read")),
+        };
+
+        Ok(())
+    }
+
+    /// Returns the parsed index, errors if it has not been completely read yet.
+    pub fn finish(self) -> Result<XzIndex> {
+        if !matches!(self.state, State::Done) {
+            return Err(invalid("xz index has not been completely read"));
+        }
+
+        let mut uncompressed_offset = 0u64;
+        let blocks = self
+            .streams
+            .into_iter()
+            .rev()
+            .flatten()
+            .map(|mut block| {
+                block.uncompressed_offset = uncompressed_offset;
+                uncompressed_offset = uncompressed_offset
+                    .checked_add(block.uncompressed_size)
+                    .ok_or_else(|| invalid("xz file is too large"))?;
+                Ok(block)
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(XzIndex { blocks })
+    }
+}
+
+/// Parses the index of a single stream, returns its blocks and their total compressed size.
+fn parse_index(data: &[u8], flags: [u8; 2]) -> Result<(Vec<XzBlock>, u64)> {
+    let check = check_flags(flags)?;
+
+    if data.len() < 8 || data.len() % 4 != 0 {
+        return Err(invalid("invalid xz index size"));
+    }
+    let (content, crc) = data.split_at(data.len() - 4);
+    check_crc(content, crc)?;
+
+    let mut input = content;
+    if input.first() != Some(&0) {
+        return Err(invalid("invalid xz index"));
+    }
+    input = &input[1..];
+
+    let count = read_varint(&mut input)?;
+    let mut blocks = Vec::new();
+    let mut blocks_size = 0u64;
+    for _ in 0..count {
+        let unpadded_size = read_varint(&mut input)?;
+        let uncompressed_size = read_varint(&mut input)?;
+        if unpadded_size == 0 {
+            return Err(invalid("invalid xz index record"));
+        }
+
+        let block = XzBlock {
+            compressed_offset: 0,
+            unpadded_size,
+            uncompressed_offset: 0,
+            uncompressed_size,
+            check,
+        };
+        blocks_size = blocks_size
+            .checked_add(block.compressed_size())
+            .ok_or_else(|| invalid("invalid xz index record"))?;
+        blocks.push(block);
+    }
+
+    if input.len() >= 4 || input.iter().any(|&byte| byte != 0) {
+        return Err(invalid("invalid xz index padding"));
+    }
+
+    Ok((blocks, blocks_size))
+}
@@ -1,4 +1,12 @@
+mod block;
 mod decoder;
 mod encoder;
+mod index;
+mod split;
 
-pub use self::{decoder::XzDecoder, encoder::XzEncoder};
+pub use self::{
+    block::XzBlockDecoder,
+    decoder::XzDecoder,
+    encoder::XzEncoder,
+    index::{XzBlock, XzIndex, XzIndexParser},
+};
@@ -0,0 +1,245 @@
+use super::index::{write_varint, FOOTER_MAGIC, HEADER_MAGIC, STREAM_HEADER_LEN};
+use crate::{
+    lzma::params::{LzmaFilter, LzmaFilters, LzmaOptions},
+    xz2::BlockEncoder,
+    EncodeV2,
+};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use liblzma::stream::{Action, Check};
+use std::{
+    convert::TryFrom,
+    io::{Error, Result},
+};
+
+/// Stream flags declaring a CRC64 check, as used by [`Xz2Encoder`](crate::Xz2Encoder) for xz.
+const STREAM_FLAGS: [u8; 2] = [0x00, 0x04];
+
+fn pad_4(data: &mut Vec<u8>) {
+    data.resize((data.len() + 3) & !3, 0);
+}
+
+/// Encodes an xz stream whose input is split into blocks of a fixed uncompressed size, like
+/// `xz --block-size`.
+///
+/// Each block is compressed on its own by liblzma's block encoder, and its compressed and
+/// uncompressed sizes are recorded in its header, so a multi-threaded decoder can decode the
+/// blocks in parallel. This requires buffering the compressed data of the current block until it
+/// is complete.
+#[derive(Debug)]
+pub(super) struct SplitEncoder {
+    block_size: u64,
+    inner: BlockEncoder,
+    block: Vec<u8>,
+    block_uncompressed: u64,
+    /// The unpadded and uncompressed size of every completed block, for the index.
+    records: Vec<(u64, u64)>,
+    pending: PartialBuffer<Vec<u8>>,
+    finished: bool,
+}
+
+impl SplitEncoder {
+    pub(super) fn new(preset: u32, block_size: u64) -> Self {
+        let options = LzmaOptions::default().preset(preset);
+        let filters = LzmaFilters::default().add_filter(LzmaFilter::Lzma2(options));
+
+        Self {
+            inner: BlockEncoder::new(&filters, Check::Crc64).unwrap(),
+            block_size,
+            block: Vec::new(),
+            block_uncompressed: 0,
+            records: Vec::new(),
+            pending: PartialBuffer::new(Self::stream_header()),
+            finished: false,
+        }
+    }
+
+    fn stream_header() -> Vec<u8> {
+        let mut header = Vec::with_capacity(STREAM_HEADER_LEN);
+        header.extend_from_slice(&HEADER_MAGIC);
+        header.extend_from_slice(&STREAM_FLAGS);
+        header.extend_from_slice(&crc32fast::hash(&STREAM_FLAGS).to_le_bytes());
+        header
+    }
+
+    /// Writes out as much of the pending output as fits, returns whether all of it was written.
+    fn drain(&mut self, output: &mut WriteBuffer<'_>) -> bool {
+        output.copy_unwritten_from(&mut self.pending);
+        self.pending.unwritten().is_empty()
+    }
+
+    fn set_pending(&mut self, data: Vec<u8>) {
+        debug_assert!(self.pending.unwritten().is_empty());
+        self.pending = PartialBuffer::new(data);
+    }
+
+    /// Compresses part of `input` into the current block.
+    fn compress(&mut self, input: &mut PartialBuffer<&[u8]>) -> Result<()> {
+        let mut chunk = [0; 8 * 1024];
+        let mut output = WriteBuffer::new_initialized(&mut chunk);
+        self.inner.process(input, &mut output, Action::Run)?;
+        self.block.extend_from_slice(output.written());
+        Ok(())
+    }
+
+    /// Completes the current block and queues it for output.
+    fn end_block(&mut self) -> Result<()> {
+        let mut chunk = [0; 8 * 1024];
+        loop {
+            let mut output = WriteBuffer::new_initialized(&mut chunk);
+            let done =
+                self.inner
+                    .process(&mut PartialBuffer::new(&[]), &mut output, Action::Finish)?;
+            self.block.extend_from_slice(output.written());
+            if done {
+                break;
+            }
+        }
+
+        // The block encoder writes the padding and check after the data, the header records the
+        // sizes it ended up with.
+        let mut data = self.inner.header()?;
+        self.records
+            .push((self.inner.unpadded_size(), self.inner.uncompressed_size()));
+        data.append(&mut self.block);
+        self.set_pending(data);
+
+        self.inner.init()?;
+        self.block_uncompressed = 0;
+        Ok(())
+    }
+
+    /// Queues the index and stream footer for output.
+    fn end_stream(&mut self) {
+        let mut index = vec![0];
+        write_varint(&mut index, self.records.len() as u64);
+        for &(unpadded_size, uncompressed_size) in &self.records {
+            write_varint(&mut index, unpadded_size);
+            write_varint(&mut index, uncompressed_size);
+        }
+        pad_4(&mut index);
+        index.extend_from_slice(&crc32fast::hash(&index).to_le_bytes());
+
+        let backward_size = (index.len() / 4 - 1) as u32;
+        let mut footer = [0; STREAM_HEADER_LEN];
+        footer[4..8].copy_from_slice(&backward_size.to_le_bytes());
+        footer[8..10].copy_from_slice(&STREAM_FLAGS);
+        footer[10..].copy_from_slice(&FOOTER_MAGIC);
+        let footer_crc = crc32fast::hash(&footer[4..10]).to_le_bytes();
+        footer[..4].copy_from_slice(&footer_crc);
+        index.extend_from_slice(&footer);
+
+        self.set_pending(index);
+        self.finished = true;
+    }
+}
+
+impl EncodeV2 for SplitEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<()> {
+        if self.finished {
+            return Err(Error::other("encode after complete"));
+        }
+
+        loop {
+            if !self.drain(output) || input.unwritten().is_empty() {
+                return Ok(());
+            }
+
+            let remaining = self.block_size - self.block_uncompressed;
+            let len = input
+                .unwritten()
+                .len()
+                .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+            let data = &input.unwritten()[..len];
+            let mut block_input = PartialBuffer::new(data);
+            self.compress(&mut block_input)?;
+
+            let read = block_input.written_len();
+            self.block_uncompressed += read as u64;
+            input.advance(read);
+
+            if self.block_uncompressed == self.block_size {
+                self.end_block()?;
+            }
+        }
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        // Ending the current block early makes everything written so far decodable.
+        if self.drain(output) && !self.finished && self.block_uncompressed > 0 {
+            self.end_block()?;
+        }
+
+        Ok(self.drain(output))
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        loop {
+            if !self.drain(output) {
+                return Ok(false);
+            }
+
+            if self.finished {
+                return Ok(true);
+            }
+
+            if self.block_uncompressed > 0 {
+                self.end_block()?;
+            } else {
+                self.end_stream();
+            }
+        }
+    }
+
+    fn reinit(&mut self) -> Result<()> {
+        self.inner.init()?;
+        self.block.clear();
+        self.block_uncompressed = 0;
+        self.records.clear();
+        self.pending = PartialBuffer::new(Self::stream_header());
+        self.finished = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitEncoder;
+    use crate::EncodeV2;
+    use compression_core::util::{PartialBuffer, WriteBuffer};
+
+    fn encode(encoder: &mut SplitEncoder, input: &[u8]) -> Vec<u8> {
+        let mut buffer = vec![0; 64 * 1024];
+        let mut output = WriteBuffer::new_initialized(&mut buffer);
+        let mut input = PartialBuffer::new(input);
+        while !input.unwritten().is_empty() {
+            encoder.encode(&mut input, &mut output).unwrap();
+        }
+        while !encoder.finish(&mut output).unwrap() {}
+        output.written().to_vec()
+    }
+
+    #[test]
+    fn reinit_starts_a_new_stream() {
+        let input: Vec<u8> = (0..10_000u32).flat_map(|i| (i % 7).to_le_bytes()).collect();
+        let mut encoder = SplitEncoder::new(1, 4096);
+
+        let compressed = encode(&mut encoder, &input);
+        let mut decompressed = Vec::with_capacity(input.len());
+        liblzma::stream::Stream::new_stream_decoder(u64::MAX, 0)
+            .unwrap()
+            .process_vec(
+                &compressed,
+                &mut decompressed,
+                liblzma::stream::Action::Finish,
+            )
+            .unwrap();
+        assert_eq!(decompressed, input);
+
+        encoder.reinit().unwrap();
+        assert_eq!(encode(&mut encoder, &input), compressed);
+    }
+}
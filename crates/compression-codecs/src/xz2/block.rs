@@ -0,0 +1,80 @@
+use super::stream::EncoderStream;
+use crate::lzma::params::{cvt, LzmaFilters, RawFilters};
+use compression_core::util::{PartialBuffer, WriteBuffer};
+use liblzma::stream::{Action, Check, Error};
+use std::{convert::TryFrom, fmt, io};
+
+/// An `lzma_block_encoder`, which encodes the data of a single xz block followed by its check,
+/// leaving the block header to be written once the sizes of the block are known.
+pub(crate) struct BlockEncoder {
+    stream: EncoderStream,
+    /// Boxed, as liblzma records the sizes and check of the block in it once the block ends.
+    block: Box<liblzma_sys::lzma_block>,
+    filters: RawFilters,
+    check: Check,
+}
+
+impl fmt::Debug for BlockEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockEncoder").finish_non_exhaustive()
+    }
+}
+
+// Safety: the block and filters are only used through `&mut self`, like the stream.
+unsafe impl Send for BlockEncoder {}
+unsafe impl Sync for BlockEncoder {}
+
+impl BlockEncoder {
+    pub(crate) fn new(filters: &LzmaFilters, check: Check) -> Result<Self, Error> {
+        let mut encoder = Self {
+            stream: EncoderStream::empty(),
+            // All zeroes is a valid, if empty, block.
+            block: Box::new(unsafe { std::mem::zeroed() }),
+            filters: RawFilters::try_from(filters)?,
+            check,
+        };
+        encoder.init()?;
+        Ok(encoder)
+    }
+
+    /// Sets the encoder up to encode a new block.
+    pub(crate) fn init(&mut self) -> Result<(), Error> {
+        *self.block = unsafe { std::mem::zeroed() };
+        self.block.check = self.check as _;
+        self.block.compressed_size = liblzma_sys::LZMA_VLI_UNKNOWN;
+        self.block.uncompressed_size = liblzma_sys::LZMA_VLI_UNKNOWN;
+        // liblzma only reads the filters.
+        self.block.filters = self.filters.as_ptr().cast_mut();
+
+        cvt(unsafe { liblzma_sys::lzma_block_encoder(&mut self.stream.raw, &mut *self.block) })
+            .map(|_| ())
+    }
+
+    /// Encodes `input` with `action`, returns whether the block has ended.
+    pub(crate) fn process(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+        action: Action,
+    ) -> io::Result<bool> {
+        super::process_stream(&mut self.stream, input, output, action)
+    }
+
+    /// The header of the block which has ended, recording its compressed and uncompressed sizes.
+    pub(crate) fn header(&mut self) -> Result<Vec<u8>, Error> {
+        cvt(unsafe { liblzma_sys::lzma_block_header_size(&mut *self.block) })?;
+        let mut header = vec![0; self.block.header_size as usize];
+        cvt(unsafe { liblzma_sys::lzma_block_header_encode(&*self.block, header.as_mut_ptr()) })?;
+        Ok(header)
+    }
+
+    /// The size of the block which has ended without its padding, as recorded in the index. Only
+    /// valid after [`header`](Self::header).
+    pub(crate) fn unpadded_size(&self) -> u64 {
+        unsafe { liblzma_sys::lzma_block_unpadded_size(&*self.block) }
+    }
+
+    pub(crate) fn uncompressed_size(&self) -> u64 {
+        self.block.uncompressed_size
+    }
+}
@@ -1,16 +1,74 @@
-use crate::{lzma::params::LzmaDecoderParams, xz2::process_stream, DecodeV2, DecodedSize};
+use crate::{
+    lzma::params::{LzmaDecoderParams, StreamCheck},
+    xz2::process_stream,
+    DecodeV2, DecodedSize, DecoderLimits,
+};
 use compression_core::util::{PartialBuffer, WriteBuffer};
-use liblzma::stream::{Action, Stream};
+use liblzma::stream::{Action, Check, Stream};
 use std::{
     convert::TryFrom,
     fmt,
     io::{self, Cursor},
 };
 
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+const LZIP_MAGIC: [u8; 4] = *b"LZIP";
+
+/// Works out the integrity check a stream uses from the first bytes of its header.
+#[derive(Debug)]
+enum CheckState {
+    Header { bytes: [u8; 8], len: usize },
+    Known(StreamCheck),
+}
+
+impl CheckState {
+    fn new(params: &LzmaDecoderParams) -> Self {
+        match params {
+            // Neither raw streams nor the legacy lzma format have an integrity check.
+            LzmaDecoderParams::Lzma { .. } | LzmaDecoderParams::Raw { .. } => {
+                Self::Known(StreamCheck::Supported(Check::None))
+            }
+            _ => Self::Header {
+                bytes: [0; 8],
+                len: 0,
+            },
+        }
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        let Self::Header { bytes, len } = self else {
+            return;
+        };
+
+        let new_len = (*len + input.len()).min(bytes.len());
+        bytes[*len..new_len].copy_from_slice(&input[..new_len - *len]);
+        *len = new_len;
+
+        if *len >= LZIP_MAGIC.len() && bytes[..LZIP_MAGIC.len()] == LZIP_MAGIC {
+            *self = Self::Known(StreamCheck::Supported(Check::Crc32));
+        } else if *len >= XZ_MAGIC.len() && bytes[..XZ_MAGIC.len()] != XZ_MAGIC {
+            // The only other format the auto decoder accepts is lzma, without a check.
+            *self = Self::Known(StreamCheck::Supported(Check::None));
+        } else if *len == bytes.len() {
+            // The check ID is in the low 4 bits of the second stream flags byte.
+            let check = match bytes[7] & 0x0f {
+                0x00 => StreamCheck::Supported(Check::None),
+                0x01 => StreamCheck::Supported(Check::Crc32),
+                0x04 => StreamCheck::Supported(Check::Crc64),
+                0x0a => StreamCheck::Supported(Check::Sha256),
+                id => StreamCheck::Unsupported(id),
+            };
+            *self = Self::Known(check);
+        }
+    }
+}
+
 /// Xz2 decoding stream
 pub struct Xz2Decoder {
     stream: Stream,
     params: LzmaDecoderParams,
+    check: CheckState,
+    limits: DecoderLimits,
 }
 
 impl fmt::Debug for Xz2Decoder {
@@ -24,7 +82,13 @@ impl TryFrom<LzmaDecoderParams> for Xz2Decoder {
 
     fn try_from(params: LzmaDecoderParams) -> Result<Self, Self::Error> {
         let stream = Stream::try_from(&params)?;
-        Ok(Self { stream, params })
+        let check = CheckState::new(&params);
+        Ok(Self {
+            stream,
+            params,
+            check,
+            limits: DecoderLimits::new(),
+        })
     }
 }
 
@@ -52,11 +116,34 @@ impl Xz2Decoder {
 
         Self::try_from(params).unwrap()
     }
+
+    /// Returns the integrity check of the stream being decoded, once enough of its header has
+    /// been read.
+    ///
+    /// Returns `None` before that, including after [`reinit`](DecodeV2::reinit) until the header
+    /// of the next member has been read. Only the header of the first stream is inspected when
+    /// decoding concatenated streams.
+    pub fn check(&self) -> Option<StreamCheck> {
+        match self.check {
+            CheckState::Header { .. } => None,
+            CheckState::Known(check) => Some(check),
+        }
+    }
+
+    /// Replaces the error liblzma returns when the stream needs more memory than its limit.
+    fn limit_error(&self, err: io::Error) -> io::Error {
+        match err.kind() {
+            io::ErrorKind::OutOfMemory => self.limits.error(None),
+            _ => err,
+        }
+    }
 }
 
 impl DecodeV2 for Xz2Decoder {
     fn reinit(&mut self) -> io::Result<()> {
-        *self = Self::try_from(self.params.clone())?;
+        self.stream = Stream::try_from(&self.params)?;
+        // The next member has a header of its own.
+        self.check = CheckState::new(&self.params);
         Ok(())
     }
 
@@ -65,7 +152,10 @@ impl DecodeV2 for Xz2Decoder {
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> io::Result<bool> {
-        process_stream(&mut self.stream, input, output, Action::Run)
+        let prior_read = input.written_len();
+        let done = process_stream(&mut self.stream, input, output, Action::Run);
+        self.check.update(&input.written()[prior_read..]);
+        done.map_err(|err| self.limit_error(err))
     }
 
     fn flush(&mut self, _output: &mut WriteBuffer<'_>) -> io::Result<bool> {
@@ -80,6 +170,15 @@ impl DecodeV2 for Xz2Decoder {
             output,
             Action::Finish,
         )
+        .map_err(|err| self.limit_error(err))
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> io::Result<()> {
+        let mem_limit = limits.max_memory.unwrap_or(u64::MAX);
+        self.params.set_mem_limit(mem_limit)?;
+        self.stream.set_memlimit(mem_limit)?;
+        self.limits = limits;
+        Ok(())
     }
 }
 
@@ -94,15 +193,50 @@ impl DecodedSize for Xz2Decoder {
 mod tests {
     use std::convert::TryFrom;
 
+    use compression_core::util::{PartialBuffer, WriteBuffer};
+    use liblzma::stream::{Action, Check, Stream};
+
     use crate::{
-        lzma::params::{LzmaDecoderParams, LzmaFilter, LzmaFilters, LzmaOptions},
-        Xz2Decoder,
+        lzma::params::{LzmaDecoderParams, LzmaFilter, LzmaFilters, LzmaOptions, StreamCheck},
+        DecodeV2, Xz2Decoder,
     };
 
+    fn compress_with_check(check: Check) -> Vec<u8> {
+        let mut stream = Stream::new_easy_encoder(1, check).unwrap();
+        let mut output = Vec::with_capacity(1024);
+        stream
+            .process_vec(&[1, 2, 3, 4, 5, 6], &mut output, Action::Finish)
+            .unwrap();
+        output
+    }
+
     #[test]
     fn test_lzma_decoder_from_params() {
         let filters = LzmaFilters::default().add_filter(LzmaFilter::Lzma2(LzmaOptions::default()));
         let params = LzmaDecoderParams::Raw { filters };
         Xz2Decoder::try_from(params).unwrap();
     }
+
+    #[test]
+    fn test_reinit_clears_check() {
+        let mut decoder = Xz2Decoder::new(u64::MAX);
+        let mut buffer = [0; 64];
+
+        for check in [Check::Sha256, Check::Crc32] {
+            assert!(decoder.check().is_none());
+
+            let compressed = compress_with_check(check);
+            let mut input = PartialBuffer::new(&compressed[..]);
+            let mut output = WriteBuffer::new_initialized(&mut buffer);
+            assert!(decoder.decode(&mut input, &mut output).unwrap());
+            assert_eq!(output.written(), &[1, 2, 3, 4, 5, 6]);
+
+            assert!(matches!(
+                decoder.check(),
+                Some(StreamCheck::Supported(found)) if found as u32 == check as u32
+            ));
+
+            decoder.reinit().unwrap();
+        }
+    }
 }
@@ -2,7 +2,7 @@ use compression_core::{
     util::{PartialBuffer, WriteBuffer},
     Level,
 };
-use liblzma::stream::{Action, Check, Stream};
+use liblzma::stream::{Action, Check};
 use std::{
     convert::{TryFrom, TryInto},
     fmt, io,
@@ -10,14 +10,15 @@ use std::{
 
 use crate::{
     lzma::params::{LzmaEncoderParams, LzmaOptions},
-    xz2::process_stream,
-    EncodeV2, Xz2FileFormat,
+    xz2::{process_stream, stream::EncoderStream},
+    EncodeV2, FlushMode, Xz2FileFormat,
 };
 
 /// Xz2 encoding stream
 pub struct Xz2Encoder {
-    stream: Stream,
+    stream: EncoderStream,
     params: LzmaEncoderParams,
+    flush_mode: FlushMode,
 }
 
 impl fmt::Debug for Xz2Encoder {
@@ -30,15 +31,16 @@ impl TryFrom<LzmaEncoderParams> for Xz2Encoder {
     type Error = liblzma::stream::Error;
 
     fn try_from(params: LzmaEncoderParams) -> Result<Self, Self::Error> {
-        let stream = Stream::try_from(&params)?;
+        let stream = EncoderStream::new(&params)?;
         Ok(Self {
             stream,
             params: params.clone(),
+            flush_mode: FlushMode::Sync,
         })
     }
 }
 
-fn xz2_level(level: Level) -> u32 {
+pub(crate) fn xz2_level(level: Level) -> u32 {
     match level {
         Level::Fastest => 0,
         Level::Best => 9,
@@ -47,6 +49,21 @@ fn xz2_level(level: Level) -> u32 {
     }
 }
 
+/// Dictionary size used by the liblzma preset, these are all powers of two.
+#[cfg(any(feature = "lzip", feature = "zip"))]
+pub(crate) fn xz2_dictionary_size(preset: u32) -> u32 {
+    match preset {
+        0 => 1 << 18,
+        1 => 1 << 20,
+        2 => 1 << 21,
+        3 | 4 => 1 << 22,
+        5 | 6 => 1 << 23,
+        7 => 1 << 24,
+        8 => 1 << 25,
+        _ => 1 << 26,
+    }
+}
+
 impl Xz2Encoder {
     pub fn new(format: Xz2FileFormat, level: Level) -> Self {
         let preset = xz2_level(level);
@@ -90,10 +107,12 @@ impl EncodeV2 for Xz2Encoder {
     }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
-        let action = match &self.params {
+        let action = match (&self.params, self.flush_mode) {
+            (_, FlushMode::BlockEnd) => Action::FullBarrier,
             // Multi-threaded streams don't support SyncFlush, use FullFlush instead
             #[cfg(feature = "xz-parallel")]
-            LzmaEncoderParams::MultiThread { builder: _ } => Action::FullFlush,
+            (LzmaEncoderParams::MultiThread { builder: _ }, _) => Action::FullFlush,
+            (_, FlushMode::Full) => Action::FullFlush,
             _ => Action::SyncFlush,
         };
 
@@ -113,4 +132,72 @@ impl EncodeV2 for Xz2Encoder {
             Action::Finish,
         )
     }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> io::Result<()> {
+        // Only the xz format has blocks to end.
+        match (&self.params, mode) {
+            (_, FlushMode::Sync)
+            | (LzmaEncoderParams::Easy { .. } | LzmaEncoderParams::Stream { .. }, _) => {}
+            #[cfg(feature = "xz-parallel")]
+            (LzmaEncoderParams::MultiThread { .. }, _) => {}
+            _ => return Err(mode.unsupported()),
+        }
+
+        self.flush_mode = mode;
+        Ok(())
+    }
+
+    fn reinit(&mut self) -> io::Result<()> {
+        self.stream.init(&self.params)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use compression_core::util::{PartialBuffer, WriteBuffer};
+    use liblzma::stream::{Action, Check, Stream};
+
+    use crate::{
+        lzma::params::{LzmaEncoderParams, LzmaFilter, LzmaFilters, LzmaOptions},
+        EncodeV2, Xz2Encoder,
+    };
+
+    fn encode(encoder: &mut Xz2Encoder, input: &[u8]) -> Vec<u8> {
+        let mut buffer = vec![0; 4096];
+        let mut output = WriteBuffer::new_initialized(&mut buffer);
+        encoder
+            .encode(&mut PartialBuffer::new(input), &mut output)
+            .unwrap();
+        assert!(encoder.finish(&mut output).unwrap());
+        output.written().to_vec()
+    }
+
+    #[test]
+    fn test_reinit_matches_liblzma() {
+        let input: Vec<u8> = (0..1000u32).flat_map(|i| (i % 7).to_le_bytes()).collect();
+        let filters = LzmaFilters::default()
+            .add_filter(LzmaFilter::X86(None))
+            .add_filter(LzmaFilter::Delta(Some(vec![3])))
+            .add_filter(LzmaFilter::Lzma2(
+                LzmaOptions::default().preset(1).dict_size(1 << 16),
+            ));
+        let params = LzmaEncoderParams::Stream {
+            filters,
+            check: Check::Crc32,
+        };
+
+        let mut expected = Vec::with_capacity(4096);
+        Stream::try_from(&params)
+            .unwrap()
+            .process_vec(&input, &mut expected, Action::Finish)
+            .unwrap();
+
+        let mut encoder = Xz2Encoder::try_from(params).unwrap();
+        assert_eq!(encode(&mut encoder, &input), expected);
+        encoder.reinit().unwrap();
+        assert_eq!(encode(&mut encoder, &input), expected);
+    }
 }
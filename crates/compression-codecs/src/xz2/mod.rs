@@ -1,5 +1,8 @@
+#[cfg(feature = "xz")]
+mod block;
 mod decoder;
 mod encoder;
+mod stream;
 
 #[derive(Debug)]
 pub enum Xz2FileFormat {
@@ -7,15 +10,54 @@ pub enum Xz2FileFormat {
     Lzma,
 }
 
+#[cfg(feature = "xz")]
+pub(crate) use self::block::BlockEncoder;
+#[cfg(any(feature = "lzip", feature = "zip"))]
+pub(crate) use self::encoder::xz2_dictionary_size;
+#[cfg(any(feature = "lzip", feature = "xz", feature = "zip"))]
+pub(crate) use self::encoder::xz2_level;
 pub use self::{decoder::Xz2Decoder, encoder::Xz2Encoder};
 
 use compression_core::util::{PartialBuffer, WriteBuffer};
-use liblzma::stream::{Action, Status, Stream};
-use std::io;
+use liblzma::stream::{Action, Error, Status, Stream};
+use std::{io, mem::MaybeUninit};
+
+/// The parts of liblzma's `Stream` which `process_stream` uses.
+trait LzmaStream {
+    fn total_in(&self) -> u64;
+
+    fn total_out(&self) -> u64;
+
+    fn process_uninit(
+        &mut self,
+        input: &[u8],
+        output: &mut [MaybeUninit<u8>],
+        action: Action,
+    ) -> Result<Status, Error>;
+}
+
+impl LzmaStream for Stream {
+    fn total_in(&self) -> u64 {
+        self.total_in()
+    }
+
+    fn total_out(&self) -> u64 {
+        self.total_out()
+    }
+
+    fn process_uninit(
+        &mut self,
+        input: &[u8],
+        output: &mut [MaybeUninit<u8>],
+        action: Action,
+    ) -> Result<Status, Error> {
+        self.process_uninit(input, output, action)
+    }
+}
 
 /// Return `Ok(true)` if stream ends.
 fn process_stream(
-    stream: &mut Stream,
+    stream: &mut impl LzmaStream,
     input: &mut PartialBuffer<&[u8]>,
     output: &mut WriteBuffer<'_>,
     action: Action,
@@ -24,8 +66,12 @@ fn process_stream(
     let previous_out = stream.total_out() as usize;
 
     // Safety: We **trust** liblzma to not write uninitialized bytes into the buffer
-    let status =
-        stream.process_uninit(input.unwritten(), unsafe { output.unwritten_mut() }, action)?;
+    let status = stream
+        .process_uninit(input.unwritten(), unsafe { output.unwritten_mut() }, action)
+        .map_err(|err| match err {
+            liblzma::stream::Error::MemLimit => io::ErrorKind::OutOfMemory.into(),
+            err => io::Error::from(err),
+        })?;
 
     input.advance(stream.total_in() as usize - previous_in);
     // Safety: We **trust** liblzma to write bytes into the buffer properly
@@ -0,0 +1,91 @@
+use crate::lzma::params::{cvt, LzmaEncoderParams, RawFilters};
+use liblzma::stream::{Action, Error, Status};
+use std::{convert::TryFrom, mem::MaybeUninit};
+
+/// An encoding `lzma_stream` driven through liblzma-sys, as liblzma's `Stream` can only be set up
+/// once.
+///
+/// Setting it up again for a new stream keeps the coder liblzma allocated for the last one, along
+/// with its dictionary and buffers, as long as the new stream uses the same kind of coder.
+pub(super) struct EncoderStream {
+    pub(super) raw: liblzma_sys::lzma_stream,
+}
+
+// Safety: liblzma streams are not tied to the thread they are used on, as `liblzma` relies on too.
+unsafe impl Send for EncoderStream {}
+unsafe impl Sync for EncoderStream {}
+
+impl EncoderStream {
+    pub(super) fn new(params: &LzmaEncoderParams) -> Result<Self, Error> {
+        let mut stream = Self::empty();
+        stream.init(params)?;
+        Ok(stream)
+    }
+
+    pub(super) fn empty() -> Self {
+        // All zeroes is `LZMA_STREAM_INIT`.
+        Self {
+            raw: unsafe { std::mem::zeroed() },
+        }
+    }
+
+    /// Sets the stream up to encode a new stream with `params`.
+    pub(super) fn init(&mut self, params: &LzmaEncoderParams) -> Result<(), Error> {
+        // liblzma copies the options it needs, so they only have to outlive the call.
+        let ret = match params {
+            LzmaEncoderParams::Easy { preset, check } => unsafe {
+                liblzma_sys::lzma_easy_encoder(&mut self.raw, *preset, *check as _)
+            },
+            LzmaEncoderParams::Lzma { options } => {
+                let options = options.to_raw()?;
+                unsafe { liblzma_sys::lzma_alone_encoder(&mut self.raw, &options) }
+            }
+            LzmaEncoderParams::Raw { filters } => {
+                let filters = RawFilters::try_from(filters)?;
+                unsafe { liblzma_sys::lzma_raw_encoder(&mut self.raw, filters.as_ptr()) }
+            }
+            LzmaEncoderParams::Stream { filters, check } => {
+                let filters = RawFilters::try_from(filters)?;
+                unsafe {
+                    liblzma_sys::lzma_stream_encoder(&mut self.raw, filters.as_ptr(), *check as _)
+                }
+            }
+            #[cfg(feature = "xz-parallel")]
+            LzmaEncoderParams::MultiThread { builder } => {
+                let (options, _filters) = builder.to_raw()?;
+                unsafe { liblzma_sys::lzma_stream_encoder_mt(&mut self.raw, &options) }
+            }
+        };
+
+        cvt(ret).map(|_| ())
+    }
+}
+
+impl super::LzmaStream for EncoderStream {
+    fn total_in(&self) -> u64 {
+        self.raw.total_in
+    }
+
+    fn total_out(&self) -> u64 {
+        self.raw.total_out
+    }
+
+    fn process_uninit(
+        &mut self,
+        input: &[u8],
+        output: &mut [MaybeUninit<u8>],
+        action: Action,
+    ) -> Result<Status, Error> {
+        self.raw.next_in = input.as_ptr();
+        self.raw.avail_in = input.len();
+        self.raw.next_out = output.as_mut_ptr().cast();
+        self.raw.avail_out = output.len();
+        cvt(unsafe { liblzma_sys::lzma_code(&mut self.raw, action as _) })
+    }
+}
+
+impl Drop for EncoderStream {
+    fn drop(&mut self) {
+        unsafe { liblzma_sys::lzma_end(&mut self.raw) }
+    }
+}
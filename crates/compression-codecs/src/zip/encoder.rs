@@ -0,0 +1,537 @@
+use super::{ZipEntry, ZipMethod};
+use crate::{flate::params::FlateEncoderParams, DeflateEncoder, EncodeV2};
+use compression_core::{
+    util::{PartialBuffer, WriteBuffer},
+    Level,
+};
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{Error, ErrorKind, Result},
+};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const END_SIGNATURE: u32 = 0x0605_4b50;
+
+/// The compressed data of an LZMA entry ends with an end of stream marker.
+#[cfg(feature = "lzma")]
+const FLAG_LZMA_EOS: u16 = 1 << 1;
+/// The CRC-32 and sizes follow the data instead of being in the local header.
+const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+/// The name and comment are UTF-8.
+const FLAG_UTF8: u16 = 1 << 11;
+
+const VERSION_ZIP64: u16 = 45;
+/// Version 6.3 of the specification, which covers every supported method.
+const VERSION_MADE_BY: u16 = 63;
+const HOST_UNIX: u16 = 3 << 8;
+
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+/// Sizes, offsets and counts at least this large are stored in ZIP64 fields.
+const ZIP64_LIMIT: u64 = u32::MAX as u64;
+const ZIP64_COUNT_LIMIT: usize = u16::MAX as usize;
+
+fn put_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(data: &mut Vec<u8>, value: u64) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Returns `value` for a 32 bit field, or the marker that it is stored in a ZIP64 field.
+fn field_u32(value: u64) -> u32 {
+    u32::try_from(value)
+        .ok()
+        .filter(|&value| value != u32::MAX)
+        .unwrap_or(u32::MAX)
+}
+
+/// The compressor of the current entry.
+enum Codec {
+    Stored,
+    Deflate(DeflateEncoder),
+    #[cfg(feature = "bzip2")]
+    Bzip2(crate::BzEncoder),
+    #[cfg(feature = "lzma")]
+    Lzma(Box<crate::LzmaEncoder>),
+    #[cfg(feature = "zstd")]
+    Zstd(crate::ZstdEncoder),
+}
+
+impl fmt::Debug for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stored => f.write_str("Stored"),
+            Self::Deflate(_) => f.write_str("Deflate"),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(_) => f.write_str("Bzip2"),
+            #[cfg(feature = "lzma")]
+            Self::Lzma(_) => f.write_str("Lzma"),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(_) => f.write_str("Zstd"),
+        }
+    }
+}
+
+impl Codec {
+    /// Creates the compressor, along with any header which precedes the compressed data.
+    fn new(method: ZipMethod, level: Level) -> Result<(Self, Vec<u8>)> {
+        Ok(match method {
+            ZipMethod::Stored => (Self::Stored, Vec::new()),
+            ZipMethod::Deflate => (
+                Self::Deflate(DeflateEncoder::new(FlateEncoderParams::from(level))),
+                Vec::new(),
+            ),
+            #[cfg(feature = "bzip2")]
+            ZipMethod::Bzip2 => (
+                Self::Bzip2(crate::BzEncoder::new(
+                    crate::bzip2::params::Bzip2EncoderParams::from(level),
+                    0,
+                )),
+                Vec::new(),
+            ),
+            #[cfg(feature = "lzma")]
+            ZipMethod::Lzma => {
+                use crate::{
+                    lzma::params::{
+                        Lzma1Props, LzmaEncoderParams, LzmaFilter, LzmaFilters, LzmaOptions,
+                    },
+                    xz2::{xz2_dictionary_size, xz2_level},
+                };
+
+                let preset = xz2_level(level);
+                let options = LzmaOptions::default()
+                    .preset(preset)
+                    .dict_size(xz2_dictionary_size(preset));
+                let filters = LzmaFilters::default().add_filter(LzmaFilter::Lzma1(options));
+                // The literal and position bits are the same for every liblzma preset.
+                let props = Lzma1Props {
+                    literal_context_bits: 3,
+                    literal_position_bits: 0,
+                    position_bits: 2,
+                    dict_size: xz2_dictionary_size(preset),
+                };
+                let encoder = crate::LzmaEncoder::with_params(LzmaEncoderParams::Raw { filters })?;
+                (
+                    Self::Lzma(Box::new(encoder)),
+                    props.to_zip_header().to_vec(),
+                )
+            }
+            #[cfg(feature = "zstd")]
+            ZipMethod::Zstd => (
+                Self::Zstd(crate::ZstdEncoder::new(
+                    crate::zstd::params::CParameter::quality(level),
+                )),
+                Vec::new(),
+            ),
+        })
+    }
+
+    fn encoder(&mut self) -> Option<&mut dyn EncodeV2> {
+        match self {
+            Self::Stored => None,
+            Self::Deflate(encoder) => Some(encoder),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(encoder) => Some(encoder),
+            #[cfg(feature = "lzma")]
+            Self::Lzma(encoder) => Some(&mut **encoder),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => Some(encoder),
+        }
+    }
+}
+
+/// An entry as recorded in the central directory.
+#[derive(Debug)]
+struct Record {
+    name: Vec<u8>,
+    comment: Vec<u8>,
+    method: ZipMethod,
+    flags: u16,
+    date: u16,
+    time: u16,
+    unix_permissions: Option<u32>,
+    crc: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    offset: u64,
+}
+
+impl Record {
+    fn needs_zip64(&self) -> bool {
+        self.compressed_size >= ZIP64_LIMIT
+            || self.uncompressed_size >= ZIP64_LIMIT
+            || self.offset >= ZIP64_LIMIT
+    }
+
+    /// Every entry needs ZIP64 support, as its local header has a ZIP64 extra field.
+    fn version_needed(&self) -> u16 {
+        self.method.version_needed().max(VERSION_ZIP64)
+    }
+
+    fn write_central_header(&self, data: &mut Vec<u8>) {
+        let mut extra = Vec::new();
+        if self.needs_zip64() {
+            put_u16(&mut extra, ZIP64_EXTRA_ID);
+            put_u16(&mut extra, 0);
+            for value in [self.uncompressed_size, self.compressed_size, self.offset] {
+                if value >= ZIP64_LIMIT {
+                    put_u64(&mut extra, value);
+                }
+            }
+            let len = (extra.len() - 4) as u16;
+            extra[2..4].copy_from_slice(&len.to_le_bytes());
+        }
+
+        let (made_by, external_attributes) = match self.unix_permissions {
+            Some(mode) => (HOST_UNIX | VERSION_MADE_BY, mode << 16),
+            None => (VERSION_MADE_BY, 0),
+        };
+
+        put_u32(data, CENTRAL_HEADER_SIGNATURE);
+        put_u16(data, made_by);
+        put_u16(data, self.version_needed());
+        put_u16(data, self.flags);
+        put_u16(data, self.method.id());
+        put_u16(data, self.time);
+        put_u16(data, self.date);
+        put_u32(data, self.crc);
+        put_u32(data, field_u32(self.compressed_size));
+        put_u32(data, field_u32(self.uncompressed_size));
+        // The lengths were checked when the entry was started.
+        put_u16(data, self.name.len() as u16);
+        put_u16(data, extra.len() as u16);
+        put_u16(data, self.comment.len() as u16);
+        // Disk number and internal attributes.
+        put_u16(data, 0);
+        put_u16(data, 0);
+        put_u32(data, external_attributes);
+        put_u32(data, field_u32(self.offset));
+        data.extend_from_slice(&self.name);
+        data.extend_from_slice(&extra);
+        data.extend_from_slice(&self.comment);
+    }
+}
+
+/// The entry whose data is being written.
+#[derive(Debug)]
+struct Entry {
+    record: Record,
+    codec: Codec,
+    hasher: crc32fast::Hasher,
+}
+
+/// Encoder for a ZIP archive, written as a stream without seeking back.
+///
+/// Each entry is started with [`start_entry`](Self::start_entry), its uncompressed data is then
+/// passed to [`encode`](EncodeV2::encode) and it is completed with
+/// [`finish_entry`](Self::finish_entry). As the CRC-32 and sizes are only known once the data has
+/// been written, they follow it in a data descriptor and the local header leaves them zeroed.
+/// Because the sizes aren't known to fit 32 bits either, every local header has a ZIP64 extra
+/// field and every data descriptor has 64 bit sizes, which readers expect to go together.
+///
+/// [`finish`](EncodeV2::finish) completes the current entry and writes the central directory, for
+/// which every entry is kept in memory until then. ZIP64 fields are used for the entries and the
+/// directory when their sizes, offsets or count don't fit the original fields.
+#[derive(Debug)]
+pub struct ZipEncoder {
+    entry: Option<Entry>,
+    records: Vec<Record>,
+    /// Number of bytes of the archive output so far, including those still pending.
+    offset: u64,
+    pending: PartialBuffer<Vec<u8>>,
+    finished: bool,
+}
+
+impl Default for ZipEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZipEncoder {
+    pub fn new() -> Self {
+        Self {
+            entry: None,
+            records: Vec::new(),
+            offset: 0,
+            pending: PartialBuffer::new(Vec::new()),
+            finished: false,
+        }
+    }
+
+    /// Starts a new entry, whose data is then passed to [`encode`](EncodeV2::encode).
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the previous entry has not been finished, the archive has been finished,
+    /// or the name or comment of the entry is too long.
+    pub fn start_entry(&mut self, entry: ZipEntry) -> Result<()> {
+        if self.finished {
+            return Err(Error::other("zip archive has already been finished"));
+        }
+        if self.entry.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "the previous zip entry has not been finished",
+            ));
+        }
+        if entry.name.len() > usize::from(u16::MAX) || entry.comment.len() > usize::from(u16::MAX) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "zip entry name or comment is too long",
+            ));
+        }
+
+        let (codec, data_header) = Codec::new(entry.method, entry.level)?;
+
+        let mut flags = FLAG_DATA_DESCRIPTOR;
+        if !entry.name.is_ascii() || !entry.comment.is_ascii() {
+            flags |= FLAG_UTF8;
+        }
+        #[cfg(feature = "lzma")]
+        if entry.method == ZipMethod::Lzma {
+            flags |= FLAG_LZMA_EOS;
+        }
+
+        let record = Record {
+            name: entry.name.into_bytes(),
+            comment: entry.comment.into_bytes(),
+            method: entry.method,
+            flags,
+            date: entry.date,
+            time: entry.time,
+            unix_permissions: entry.unix_permissions,
+            crc: 0,
+            compressed_size: data_header.len() as u64,
+            uncompressed_size: 0,
+            offset: self.offset,
+        };
+
+        let mut header = Vec::with_capacity(50 + record.name.len() + data_header.len());
+        put_u32(&mut header, LOCAL_HEADER_SIGNATURE);
+        put_u16(&mut header, record.version_needed());
+        put_u16(&mut header, record.flags);
+        put_u16(&mut header, record.method.id());
+        put_u16(&mut header, record.time);
+        put_u16(&mut header, record.date);
+        // The CRC-32 and sizes are in the data descriptor, the sizes are marked as being in the
+        // ZIP64 extra field, which leaves them zeroed.
+        put_u32(&mut header, 0);
+        put_u32(&mut header, u32::MAX);
+        put_u32(&mut header, u32::MAX);
+        put_u16(&mut header, record.name.len() as u16);
+        put_u16(&mut header, 20);
+        header.extend_from_slice(&record.name);
+        put_u16(&mut header, ZIP64_EXTRA_ID);
+        put_u16(&mut header, 16);
+        put_u64(&mut header, 0);
+        put_u64(&mut header, 0);
+        header.extend_from_slice(&data_header);
+        self.queue(header);
+
+        self.entry = Some(Entry {
+            record,
+            codec,
+            hasher: crc32fast::Hasher::new(),
+        });
+        Ok(())
+    }
+
+    /// Completes the current entry, if any, by finishing its compressed data and writing its data
+    /// descriptor. Returns whether that has been completely written.
+    pub fn finish_entry(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        loop {
+            if !self.drain(output) {
+                return Ok(false);
+            }
+
+            let Some(entry) = &mut self.entry else {
+                return Ok(true);
+            };
+
+            let done = match entry.codec.encoder() {
+                Some(encoder) => {
+                    let written_len = output.written_len();
+                    let done = encoder.finish(output)?;
+                    self.count_output(output.written_len() - written_len);
+                    done
+                }
+                None => true,
+            };
+
+            if !done {
+                return Ok(false);
+            }
+
+            let entry = self.entry.take().unwrap();
+            self.end_entry(entry);
+        }
+    }
+
+    /// Queues the data descriptor of a completed entry and records it for the central directory.
+    fn end_entry(&mut self, entry: Entry) {
+        let Entry {
+            mut record, hasher, ..
+        } = entry;
+        record.crc = hasher.finalize();
+
+        // The sizes are 64 bit as the local header has a ZIP64 extra field.
+        let mut descriptor = Vec::with_capacity(24);
+        put_u32(&mut descriptor, DATA_DESCRIPTOR_SIGNATURE);
+        put_u32(&mut descriptor, record.crc);
+        put_u64(&mut descriptor, record.compressed_size);
+        put_u64(&mut descriptor, record.uncompressed_size);
+        self.queue(descriptor);
+
+        self.records.push(record);
+    }
+
+    /// Queues the central directory and end of central directory records.
+    fn end_archive(&mut self) {
+        let start = self.offset;
+        let mut data = Vec::new();
+        for record in &self.records {
+            record.write_central_header(&mut data);
+        }
+        let size = data.len() as u64;
+        let count = self.records.len();
+
+        if count >= ZIP64_COUNT_LIMIT || size >= ZIP64_LIMIT || start >= ZIP64_LIMIT {
+            let zip64_end = start + size;
+            put_u32(&mut data, ZIP64_END_SIGNATURE);
+            // Size of the rest of the record.
+            put_u64(&mut data, 44);
+            put_u16(&mut data, VERSION_MADE_BY);
+            put_u16(&mut data, VERSION_ZIP64);
+            // Number of this disk and of the disk with the central directory.
+            put_u32(&mut data, 0);
+            put_u32(&mut data, 0);
+            put_u64(&mut data, count as u64);
+            put_u64(&mut data, count as u64);
+            put_u64(&mut data, size);
+            put_u64(&mut data, start);
+
+            put_u32(&mut data, ZIP64_LOCATOR_SIGNATURE);
+            put_u32(&mut data, 0);
+            put_u64(&mut data, zip64_end);
+            // Total number of disks.
+            put_u32(&mut data, 1);
+        }
+
+        let count = u16::try_from(count)
+            .ok()
+            .filter(|&count| count != u16::MAX)
+            .unwrap_or(u16::MAX);
+        put_u32(&mut data, END_SIGNATURE);
+        put_u16(&mut data, 0);
+        put_u16(&mut data, 0);
+        put_u16(&mut data, count);
+        put_u16(&mut data, count);
+        put_u32(&mut data, field_u32(size));
+        put_u32(&mut data, field_u32(start));
+        // Archive comment length.
+        put_u16(&mut data, 0);
+
+        self.queue(data);
+        self.finished = true;
+    }
+
+    /// Appends `data` to the output which is still to be written.
+    fn queue(&mut self, data: Vec<u8>) {
+        self.offset += data.len() as u64;
+        if self.pending.unwritten().is_empty() {
+            self.pending = PartialBuffer::new(data);
+        } else {
+            self.pending.get_mut().extend_from_slice(&data);
+        }
+    }
+
+    /// Records `len` bytes of compressed data written directly to the output.
+    fn count_output(&mut self, len: usize) {
+        let len = len as u64;
+        self.offset += len;
+        if let Some(entry) = &mut self.entry {
+            entry.record.compressed_size += len;
+        }
+    }
+
+    /// Writes out as much of the pending output as fits, returns whether all of it was written.
+    fn drain(&mut self, output: &mut WriteBuffer<'_>) -> bool {
+        output.copy_unwritten_from(&mut self.pending);
+        self.pending.unwritten().is_empty()
+    }
+}
+
+impl EncodeV2 for ZipEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> Result<()> {
+        if !self.drain(output) {
+            return Ok(());
+        }
+
+        let Some(entry) = &mut self.entry else {
+            return Err(Error::other("no zip entry has been started"));
+        };
+
+        let read_len = input.written_len();
+        let written_len = output.written_len();
+        match entry.codec.encoder() {
+            Some(encoder) => encoder.encode(input, output)?,
+            None => {
+                output.copy_unwritten_from(input);
+            }
+        }
+
+        let read = &input.written()[read_len..];
+        entry.hasher.update(read);
+        entry.record.uncompressed_size += read.len() as u64;
+        self.count_output(output.written_len() - written_len);
+        Ok(())
+    }
+
+    fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if !self.drain(output) {
+            return Ok(false);
+        }
+
+        let Some(encoder) = self.entry.as_mut().and_then(|entry| entry.codec.encoder()) else {
+            return Ok(true);
+        };
+
+        let written_len = output.written_len();
+        let done = encoder.flush(output)?;
+        self.count_output(output.written_len() - written_len);
+        Ok(done)
+    }
+
+    fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if !self.finish_entry(output)? {
+            return Ok(false);
+        }
+
+        if !self.finished {
+            self.end_archive();
+        }
+
+        Ok(self.drain(output))
+    }
+
+    /// Starts a new archive, dropping any entry in progress.
+    fn reinit(&mut self) -> Result<()> {
+        *self = Self::new();
+        Ok(())
+    }
+}
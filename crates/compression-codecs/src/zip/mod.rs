@@ -0,0 +1,174 @@
+mod encoder;
+
+pub use self::encoder::ZipEncoder;
+
+use compression_core::Level;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The MS-DOS date of 1980-01-01, the earliest date which can be stored in a ZIP archive.
+const DOS_EPOCH_DATE: u16 = (1 << 5) | 1;
+
+/// Compression method of a ZIP entry.
+///
+/// Deflate64 (method 9) can't be written, as only a decoder is available for it.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZipMethod {
+    /// The data is stored uncompressed.
+    Stored,
+    /// The data is compressed with deflate.
+    Deflate,
+    /// The data is compressed with bzip2.
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+    /// The data is compressed with raw LZMA1, preceded by its properties.
+    #[cfg(feature = "lzma")]
+    Lzma,
+    /// The data is compressed with zstd.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl ZipMethod {
+    /// Returns the method identifier stored in the archive.
+    pub fn id(self) -> u16 {
+        match self {
+            Self::Stored => 0,
+            Self::Deflate => 8,
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => 12,
+            #[cfg(feature = "lzma")]
+            Self::Lzma => 14,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => 93,
+        }
+    }
+
+    /// Returns the ZIP specification version needed to extract entries using this method.
+    fn version_needed(self) -> u16 {
+        match self {
+            Self::Stored => 10,
+            Self::Deflate => 20,
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => 46,
+            #[cfg(feature = "lzma")]
+            Self::Lzma => 63,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => 63,
+        }
+    }
+}
+
+/// Describes an entry to add to a ZIP archive.
+#[derive(Debug, Clone)]
+pub struct ZipEntry {
+    name: String,
+    method: ZipMethod,
+    level: Level,
+    date: u16,
+    time: u16,
+    comment: String,
+    unix_permissions: Option<u32>,
+}
+
+impl ZipEntry {
+    /// Creates an entry with the given path inside the archive, compressed with deflate at the
+    /// default level. Directories are entries whose name ends with `/` and which have no data.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            method: ZipMethod::Deflate,
+            level: Level::Default,
+            date: DOS_EPOCH_DATE,
+            time: 0,
+            comment: String::new(),
+            unix_permissions: None,
+        }
+    }
+
+    /// Sets the compression method.
+    pub fn method(mut self, value: ZipMethod) -> Self {
+        self.method = value;
+        self
+    }
+
+    /// Sets the compression level, ignored for [`ZipMethod::Stored`].
+    pub fn level(mut self, value: Level) -> Self {
+        self.level = value;
+        self
+    }
+
+    /// Sets the modification time, which is stored in UTC with a two second precision and clamped
+    /// to the years 1980 to 2107. Defaults to the start of 1980.
+    pub fn last_modified(mut self, value: SystemTime) -> Self {
+        (self.date, self.time) = dos_date_time(value);
+        self
+    }
+
+    /// Sets the comment of the entry.
+    pub fn comment(mut self, value: impl Into<String>) -> Self {
+        self.comment = value.into();
+        self
+    }
+
+    /// Sets the Unix mode of the entry, such as `0o100644` for a regular file.
+    pub fn unix_permissions(mut self, value: u32) -> Self {
+        self.unix_permissions = Some(value);
+        self
+    }
+}
+
+/// Converts `time` to an MS-DOS date and time.
+fn dos_date_time(time: SystemTime) -> (u16, u16) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    // Converts the days since 1970-01-01 to a date in the proleptic Gregorian calendar, counting
+    // from 0000-03-01 so leap days are at the end of the year.
+    let days = secs / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    match year {
+        ..=1979 => (DOS_EPOCH_DATE, 0),
+        2108.. => ((127 << 9) | (12 << 5) | 31, (23 << 11) | (59 << 5) | 29),
+        _ => {
+            let secs_of_day = secs % 86_400;
+            let date = ((year - 1980) << 9) | (month << 5) | day;
+            let time =
+                (secs_of_day / 3_600) << 11 | (secs_of_day / 60 % 60) << 5 | (secs_of_day % 60 / 2);
+            // Both fit as every field is within its bit width.
+            (date as u16, time as u16)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dos_date_time, DOS_EPOCH_DATE};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn dos_date_time_conversion() {
+        // 2024-02-29 13:45:31 UTC.
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_214_331);
+        assert_eq!(
+            dos_date_time(time),
+            ((44 << 9) | (2 << 5) | 29, (13 << 11) | (45 << 5) | 15)
+        );
+
+        assert_eq!(dos_date_time(UNIX_EPOCH), (DOS_EPOCH_DATE, 0));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{DecodeV2, FlateDecoder};
+use crate::{DecodeV2, DecoderLimits, FlateDecoder};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use std::io::Result;
 
@@ -40,4 +40,8 @@ impl DecodeV2 for ZlibDecoder {
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        self.inner.set_limits(limits)
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{flate::params::FlateEncoderParams, EncodeV2, FlateEncoder};
+use crate::{flate::params::FlateEncoderParams, EncodeV2, FlateEncoder, FlushMode};
 use compression_core::util::{PartialBuffer, WriteBuffer};
 use std::io::Result;
 
@@ -35,4 +35,12 @@ impl EncodeV2 for ZlibEncoder {
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()> {
+        self.inner.set_flush_mode(mode)
+    }
+
+    fn reinit(&mut self) -> Result<()> {
+        self.inner.reinit()
+    }
 }
@@ -1,60 +1,108 @@
 use crate::{
-    zstd::{params::DParameter, OperationExt},
-    {DecodeV2, DecodedSize},
+    zstd::{params::DParameter, DecoderContext, OperationExt, ZstdError},
+    {DecodeV2, DecodedSize, DecoderLimits},
 };
 use compression_core::{
     unshared::Unshared,
     util::{PartialBuffer, WriteBuffer},
 };
-use libzstd::stream::raw::Decoder;
 use std::{
     convert::TryInto,
     io::{self, Result},
 };
-use zstd_safe::get_error_name;
+use zstd_safe::{get_error_name, zstd_sys::ZSTD_ErrorCode};
 
 #[derive(Debug)]
 pub struct ZstdDecoder {
-    decoder: Unshared<Decoder<'static>>,
+    decoder: Unshared<DecoderContext>,
     stream_ended: bool,
+    limits: DecoderLimits,
+    #[cfg(feature = "zstd-parallel")]
+    parallel: Option<Box<super::parallel::ParallelZstdDecoder>>,
 }
 
 impl Default for ZstdDecoder {
     fn default() -> Self {
+        Self::from_decoder(DecoderContext::new().unwrap())
+    }
+}
+
+impl ZstdDecoder {
+    fn from_decoder(decoder: DecoderContext) -> Self {
         Self {
-            decoder: Unshared::new(Decoder::new().unwrap()),
+            decoder: Unshared::new(decoder),
             stream_ended: false,
+            limits: DecoderLimits::new(),
+            #[cfg(feature = "zstd-parallel")]
+            parallel: None,
         }
     }
-}
 
-impl ZstdDecoder {
     pub fn new() -> Self {
         Self::default()
     }
 
     pub fn new_with_params(params: &[DParameter]) -> Self {
-        let mut decoder = Decoder::new().unwrap();
+        let mut decoder = DecoderContext::new().unwrap();
         for param in params {
             decoder.set_parameter(param.as_zstd()).unwrap();
         }
-        Self {
-            decoder: Unshared::new(decoder),
-            stream_ended: false,
-        }
+        Self::from_decoder(decoder)
     }
 
     pub fn new_with_dict(dictionary: &[u8]) -> io::Result<Self> {
-        let decoder = Decoder::with_dictionary(dictionary)?;
-        Ok(Self {
-            decoder: Unshared::new(decoder),
-            stream_ended: false,
-        })
+        let decoder = DecoderContext::with_dictionary(dictionary)?;
+        Ok(Self::from_decoder(decoder))
+    }
+
+    /// Creates a decoder which splits its input into frames and decodes up to `threads` frames at
+    /// the same time, writing the decoded frames out in order.
+    ///
+    /// All frames in the input are decoded, as if multi-member decoding was enabled. Frames are
+    /// buffered until complete and their output is handed back in chunks, so memory use stays
+    /// bounded; frames over 4 MiB compressed are decoded on the calling thread instead.
+    ///
+    /// The decoder doesn't wait for its workers: while it can make no progress until one of them
+    /// is done, [`decode`](DecodeV2::decode), [`flush`](DecodeV2::flush) and
+    /// [`finish`](DecodeV2::finish) return a [`WouldBlock`](io::ErrorKind::WouldBlock) error, and
+    /// the waker given to [`register_waker`](DecodeV2::register_waker) is woken once it can
+    /// continue.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the worker threads could not be spawned.
+    #[cfg(feature = "zstd-parallel")]
+    pub fn parallel(threads: std::num::NonZeroU32, params: &[DParameter]) -> io::Result<Self> {
+        let mut this = Self::new();
+        this.parallel = Some(Box::new(super::parallel::ParallelZstdDecoder::new(
+            threads, params,
+        )?));
+        Ok(this)
+    }
+
+    /// Replaces the error zstd returns for frames with a window over `WindowLogMax`.
+    fn limit_error(&self, err: io::Error) -> io::Error {
+        if self.limits.max_memory.is_some()
+            && ZstdError::matches(
+                &err,
+                ZSTD_ErrorCode::ZSTD_error_frameParameter_windowTooLarge,
+            )
+        {
+            self.limits.error(None)
+        } else {
+            err
+        }
     }
 }
 
 impl DecodeV2 for ZstdDecoder {
     fn reinit(&mut self) -> Result<()> {
+        #[cfg(feature = "zstd-parallel")]
+        if let Some(parallel) = &mut self.parallel {
+            parallel.reinit();
+            return Ok(());
+        }
+
         self.decoder.reinit()?;
         self.stream_ended = false;
         Ok(())
@@ -65,7 +113,17 @@ impl DecodeV2 for ZstdDecoder {
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> Result<bool> {
-        let finished = self.decoder.run(input, output)?;
+        #[cfg(feature = "zstd-parallel")]
+        if let Some(parallel) = &mut self.parallel {
+            return parallel
+                .decode(input, output)
+                .map_err(|err| self.limit_error(err));
+        }
+
+        let finished = self
+            .decoder
+            .run(input, output)
+            .map_err(|err| self.limit_error(err))?;
         if finished {
             self.stream_ended = true;
         }
@@ -73,6 +131,11 @@ impl DecodeV2 for ZstdDecoder {
     }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        #[cfg(feature = "zstd-parallel")]
+        if let Some(parallel) = &mut self.parallel {
+            return parallel.flush(output).map_err(|err| self.limit_error(err));
+        }
+
         // Note: stream_ended is not updated here because zstd's flush only flushes
         // buffered output and doesn't indicate stream completion. Stream completion
         // is detected in decode() when status.remaining == 0.
@@ -80,6 +143,11 @@ impl DecodeV2 for ZstdDecoder {
     }
 
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        #[cfg(feature = "zstd-parallel")]
+        if let Some(parallel) = &mut self.parallel {
+            return parallel.finish(output).map_err(|err| self.limit_error(err));
+        }
+
         self.decoder.finish(output)?;
 
         if self.stream_ended {
@@ -91,6 +159,44 @@ impl DecodeV2 for ZstdDecoder {
             ))
         }
     }
+
+    fn input_size_hint(&self) -> usize {
+        zstd_safe::DCtx::in_size()
+    }
+
+    #[cfg(feature = "zstd-parallel")]
+    fn register_waker(&mut self, waker: &std::task::Waker) {
+        if let Some(parallel) = &self.parallel {
+            parallel.register_waker(waker);
+        }
+    }
+
+    fn set_limits(&mut self, limits: DecoderLimits) -> Result<()> {
+        // zstd limits the window to a power of two, of at least 1 KiB, with 0 for its default.
+        let window_log_max = match limits.max_memory {
+            Some(max_memory) => {
+                let max = if cfg!(target_pointer_width = "64") {
+                    31
+                } else {
+                    30
+                };
+                // Limits below the smallest window can't be met.
+                limits.check(1 << 10)?;
+                max_memory.ilog2().min(max)
+            }
+            None => 0,
+        };
+        let param = DParameter::window_log_max(window_log_max);
+
+        #[cfg(feature = "zstd-parallel")]
+        if let Some(parallel) = &mut self.parallel {
+            parallel.set_param(param);
+        }
+
+        self.decoder.get_mut().set_parameter(param.as_zstd())?;
+        self.limits = limits;
+        Ok(())
+    }
 }
 
 impl DecodedSize for ZstdDecoder {
@@ -1,6 +1,6 @@
 use crate::{
     zstd::{params::CParameter, OperationExt},
-    EncodeV2,
+    EncodeV2, FlushMode,
 };
 use compression_core::{
     unshared::Unshared,
@@ -12,13 +12,14 @@ use std::io::{self, Result};
 #[derive(Debug)]
 pub struct ZstdEncoder {
     encoder: Unshared<Encoder<'static>>,
+    flush_mode: FlushMode,
+    /// Whether a frame has been started, or has to be written, since a flush last ended one.
+    frame_pending: bool,
 }
 
 impl ZstdEncoder {
     pub fn new(level: i32) -> Self {
-        Self {
-            encoder: Unshared::new(Encoder::new(level).unwrap()),
-        }
+        Self::from_encoder(Encoder::new(level).unwrap())
     }
 
     pub fn new_with_params(level: i32, params: &[CParameter]) -> Self {
@@ -26,16 +27,20 @@ impl ZstdEncoder {
         for param in params {
             encoder.set_parameter(param.as_zstd()).unwrap();
         }
-        Self {
-            encoder: Unshared::new(encoder),
-        }
+        Self::from_encoder(encoder)
     }
 
     pub fn new_with_dict(level: i32, dictionary: &[u8]) -> io::Result<Self> {
         let encoder = Encoder::with_dictionary(level, dictionary)?;
-        Ok(Self {
+        Ok(Self::from_encoder(encoder))
+    }
+
+    fn from_encoder(encoder: Encoder<'static>) -> Self {
+        Self {
             encoder: Unshared::new(encoder),
-        })
+            flush_mode: FlushMode::Sync,
+            frame_pending: true,
+        }
     }
 }
 
@@ -45,15 +50,51 @@ impl EncodeV2 for ZstdEncoder {
         input: &mut PartialBuffer<&[u8]>,
         output: &mut WriteBuffer<'_>,
     ) -> Result<()> {
+        if !input.unwritten().is_empty() {
+            self.frame_pending = true;
+        }
         self.encoder.run(input, output)?;
         Ok(())
     }
 
     fn flush(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
-        self.encoder.flush(output)
+        // Anything but finishing would start a new frame, which then has to be finished too.
+        if !self.frame_pending {
+            return Ok(true);
+        }
+
+        match self.flush_mode {
+            FlushMode::Sync => self.encoder.flush(output),
+            _ => {
+                // The next input starts a new frame.
+                let done = self.encoder.finish(output)?;
+                self.frame_pending = !done;
+                Ok(done)
+            }
+        }
     }
 
     fn finish(&mut self, output: &mut WriteBuffer<'_>) -> Result<bool> {
+        if !self.frame_pending {
+            return Ok(true);
+        }
+
         self.encoder.finish(output)
     }
+
+    fn input_size_hint(&self) -> usize {
+        zstd_safe::CCtx::in_size()
+    }
+
+    fn set_flush_mode(&mut self, mode: FlushMode) -> Result<()> {
+        self.flush_mode = mode;
+        Ok(())
+    }
+
+    fn reinit(&mut self) -> Result<()> {
+        // Only resets the session, the parameters and dictionary are kept.
+        self.encoder.reinit()?;
+        self.frame_pending = true;
+        Ok(())
+    }
 }
@@ -1,5 +1,7 @@
 mod decoder;
 mod encoder;
+#[cfg(feature = "zstd-parallel")]
+mod parallel;
 pub mod params;
 
 pub use self::{decoder::ZstdDecoder, encoder::ZstdEncoder};
@@ -8,8 +10,90 @@ use compression_core::{
     unshared::Unshared,
     util::{PartialBuffer, WriteBuffer},
 };
-use libzstd::stream::raw::{InBuffer, Operation, OutBuffer, WriteBuf};
-use std::io;
+use libzstd::stream::raw::{DParameter, InBuffer, Operation, OutBuffer, WriteBuf};
+use std::{error::Error, fmt, io};
+use zstd_safe::{get_error_name, zstd_sys::ZSTD_ErrorCode, DCtx, ErrorCode, ResetDirective};
+
+/// Whether `code`, an error returned by a `zstd_safe` function, is `error`.
+fn is_error(code: ErrorCode, error: ZSTD_ErrorCode) -> bool {
+    // zstd returns error codes negated.
+    code == 0usize.wrapping_sub(error as usize)
+}
+
+/// An error returned by zstd, which keeps its code around in the `io::Error` it is wrapped in.
+#[derive(Debug)]
+struct ZstdError(ErrorCode);
+
+impl ZstdError {
+    fn into_io(code: ErrorCode) -> io::Error {
+        io::Error::other(Self(code))
+    }
+
+    /// Whether `err` is the zstd error `error`.
+    fn matches(err: &io::Error, error: ZSTD_ErrorCode) -> bool {
+        err.get_ref()
+            .and_then(|err| err.downcast_ref::<Self>())
+            .is_some_and(|Self(code)| is_error(*code, error))
+    }
+}
+
+impl fmt::Display for ZstdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(get_error_name(self.0))
+    }
+}
+
+impl Error for ZstdError {}
+
+/// A zstd decoding context, like `libzstd`'s `Decoder` but returning [`ZstdError`]s.
+struct DecoderContext(DCtx<'static>);
+
+impl DecoderContext {
+    fn new() -> io::Result<Self> {
+        Self::with_dictionary(&[])
+    }
+
+    fn with_dictionary(dictionary: &[u8]) -> io::Result<Self> {
+        let mut context =
+            DCtx::try_create().ok_or_else(|| io::Error::from(io::ErrorKind::OutOfMemory))?;
+        context.init().map_err(ZstdError::into_io)?;
+        context
+            .load_dictionary(dictionary)
+            .map_err(ZstdError::into_io)?;
+        Ok(Self(context))
+    }
+
+    fn set_parameter(&mut self, param: DParameter) -> io::Result<()> {
+        self.0.set_parameter(param).map_err(ZstdError::into_io)?;
+        Ok(())
+    }
+}
+
+impl Operation for DecoderContext {
+    fn run<C: WriteBuf + ?Sized>(
+        &mut self,
+        input: &mut InBuffer<'_>,
+        output: &mut OutBuffer<'_, C>,
+    ) -> io::Result<usize> {
+        self.0
+            .decompress_stream(output, input)
+            .map_err(ZstdError::into_io)
+    }
+
+    fn flush<C: WriteBuf + ?Sized>(&mut self, output: &mut OutBuffer<'_, C>) -> io::Result<usize> {
+        self.run(&mut InBuffer::around(&[]), output)?;
+
+        // Whether there is more buffered output is only known when it didn't fill the buffer.
+        Ok(usize::from(output.pos() == output.capacity()))
+    }
+
+    fn reinit(&mut self) -> io::Result<()> {
+        self.0
+            .reset(ResetDirective::SessionOnly)
+            .map_err(ZstdError::into_io)?;
+        Ok(())
+    }
+}
 
 #[repr(transparent)]
 struct WriteBufferWrapper<'a>(WriteBuffer<'a>);
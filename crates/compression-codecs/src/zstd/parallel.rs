@@ -0,0 +1,345 @@
+//! Decoding of streams made of many independent zstd frames, with each complete frame handed to a
+//! pool of worker threads and the decoded frames written out in their original order.
+//!
+//! Memory use is bounded: input is only buffered up to [`MAX_FRAME_SIZE`] while looking for the
+//! end of a frame, at most `max_pending` frames are decoded at once, and the workers hand their
+//! output back in chunks of [`CHUNK_SIZE`] through a queue of [`QUEUED_CHUNKS`], waiting for it
+//! to be written out rather than decoding whole frames into memory. Frames larger than
+//! [`MAX_FRAME_SIZE`] are decoded on the calling thread as their input arrives, once the frames
+//! before them have been written out.
+
+use crate::{
+    pool::{would_block, Ordered, Results, WorkerPool},
+    zstd::{is_error, params::DParameter, DecoderContext, OperationExt},
+};
+use compression_core::{
+    unshared::Unshared,
+    util::{PartialBuffer, WriteBuffer},
+};
+use libzstd::stream::raw::{InBuffer, Operation, OutBuffer};
+use std::{fmt, io, mem, num::NonZeroU32, sync::Arc, task::Waker};
+use zstd_safe::{get_error_name, zstd_sys::ZSTD_ErrorCode};
+
+/// Size of the input buffered while looking for the end of a frame, larger frames are decoded
+/// without handing them to a worker.
+const MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// Size of the pieces the output of a frame is handed back in.
+const CHUNK_SIZE: usize = 128 * 1024;
+
+/// Number of pieces of output a worker decodes ahead of the output written out.
+const QUEUED_CHUNKS: usize = 2;
+
+fn new_decoder(params: &[DParameter]) -> io::Result<DecoderContext> {
+    let mut decoder = DecoderContext::new()?;
+    for param in params {
+        decoder.set_parameter(param.as_zstd())?;
+    }
+    Ok(decoder)
+}
+
+/// Decodes `frame`, handing its output back in chunks.
+fn decode_frame(params: &[DParameter], frame: &[u8], results: &Results<Vec<u8>>) {
+    if let Err(err) = try_decode_frame(params, frame, results) {
+        results.send(Err(err));
+    }
+}
+
+fn try_decode_frame(
+    params: &[DParameter],
+    frame: &[u8],
+    results: &Results<Vec<u8>>,
+) -> io::Result<()> {
+    let mut decoder = new_decoder(params)?;
+    let mut input = InBuffer::around(frame);
+    let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+
+    loop {
+        let remaining = {
+            let len = chunk.len();
+            decoder.run(&mut input, &mut OutBuffer::around_pos(&mut chunk, len))?
+        };
+
+        if remaining == 0 {
+            break;
+        }
+
+        if chunk.len() == chunk.capacity() {
+            let full = mem::replace(&mut chunk, Vec::with_capacity(CHUNK_SIZE));
+            if !results.send(Ok(full)) {
+                // The decoder has been dropped or reinitialized.
+                return Ok(());
+            }
+        } else if input.pos() == frame.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "zstd frame did not finish",
+            ));
+        }
+    }
+
+    if !chunk.is_empty() {
+        results.send(Ok(chunk));
+    }
+    Ok(())
+}
+
+/// A frame too large to buffer, decoded on the calling thread.
+struct Oversized {
+    decoder: Unshared<DecoderContext>,
+    /// The input buffered before the frame was found to be too large, which starts with it.
+    buffered: PartialBuffer<Vec<u8>>,
+}
+
+/// Zstd decoder that decodes complete frames on multiple threads.
+pub(super) struct ParallelZstdDecoder {
+    params: Arc<[DParameter]>,
+    /// Declared before `pool` so that it is dropped first, which stops the workers waiting to
+    /// hand back output before the pool waits for them to exit.
+    pending: Ordered<Vec<u8>>,
+    pool: WorkerPool,
+    max_pending: usize,
+    /// Input which has not yet been split off into a frame.
+    buffer: Vec<u8>,
+    /// Length `buffer` has to reach before trying to find the end of a frame again, so that large
+    /// frames arriving in small pieces are not rescanned on every call.
+    retry_at: usize,
+    current: PartialBuffer<Vec<u8>>,
+    oversized: Option<Oversized>,
+}
+
+impl fmt::Debug for ParallelZstdDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParallelZstdDecoder")
+            .field("params", &self.params)
+            .field("pool", &self.pool)
+            .field("buffer", &self.buffer.len())
+            .field("oversized", &self.oversized.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ParallelZstdDecoder {
+    pub(super) fn new(threads: NonZeroU32, params: &[DParameter]) -> io::Result<Self> {
+        Ok(Self {
+            params: params.into(),
+            pending: Ordered::default(),
+            pool: WorkerPool::new("zstd-parallel", threads)?,
+            // Allow every worker to have one frame queued behind the one it is decoding.
+            max_pending: 2 * threads.get() as usize,
+            buffer: Vec::new(),
+            retry_at: 0,
+            current: PartialBuffer::new(Vec::new()),
+            oversized: None,
+        })
+    }
+
+    /// Splits the first complete frame off `buffer` and submits it, returns whether there was one.
+    fn split_frame(&mut self, at_end: bool) -> io::Result<bool> {
+        if self.buffer.is_empty() || (!at_end && self.buffer.len() < self.retry_at) {
+            return Ok(false);
+        }
+
+        match zstd_safe::find_frame_compressed_size(&self.buffer) {
+            Ok(size) => {
+                let rest = self.buffer.split_off(size);
+                let frame = mem::replace(&mut self.buffer, rest);
+                self.retry_at = 0;
+
+                let params = Arc::clone(&self.params);
+                let receiver = self.pool.spawn(QUEUED_CHUNKS, move |results| {
+                    decode_frame(&params, &frame, results);
+                })?;
+                self.pending.push(receiver);
+                Ok(true)
+            }
+            Err(code) if is_error(code, ZSTD_ErrorCode::ZSTD_error_srcSize_wrong) => {
+                if at_end {
+                    Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "zstd stream did not finish",
+                    ))
+                } else {
+                    self.retry_at = (self.buffer.len() * 2).min(MAX_FRAME_SIZE);
+                    Ok(false)
+                }
+            }
+            Err(code) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                get_error_name(code),
+            )),
+        }
+    }
+
+    /// Decodes the frame too large to buffer from `input`, after the input buffered before it,
+    /// returns whether it is done.
+    fn decode_oversized(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> io::Result<bool> {
+        let Some(oversized) = &mut self.oversized else {
+            return Ok(true);
+        };
+
+        let mut done = false;
+        if !oversized.buffered.unwritten().is_empty() {
+            let mut buffered = PartialBuffer::new(oversized.buffered.unwritten());
+            done = oversized.decoder.run(&mut buffered, output)?;
+            let len = buffered.written_len();
+            oversized.buffered.advance(len);
+        }
+        if !done && oversized.buffered.unwritten().is_empty() {
+            done = oversized.decoder.run(input, output)?;
+        }
+
+        if done {
+            // Whatever was buffered after the frame goes back to being split into frames.
+            if let Some(Oversized { buffered, .. }) = self.oversized.take() {
+                let len = buffered.written_len();
+                self.buffer = buffered.into_inner();
+                self.buffer.drain(..len);
+                self.retry_at = 0;
+            }
+        }
+        Ok(done)
+    }
+
+    /// Writes out decoded frames, returns whether everything submitted has been written.
+    fn drain(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        loop {
+            output.copy_unwritten_from(&mut self.current);
+
+            if !self.current.unwritten().is_empty() {
+                return Ok(false);
+            }
+
+            match self.pending.try_next()? {
+                Some(decoded) => self.current = PartialBuffer::new(decoded),
+                None => return Ok(self.pending.is_empty()),
+            }
+        }
+    }
+
+    /// Like [`drain`](Self::drain), but returns a [`would_block`] error if nothing has been
+    /// written since `written` and there is more to come.
+    fn drain_or_block(&mut self, output: &mut WriteBuffer<'_>, written: usize) -> io::Result<bool> {
+        let done = self.drain(output)?;
+        if done || output.has_no_spare_space() || output.written_len() > written {
+            Ok(done)
+        } else {
+            Err(would_block())
+        }
+    }
+
+    /// Sets `param` for the frames submitted from now on, in addition to the others.
+    pub(super) fn set_param(&mut self, param: DParameter) {
+        self.params = self.params.iter().copied().chain(Some(param)).collect();
+    }
+
+    pub(super) fn register_waker(&self, waker: &Waker) {
+        self.pool.register_waker(waker);
+    }
+
+    pub(super) fn reinit(&mut self) {
+        self.pending.clear();
+        self.buffer.clear();
+        self.retry_at = 0;
+        self.current = PartialBuffer::new(Vec::new());
+        self.oversized = None;
+    }
+
+    pub(super) fn decode(
+        &mut self,
+        input: &mut PartialBuffer<&[u8]>,
+        output: &mut WriteBuffer<'_>,
+    ) -> io::Result<bool> {
+        let (read, written) = (input.written_len(), output.written_len());
+
+        loop {
+            self.drain(output)?;
+
+            if output.has_no_spare_space() {
+                return Ok(false);
+            }
+
+            if self.oversized.is_some() {
+                if self.decode_oversized(input, output)? {
+                    continue;
+                }
+                return Ok(false);
+            }
+
+            if self.pending.len() < self.max_pending && self.split_frame(false)? {
+                continue;
+            }
+
+            if self.buffer.len() >= MAX_FRAME_SIZE && self.pending.is_empty() {
+                // Everything before the frame has been written out, so it can be decoded here.
+                self.oversized = Some(Oversized {
+                    decoder: Unshared::new(new_decoder(&self.params)?),
+                    buffered: PartialBuffer::new(mem::take(&mut self.buffer)),
+                });
+                continue;
+            }
+
+            if self.pending.len() >= self.max_pending || self.buffer.len() >= MAX_FRAME_SIZE {
+                // Wait for the oldest frame rather than queueing more input.
+                if input.written_len() > read || output.written_len() > written {
+                    return Ok(false);
+                }
+                return Err(would_block());
+            }
+
+            if input.unwritten().is_empty() {
+                // The end of the stream is only known once there is no more input, which is
+                // signalled by a call to `finish`.
+                return Ok(false);
+            }
+
+            let len = input
+                .unwritten()
+                .len()
+                .min(MAX_FRAME_SIZE - self.buffer.len());
+            self.buffer.extend_from_slice(&input.unwritten()[..len]);
+            input.advance(len);
+        }
+    }
+
+    pub(super) fn flush(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        if let Some(oversized) = &mut self.oversized {
+            return oversized.decoder.flush(output);
+        }
+
+        let written = output.written_len();
+        self.drain_or_block(output, written)
+    }
+
+    pub(super) fn finish(&mut self, output: &mut WriteBuffer<'_>) -> io::Result<bool> {
+        let written = output.written_len();
+
+        loop {
+            if self.oversized.is_some()
+                && !self.decode_oversized(&mut PartialBuffer::new(&[][..]), output)?
+            {
+                if output.has_no_spare_space() {
+                    return Ok(false);
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "zstd stream did not finish",
+                ));
+            }
+
+            while self.pending.len() < self.max_pending && self.split_frame(true)? {}
+
+            if !self.drain_or_block(output, written)? {
+                return Ok(false);
+            }
+
+            if self.buffer.is_empty() {
+                return Ok(true);
+            }
+        }
+    }
+}